@@ -1,6 +1,7 @@
 use crate::api_token::{ApiTokenCmd, Error as ApiTokenError};
 use crate::csv::loaders::{CsvDataCmd, Error as CsvDataError};
 use crate::init_db::{Db, Error as DbError};
+use crate::snapshot::{Error as SnapshotError, SnapshotCmd};
 use crate::task::ExecTask;
 use structopt::StructOpt;
 use thiserror::Error;
@@ -13,6 +14,8 @@ pub enum Error {
     CsvData(#[from] CsvDataError),
     #[error(transparent)]
     Db(#[from] DbError),
+    #[error(transparent)]
+    Snapshot(#[from] SnapshotError),
 }
 
 #[derive(StructOpt)]
@@ -23,6 +26,8 @@ pub enum CliApp {
     CsvData(CsvDataCmd),
     /// DB related operations
     Db(Db),
+    /// Snapshot related operations
+    Snapshot(SnapshotCmd),
 }
 
 impl ExecTask for CliApp {
@@ -33,6 +38,7 @@ impl ExecTask for CliApp {
             CliApp::ApiToken(api_token) => api_token.exec()?,
             CliApp::CsvData(csv_data) => csv_data.exec()?,
             CliApp::Db(db_cmd) => db_cmd.exec()?,
+            CliApp::Snapshot(snapshot_cmd) => snapshot_cmd.exec()?,
         };
         Ok(())
     }
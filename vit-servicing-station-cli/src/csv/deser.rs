@@ -3,6 +3,7 @@ use serde::Deserializer;
 use std::fmt;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
+use vit_servicing_station_lib::db::models::challenges::ChallengeHighlights;
 
 pub fn deserialize_unix_timestamp_from_rfc3339<'de, D>(deserializer: D) -> Result<i64, D::Error>
 where
@@ -42,3 +43,48 @@ where
         .deserialize_str(Rfc3339Deserializer())
         .map(|datetime| datetime.unix_timestamp())
 }
+
+/// Challenge highlights are stored as json, an empty field means no highlights
+pub fn deserialize_highlights_from_json<'de, D>(
+    deserializer: D,
+) -> Result<Option<ChallengeHighlights>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct HighlightsDeserializer();
+
+    impl<'de> Visitor<'de> for HighlightsDeserializer {
+        type Value = Option<ChallengeHighlights>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("A json encoded challenge highlights string is needed")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            if value.trim().is_empty() {
+                return Ok(None);
+            }
+            serde_json::from_str(value)
+                .map_err(|e| E::custom(format!("Cannot parse challenge highlights: {}", e)))
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(HighlightsDeserializer())
+}
@@ -0,0 +1,559 @@
+use super::loaders::Error;
+use crate::db_utils::db_file_exists;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use structopt::StructOpt;
+use vit_servicing_station_lib::db::models::{
+    challenges::Challenge, community_advisors_reviews::AdvisorReview,
+    community_advisors_reviews::ReviewRanking, funds::Fund, goals::Goal, groups::Group, vote::Vote,
+    voteplans::Voteplan,
+};
+use vit_servicing_station_lib::db::schema::{
+    challenges, community_advisors_reviews, funds, goals, groups,
+    proposal_community_choice_challenge, proposal_simple_challenge, proposals, proposals_voteplans,
+    voteplans, votes,
+};
+use vit_servicing_station_lib::db::{load_db_connection_pool, DbConnection};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            s => Err(format!("Expected any of [csv | json], found: {}", s)),
+        }
+    }
+}
+
+/// Dumps the db data into the files read by `csv-data load`, loading them back into an empty
+/// db reproduces the same data.
+///
+/// Since `csv-data load` assigns every voteplan, proposal and goal to a single fund, the db is
+/// expected to hold all of them in one fund. That fund is written with `data_fund` set, so `load`
+/// assigns them back to it whatever the dates of the funds are.
+#[derive(Debug, Eq, PartialEq, StructOpt)]
+pub struct ExportCmd {
+    /// URL of the vit-servicing-station database to interact with
+    #[structopt(long = "db-url")]
+    db_url: String,
+
+    /// Output folder for the csv format (it is created if it does not exist),
+    /// output file for the json bundle format
+    #[structopt(long = "output")]
+    output: PathBuf,
+
+    /// Output format [csv | json]
+    #[structopt(long = "format", default_value = "csv")]
+    format: ExportFormat,
+}
+
+#[derive(Serialize)]
+struct FundRecord {
+    id: i32,
+    fund_name: String,
+    fund_goal: String,
+    voting_power_threshold: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    fund_start_time: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    fund_end_time: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    next_fund_start_time: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    registration_snapshot_time: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    next_registration_snapshot_time: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    insight_sharing_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    proposal_submission_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    refine_proposals_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    finalize_proposals_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    proposal_assessment_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    assessment_qa_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    snapshot_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    voting_start: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    voting_end: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    tallying_end: i64,
+    results_url: String,
+    survey_url: String,
+    snapshot_tag: Option<String>,
+    data_fund: bool,
+}
+
+#[derive(Serialize)]
+struct VoteplanRecord {
+    id: i32,
+    chain_voteplan_id: String,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    chain_vote_start_time: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    chain_vote_end_time: i64,
+    #[serde(
+        serialize_with = "vit_servicing_station_lib::utils::serde::serialize_unix_timestamp_as_rfc3339"
+    )]
+    chain_committee_end_time: i64,
+    chain_voteplan_payload: String,
+    chain_vote_encryption_key: String,
+    fund_id: i32,
+    token_identifier: String,
+}
+
+#[derive(Serialize)]
+struct ChallengeRecord {
+    id: i32,
+    challenge_type: String,
+    title: String,
+    description: String,
+    rewards_total: i64,
+    proposers_rewards: i64,
+    fund_id: i32,
+    challenge_url: String,
+    /// json encoded, csv fields cannot hold nested values
+    highlights: String,
+}
+
+#[derive(Serialize)]
+struct ProposalRecord {
+    proposal_id: String,
+    category_name: String,
+    proposal_title: String,
+    proposal_summary: String,
+    proposal_public_key: String,
+    proposal_funds: i64,
+    proposal_url: String,
+    proposal_files_url: String,
+    proposal_impact_score: i64,
+    proposer_name: String,
+    proposer_email: String,
+    proposer_url: String,
+    proposer_relevant_experience: String,
+    chain_proposal_id: String,
+    chain_vote_options: String,
+    chain_voteplan_id: String,
+    chain_proposal_index: i64,
+    challenge_id: i32,
+    proposal_solution: Option<String>,
+    proposal_brief: Option<String>,
+    proposal_importance: Option<String>,
+    proposal_goal: Option<String>,
+    proposal_metrics: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ReviewRecord {
+    id: i32,
+    proposal_id: i32,
+    assessor: String,
+    impact_alignment_rating_given: i32,
+    impact_alignment_note: String,
+    feasibility_rating_given: i32,
+    feasibility_note: String,
+    auditability_rating_given: i32,
+    auditability_note: String,
+    excellent: bool,
+    good: bool,
+    filtered_out: bool,
+}
+
+#[derive(Serialize)]
+struct GoalRecord {
+    goal_name: String,
+    fund_id: i32,
+}
+
+/// Json bundle, each field holds the records of the csv file with the same name
+#[derive(Serialize)]
+struct ExportBundle {
+    funds: Vec<FundRecord>,
+    voteplans: Vec<VoteplanRecord>,
+    challenges: Vec<ChallengeRecord>,
+    proposals: Vec<ProposalRecord>,
+    reviews: Vec<ReviewRecord>,
+    goals: Vec<GoalRecord>,
+    groups: Vec<Group>,
+    votes: Vec<Vote>,
+}
+
+type ProposalRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    i64,
+    String,
+    String,
+    i64,
+    String,
+    String,
+    String,
+    String,
+    Vec<u8>,
+    String,
+    i32,
+);
+
+impl From<Fund> for FundRecord {
+    fn from(fund: Fund) -> Self {
+        Self {
+            id: fund.id,
+            fund_name: fund.fund_name,
+            fund_goal: fund.fund_goal,
+            voting_power_threshold: fund.voting_power_threshold,
+            fund_start_time: fund.fund_start_time,
+            fund_end_time: fund.fund_end_time,
+            next_fund_start_time: fund.next_fund_start_time,
+            registration_snapshot_time: fund.registration_snapshot_time,
+            next_registration_snapshot_time: fund.next_registration_snapshot_time,
+            insight_sharing_start: fund.stage_dates.insight_sharing_start,
+            proposal_submission_start: fund.stage_dates.proposal_submission_start,
+            refine_proposals_start: fund.stage_dates.refine_proposals_start,
+            finalize_proposals_start: fund.stage_dates.finalize_proposals_start,
+            proposal_assessment_start: fund.stage_dates.proposal_assessment_start,
+            assessment_qa_start: fund.stage_dates.assessment_qa_start,
+            snapshot_start: fund.stage_dates.snapshot_start,
+            voting_start: fund.stage_dates.voting_start,
+            voting_end: fund.stage_dates.voting_end,
+            tallying_end: fund.stage_dates.tallying_end,
+            results_url: fund.results_url,
+            survey_url: fund.survey_url,
+            snapshot_tag: fund.snapshot_tag,
+            data_fund: false,
+        }
+    }
+}
+
+impl From<Voteplan> for VoteplanRecord {
+    fn from(voteplan: Voteplan) -> Self {
+        Self {
+            id: voteplan.id,
+            chain_voteplan_id: voteplan.chain_voteplan_id,
+            chain_vote_start_time: voteplan.chain_vote_start_time,
+            chain_vote_end_time: voteplan.chain_vote_end_time,
+            chain_committee_end_time: voteplan.chain_committee_end_time,
+            chain_voteplan_payload: voteplan.chain_voteplan_payload,
+            chain_vote_encryption_key: voteplan.chain_vote_encryption_key,
+            fund_id: voteplan.fund_id,
+            token_identifier: voteplan.token_identifier,
+        }
+    }
+}
+
+impl From<Challenge> for ChallengeRecord {
+    fn from(challenge: Challenge) -> Self {
+        Self {
+            id: challenge.id,
+            challenge_type: challenge.challenge_type.to_string(),
+            title: challenge.title,
+            description: challenge.description,
+            rewards_total: challenge.rewards_total,
+            proposers_rewards: challenge.proposers_rewards,
+            fund_id: challenge.fund_id,
+            challenge_url: challenge.challenge_url,
+            // the loader stores missing highlights as `null` too
+            highlights: serde_json::to_string(&challenge.highlights).unwrap(),
+        }
+    }
+}
+
+impl From<AdvisorReview> for ReviewRecord {
+    fn from(review: AdvisorReview) -> Self {
+        Self {
+            id: review.id,
+            proposal_id: review.proposal_id,
+            assessor: review.assessor,
+            impact_alignment_rating_given: review.impact_alignment_rating_given,
+            impact_alignment_note: review.impact_alignment_note,
+            feasibility_rating_given: review.feasibility_rating_given,
+            feasibility_note: review.feasibility_note,
+            auditability_rating_given: review.auditability_rating_given,
+            auditability_note: review.auditability_note,
+            excellent: review.ranking == ReviewRanking::Excellent,
+            good: review.ranking == ReviewRanking::Good,
+            filtered_out: review.ranking == ReviewRanking::FilteredOut,
+        }
+    }
+}
+
+impl From<Goal> for GoalRecord {
+    fn from(goal: Goal) -> Self {
+        Self {
+            goal_name: goal.goal_name,
+            fund_id: goal.fund_id,
+        }
+    }
+}
+
+fn query_proposals(db_conn: &DbConnection) -> Result<Vec<ProposalRecord>, Error> {
+    let rows: Vec<ProposalRow> = proposals::table
+        .select((
+            proposals::proposal_id,
+            proposals::proposal_category,
+            proposals::proposal_title,
+            proposals::proposal_summary,
+            proposals::proposal_public_key,
+            proposals::proposal_funds,
+            proposals::proposal_url,
+            proposals::proposal_files_url,
+            proposals::proposal_impact_score,
+            proposals::proposer_name,
+            proposals::proposer_contact,
+            proposals::proposer_url,
+            proposals::proposer_relevant_experience,
+            proposals::chain_proposal_id,
+            proposals::chain_vote_options,
+            proposals::challenge_id,
+        ))
+        // the loader inserts them in file order, which keeps the ids
+        .order(proposals::id.asc())
+        .load(db_conn)?;
+
+    let mut voteplans: HashMap<String, (String, i64)> = proposals_voteplans::table
+        .select((
+            proposals_voteplans::proposal_id,
+            proposals_voteplans::chain_voteplan_id,
+            proposals_voteplans::chain_proposal_index,
+        ))
+        .load::<(String, String, i64)>(db_conn)?
+        .into_iter()
+        .map(|(proposal_id, voteplan_id, index)| (proposal_id, (voteplan_id, index)))
+        .collect();
+
+    let mut simple: HashMap<String, Option<String>> = proposal_simple_challenge::table
+        .load::<(String, Option<String>)>(db_conn)?
+        .into_iter()
+        .collect();
+
+    #[allow(clippy::type_complexity)]
+    let mut community: HashMap<
+        String,
+        (
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        ),
+    > = proposal_community_choice_challenge::table
+        .load::<(
+            String,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        )>(db_conn)?
+        .into_iter()
+        .map(|(proposal_id, brief, importance, goal, metrics)| {
+            (proposal_id, (brief, importance, goal, metrics))
+        })
+        .collect();
+
+    rows.into_iter()
+        .map(|row| {
+            let proposal_id = row.0;
+            let chain_proposal_id = String::from_utf8(row.13).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Proposal {} chain_proposal_id is not utf8", proposal_id),
+                )
+            })?;
+            let (chain_voteplan_id, chain_proposal_index) =
+                voteplans.remove(&proposal_id).unwrap_or_default();
+            let proposal_solution = simple.remove(&proposal_id).flatten();
+            let (proposal_brief, proposal_importance, proposal_goal, proposal_metrics) =
+                community.remove(&proposal_id).unwrap_or_default();
+
+            Ok(ProposalRecord {
+                proposal_id,
+                category_name: row.1,
+                proposal_title: row.2,
+                proposal_summary: row.3,
+                proposal_public_key: row.4,
+                proposal_funds: row.5,
+                proposal_url: row.6,
+                proposal_files_url: row.7,
+                proposal_impact_score: row.8,
+                proposer_name: row.9,
+                proposer_email: row.10,
+                proposer_url: row.11,
+                proposer_relevant_experience: row.12,
+                chain_proposal_id,
+                chain_vote_options: row.14,
+                chain_voteplan_id,
+                chain_proposal_index,
+                challenge_id: row.15,
+                proposal_solution,
+                proposal_brief,
+                proposal_importance,
+                proposal_goal,
+                proposal_metrics,
+            })
+        })
+        .collect()
+}
+
+impl ExportCmd {
+    fn query_bundle(&self) -> Result<ExportBundle, Error> {
+        db_file_exists(&self.db_url)?;
+        let pool = load_db_connection_pool(&self.db_url)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
+        let db_conn = pool
+            .get()
+            .map_err(|e| io::Error::new(io::ErrorKind::NotConnected, format!("{}", e)))?;
+
+        let funds: Vec<Fund> = funds::table.order(funds::id.asc()).load(&db_conn)?;
+        let voteplans: Vec<Voteplan> =
+            voteplans::table.order(voteplans::id.asc()).load(&db_conn)?;
+        let goals: Vec<Goal> = goals::table.order(goals::id.asc()).load(&db_conn)?;
+
+        let voteplans_funds: BTreeSet<i32> = voteplans
+            .iter()
+            .map(|voteplan| voteplan.fund_id)
+            .chain(goals.iter().map(|goal| goal.fund_id))
+            .collect();
+        if voteplans_funds.len() > 1 {
+            return Err(Error::InvalidFundData(format!(
+                "voteplans and goals belong to several funds {:?}, they cannot be loaded back",
+                voteplans_funds
+            )));
+        }
+        let data_fund = voteplans_funds.into_iter().next();
+        if let Some(fund_id) = data_fund {
+            if !funds.iter().any(|fund| fund.id == fund_id) {
+                return Err(Error::InvalidFundData(format!(
+                    "fund {} not found",
+                    fund_id
+                )));
+            }
+        }
+        let funds = funds
+            .into_iter()
+            .map(|fund| FundRecord {
+                data_fund: Some(fund.id) == data_fund,
+                ..fund.into()
+            })
+            .collect();
+
+        let challenges: Vec<Challenge> = challenges::table
+            .order(challenges::internal_id.asc())
+            .load(&db_conn)?;
+        let reviews: Vec<AdvisorReview> = community_advisors_reviews::table
+            .order(community_advisors_reviews::id.asc())
+            .load(&db_conn)?;
+        let groups: Vec<Group> = groups::table
+            .order((groups::fund_id.asc(), groups::token_identifier.asc()))
+            .load(&db_conn)?;
+        let votes: Vec<Vote> = votes::table
+            .order(votes::fragment_id.asc())
+            .load(&db_conn)?;
+
+        Ok(ExportBundle {
+            funds,
+            voteplans: voteplans.into_iter().map(Into::into).collect(),
+            challenges: challenges.into_iter().map(Into::into).collect(),
+            proposals: query_proposals(&db_conn)?,
+            reviews: reviews.into_iter().map(Into::into).collect(),
+            goals: goals.into_iter().map(Into::into).collect(),
+            groups,
+            votes,
+        })
+    }
+
+    fn write_csv<T: Serialize>(path: &Path, records: &[T]) -> Result<(), Error> {
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(true)
+            .from_path(path)?;
+        for record in records {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn write_csv_files(&self, bundle: &ExportBundle) -> Result<(), Error> {
+        let votes_folder = self.output.join("votes");
+        fs::create_dir_all(&votes_folder)?;
+
+        Self::write_csv(&self.output.join("funds.csv"), &bundle.funds)?;
+        Self::write_csv(&self.output.join("voteplans.csv"), &bundle.voteplans)?;
+        Self::write_csv(&self.output.join("challenges.csv"), &bundle.challenges)?;
+        Self::write_csv(&self.output.join("proposals.csv"), &bundle.proposals)?;
+        Self::write_csv(&self.output.join("reviews.csv"), &bundle.reviews)?;
+        Self::write_csv(&self.output.join("goals.csv"), &bundle.goals)?;
+        Self::write_csv(&self.output.join("groups.csv"), &bundle.groups)?;
+        Self::write_csv(&votes_folder.join("votes.csv"), &bundle.votes)?;
+        Ok(())
+    }
+
+    fn write_json_bundle(&self, bundle: &ExportBundle) -> Result<(), Error> {
+        if let Some(parent) = self.output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::File::create(&self.output)?;
+        serde_json::to_writer_pretty(io::BufWriter::new(file), bundle)?;
+        Ok(())
+    }
+
+    pub fn exec(&self) -> Result<(), Error> {
+        let bundle = self.query_bundle()?;
+        match self.format {
+            ExportFormat::Csv => self.write_csv_files(&bundle),
+            ExportFormat::Json => self.write_json_bundle(&bundle),
+        }
+    }
+}
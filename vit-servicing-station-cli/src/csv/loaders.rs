@@ -1,3 +1,5 @@
+use super::exporters::ExportCmd;
+use super::validation::{check_schedule, RecordSources, ValidateCmd};
 use crate::db_utils::{backup_db_file, restore_db_file};
 use crate::{db_utils::db_file_exists, task::ExecTask};
 use csv::Trim;
@@ -14,6 +16,7 @@ use std::str::FromStr;
 use std::{fs, io};
 use structopt::StructOpt;
 use thiserror::Error;
+use time::OffsetDateTime;
 use vit_servicing_station_lib::db;
 use vit_servicing_station_lib::db::models::goals::InsertGoal;
 use vit_servicing_station_lib::db::models::groups::Group;
@@ -22,6 +25,7 @@ use vit_servicing_station_lib::db::models::proposals::{
 };
 use vit_servicing_station_lib::db::models::vote::Vote;
 use vit_servicing_station_lib::db::schema::community_advisors_reviews as community_advisors_reviews_dsl;
+use vit_servicing_station_lib::db::validation::ScheduleViolation;
 use vit_servicing_station_lib::db::{
     load_db_connection_pool,
     models::{proposals::Proposal, voteplans::Voteplan},
@@ -40,6 +44,20 @@ pub enum Error {
 
     #[error(transparent)]
     Diesel(#[from] diesel::result::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error("Invalid fund schedule, {} violations found:\n{}", .0.len(), display_violations(.0))]
+    InvalidSchedule(Vec<ScheduleViolation>),
+}
+
+fn display_violations(violations: &[ScheduleViolation]) -> String {
+    violations
+        .iter()
+        .map(|violation| format!("\t{}", violation))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 #[derive(Debug, Eq, PartialEq, StructOpt)]
 pub struct LoadCmd {
@@ -52,10 +70,9 @@ pub struct LoadCmd {
     db_url: String,
 
     /// Path to the csv containing funds information
-    /// At the moment, it's required these are ordered.
     ///
-    /// Also the first fund being the current one, which means previous funds should not be
-    /// included. This restriction may be lifted in the future.
+    /// Past funds may be included, the voteplans, proposals and goals are assigned to the fund
+    /// running at load time, or else to the next one to start, or else to the latest one.
     #[structopt(long = "funds")]
     funds: PathBuf,
 
@@ -88,10 +105,50 @@ pub struct LoadCmd {
     votes: Option<PathBuf>,
 }
 
+/// Load the json bundle written by `csv-data export --format json`
+#[derive(Debug, Eq, PartialEq, StructOpt)]
+pub struct LoadBundleCmd {
+    /// Additional import settings
+    #[structopt(long = "additional-settings")]
+    settings: Option<PathBuf>,
+
+    /// URL of the vit-servicing-station database to interact with
+    #[structopt(long = "db-url")]
+    db_url: String,
+
+    /// Path to the json bundle
+    #[structopt(long = "bundle")]
+    bundle: PathBuf,
+}
+
 #[derive(Debug, Eq, PartialEq, StructOpt)]
 pub enum CsvDataCmd {
     /// Loads data into db
     Load(LoadCmd),
+    /// Loads a json bundle into db
+    LoadBundle(LoadBundleCmd),
+    /// Exports db data into the files accepted by `load` or `load-bundle`
+    Export(ExportCmd),
+    /// Checks the schedule of the funds, voteplans and challenges files accepted by `load`
+    Validate(ValidateCmd),
+}
+
+/// Every entity read by the load commands, the csv files of `LoadCmd` hold
+/// one of these fields each while the json bundle holds all of them.
+#[derive(Deserialize)]
+struct DataBundle {
+    funds: Vec<super::models::Fund>,
+    voteplans: Vec<super::models::Voteplan>,
+    challenges: Vec<super::models::Challenge>,
+    proposals: Vec<super::models::Proposal>,
+    reviews: Vec<super::models::AdvisorReview>,
+    #[serde(default)]
+    goals: Vec<InsertGoal>,
+    /// when missing, every voteplan token gets a `direct` group
+    #[serde(default)]
+    groups: Option<Vec<Group>>,
+    #[serde(default)]
+    votes: Vec<Vote>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -106,12 +163,15 @@ impl ExecTask for CsvDataCmd {
     fn exec(&self) -> Result<(), Error> {
         match self {
             Self::Load(load) => load.exec(),
+            Self::LoadBundle(load_bundle) => load_bundle.exec(),
+            Self::Export(export) => export.exec(),
+            Self::Validate(validate) => validate.exec(),
         }
     }
 }
 
 impl LoadCmd {
-    fn load_from_csv<T: DeserializeOwned>(csv_path: &Path) -> io::Result<Vec<T>> {
+    pub(super) fn load_from_csv<T: DeserializeOwned>(csv_path: &Path) -> io::Result<Vec<T>> {
         let mut reader = csv::ReaderBuilder::new()
             .flexible(true)
             .has_headers(true)
@@ -140,213 +200,36 @@ impl LoadCmd {
         Ok(results)
     }
 
-    fn handle_load(&self) -> Result<(), Error> {
-        let settings = {
-            if let Some(path) = &self.settings {
-                let file = File::open(path)?;
-                let reader = BufReader::new(file);
-                serde_json::from_reader(reader)?
-            } else {
-                ImportSettings::default()
-            }
-        };
-
-        db_file_exists(&self.db_url)?;
-        let mut funds = LoadCmd::load_from_csv::<super::models::Fund>(&self.funds)?;
-
-        if let Some(override_fund_id) = settings.force_fund_id {
-            funds.iter_mut().for_each(|x| x.id = override_fund_id);
-        }
-
-        let mut voteplans: Vec<Voteplan> =
-            LoadCmd::load_from_csv::<super::models::Voteplan>(&self.voteplans)?
-                .into_iter()
-                .map(|x| x.try_into().unwrap())
-                .collect();
-        let mut challenges = LoadCmd::load_from_csv::<super::models::Challenge>(&self.challenges)?;
-
-        let csv_proposals = LoadCmd::load_from_csv::<super::models::Proposal>(&self.proposals)?;
-        let mut reviews = LoadCmd::load_from_csv::<super::models::AdvisorReview>(&self.reviews)?
-            .into_iter()
-            .map(TryInto::try_into)
-            .collect::<Result<Vec<db::models::community_advisors_reviews::AdvisorReview>, _>>()
-            .unwrap();
-
-        let mut goals: Vec<InsertGoal> = if let Some(goals_path) = &self.goals {
+    fn read_csv_files(&self) -> Result<DataBundle, Error> {
+        let goals = if let Some(goals_path) = &self.goals {
             LoadCmd::load_from_csv::<InsertGoal>(goals_path)?
         } else {
             vec![]
         };
-
-        let mut proposals: Vec<Proposal> = Vec::new();
-        let mut simple_proposals_data: Vec<simple::ChallengeSqlValues> = Vec::new();
-        let mut community_proposals_data: Vec<community_choice::ChallengeSqlValues> = Vec::new();
-
-        for proposal in csv_proposals.clone() {
-            let challenge_type = challenges
-                .iter()
-                .find(|c| proposal.challenge_id == c.id)
-                .ok_or_else(|| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Challenge with id {} not found", proposal.challenge_id),
-                    )
-                })?
-                .challenge_type
-                .clone();
-
-            let (proposal, challenge_info) =
-                proposal.into_db_proposal_and_challenge_info(challenge_type)?;
-            match challenge_info {
-                ProposalChallengeInfo::Simple(simple) => simple_proposals_data
-                    .push(simple.to_sql_values_with_proposal_id(&proposal.proposal_id)),
-                ProposalChallengeInfo::CommunityChoice(community_choice) => {
-                    community_proposals_data.push(
-                        community_choice.to_sql_values_with_proposal_id(&proposal.proposal_id),
-                    )
-                }
-            };
-            proposals.push(proposal);
-        }
-
-        // start db connection
-        let pool = load_db_connection_pool(&self.db_url)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
-        let db_conn = pool
-            .get()
-            .map_err(|e| io::Error::new(io::ErrorKind::NotConnected, format!("{}", e)))?;
-
-        let mut funds_iter = funds.into_iter().map(|x| x.try_into().unwrap());
-
-        // insert fund and retrieve fund with id
-        let fund = db::queries::funds::insert_fund(
-            funds_iter
-                .next()
-                .ok_or_else(|| Error::InvalidFundData(self.funds.to_string_lossy().to_string()))?,
-            &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
-        for fund in funds_iter {
-            vit_servicing_station_lib::db::queries::funds::insert_fund(fund, &db_conn)
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-        }
-
-        // apply fund id in voteplans
-        for voteplan in voteplans.iter_mut() {
-            voteplan.fund_id = fund.id;
-        }
-
-        let new_challenge_ids: HashMap<i32, i32> = challenges
-            .iter()
-            .map(|c| {
-                let url = url::Url::from_str(&c.challenge_url).unwrap();
-                //format: https://cardano.ideascale.com/c/campaigns/XXXXX/
-                let paths_segments = url.path_segments().map(|c| c.collect::<Vec<_>>()).unwrap();
-                let id = paths_segments.get(2).unwrap();
-                (c.id, id.parse().unwrap())
-            })
-            .collect();
-
-        // apply fund id to challenges
-        for challenge in challenges.iter_mut() {
-            challenge.id = *new_challenge_ids.get(&challenge.id).unwrap();
-        }
-
-        // apply fund id in proposals
-        for proposal in proposals.iter_mut() {
-            proposal.fund_id = fund.id;
-            proposal.challenge_id = *new_challenge_ids.get(&proposal.challenge_id).unwrap();
-            proposal.internal_id = proposal.proposal_id.parse().unwrap();
-        }
-
-        for goal in goals.iter_mut() {
-            goal.fund_id = fund.id;
-        }
-
-        let advisors = db::schema::community_advisors_reviews::dsl::community_advisors_reviews
-            .select(community_advisors_reviews_dsl::id)
-            .load::<i32>(&db_conn)?;
-        let max_id = advisors.iter().max().unwrap_or(&0i32);
-
-        for review in reviews.iter_mut() {
-            review.id += max_id;
-        }
-
-        vit_servicing_station_lib::db::queries::voteplans::batch_insert_voteplans(
-            &voteplans, &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
-        vit_servicing_station_lib::db::queries::proposals::batch_insert_proposals(
-            &proposals, &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
-        let proposals_voteplans = csv_proposals
-            .iter()
-            .cloned()
-            .map(|proposal| ProposalVotePlan {
-                proposal_id: proposal.proposal_id.clone(),
-                common: ProposalVotePlanCommon {
-                    chain_voteplan_id: proposal.chain_voteplan_id.to_string(),
-                    chain_proposal_index: proposal.chain_proposal_index,
-                },
-            });
-
-        vit_servicing_station_lib::db::queries::proposals::batch_insert_proposals_voteplans(
-            proposals_voteplans,
-            &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
-        vit_servicing_station_lib::db::queries::proposals::batch_insert_simple_challenge_data(
-            &simple_proposals_data,
-            &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
-        vit_servicing_station_lib::db::queries::proposals::batch_insert_community_choice_challenge_data(
-            &community_proposals_data,
-            &db_conn,
-        )
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
-        vit_servicing_station_lib::db::queries::challenges::batch_insert_challenges(
-            &challenges
-                .into_iter()
-                .map(|c| c.into_db_challenge_values())
-                .collect::<Vec<_>>(),
-            &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
-        vit_servicing_station_lib::db::queries::community_advisors_reviews::batch_insert_advisor_reviews(&reviews, &db_conn)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-
         let groups = if let Some(groups_path) = &self.groups {
-            LoadCmd::load_from_csv::<vit_servicing_station_lib::db::models::groups::Group>(
-                groups_path,
-            )?
+            Some(LoadCmd::load_from_csv::<Group>(groups_path)?)
         } else {
-            voteplans
-                .iter()
-                .cloned()
-                .map(|v| Group {
-                    fund_id: v.fund_id,
-                    token_identifier: v.token_identifier,
-                    group_id: "direct".to_string(),
-                })
-                .collect()
+            None
         };
 
-        vit_servicing_station_lib::db::queries::groups::batch_insert(
-            &groups.into_iter().map(|c| c.values()).collect::<Vec<_>>(),
-            &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+        Ok(DataBundle {
+            funds: LoadCmd::load_from_csv(&self.funds)?,
+            voteplans: LoadCmd::load_from_csv(&self.voteplans)?,
+            challenges: LoadCmd::load_from_csv(&self.challenges)?,
+            proposals: LoadCmd::load_from_csv(&self.proposals)?,
+            reviews: LoadCmd::load_from_csv(&self.reviews)?,
+            goals,
+            groups,
+            // votes are loaded on their own afterwards
+            votes: vec![],
+        })
+    }
 
-        Ok(())
+    fn handle_load(&self) -> Result<(), Error> {
+        let settings = load_import_settings(self.settings.as_deref())?;
+        db_file_exists(&self.db_url)?;
+        let sources = RecordSources::csv_files(&self.funds, &self.voteplans, &self.challenges);
+        load_data(&self.db_url, &settings, self.read_csv_files()?, &sources)
     }
 
     fn list_of_csv_paths<P: AsRef<Path>>(root: P) -> io::Result<Vec<PathBuf>> {
@@ -370,19 +253,7 @@ impl LoadCmd {
             votes.extend(LoadCmd::load_from_csv::<Vote>(&csv_file)?);
         }
 
-        // start db connection
-        let pool = load_db_connection_pool(db_url)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
-        let db_conn = pool
-            .get()
-            .map_err(|e| io::Error::new(io::ErrorKind::NotConnected, format!("{}", e)))?;
-
-        vit_servicing_station_lib::db::queries::votes::batch_insert_votes_data(
-            &votes.into_iter().map(|c| c.values()).collect::<Vec<_>>(),
-            &db_conn,
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
-        Ok(())
+        load_votes(db_url, votes)
     }
 
     fn handle_load_with_db_backup(&self) -> Result<(), Error> {
@@ -410,3 +281,309 @@ impl LoadCmd {
         Ok(())
     }
 }
+
+impl LoadBundleCmd {
+    fn handle_load(&self) -> Result<(), Error> {
+        let settings = load_import_settings(self.settings.as_deref())?;
+        db_file_exists(&self.db_url)?;
+        // some field deserializers borrow their input, so the whole file is read first
+        let content = fs::read_to_string(&self.bundle)?;
+        let mut bundle: DataBundle = serde_json::from_str(&content)?;
+        let votes = std::mem::take(&mut bundle.votes);
+        load_data(
+            &self.db_url,
+            &settings,
+            bundle,
+            &RecordSources::bundle(&self.bundle),
+        )?;
+        if !votes.is_empty() {
+            load_votes(&self.db_url, votes)?;
+        }
+        Ok(())
+    }
+
+    pub fn exec(&self) -> Result<(), Error> {
+        let backup_file = backup_db_file(&self.db_url)?;
+        if let Err(e) = self.handle_load() {
+            restore_db_file(backup_file, &self.db_url)?;
+            Err(e)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub(super) fn load_import_settings(path: Option<&Path>) -> Result<ImportSettings, Error> {
+    if let Some(path) = path {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    } else {
+        Ok(ImportSettings::default())
+    }
+}
+
+/// Position of the fund the voteplans, proposals and goals are assigned to: the one running at
+/// `now`, otherwise the next one to start, otherwise the latest one.
+fn data_fund_position(funds: &[db::models::funds::Fund], now: i64) -> Option<usize> {
+    let running = funds
+        .iter()
+        .position(|fund| fund.fund_start_time <= now && now < fund.fund_end_time);
+    let upcoming = || {
+        funds
+            .iter()
+            .enumerate()
+            .filter(|(_, fund)| fund.fund_start_time > now)
+            .min_by_key(|(_, fund)| fund.fund_start_time)
+            .map(|(position, _)| position)
+    };
+    let latest = || {
+        funds
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, fund)| fund.fund_end_time)
+            .map(|(position, _)| position)
+    };
+    running.or_else(upcoming).or_else(latest)
+}
+
+/// Apply the import settings to the funds and convert them, along with the position of the fund
+/// the voteplans, proposals and goals are assigned to: the one marked as `data_fund` if any,
+/// otherwise the one picked by its dates
+pub(super) fn prepare_funds(
+    settings: &ImportSettings,
+    mut funds: Vec<super::models::Fund>,
+) -> Result<(Vec<db::models::funds::Fund>, usize), Error> {
+    if let Some(override_fund_id) = settings.force_fund_id {
+        funds.iter_mut().for_each(|x| x.id = override_fund_id);
+    }
+
+    let marked: Vec<usize> = funds
+        .iter()
+        .enumerate()
+        .filter(|(_, fund)| fund.data_fund)
+        .map(|(position, _)| position)
+        .collect();
+    if marked.len() > 1 {
+        return Err(Error::InvalidFundData(format!(
+            "{} funds are marked as data_fund, at most one can be",
+            marked.len()
+        )));
+    }
+
+    let funds: Vec<db::models::funds::Fund> = funds
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()?;
+    let data_fund = match marked.first() {
+        Some(position) => *position,
+        None => data_fund_position(&funds, OffsetDateTime::now_utc().unix_timestamp())
+            .ok_or_else(|| Error::InvalidFundData("no fund to load".to_string()))?,
+    };
+    Ok((funds, data_fund))
+}
+
+fn load_data(
+    db_url: &str,
+    settings: &ImportSettings,
+    data: DataBundle,
+    sources: &RecordSources,
+) -> Result<(), Error> {
+    let DataBundle {
+        funds,
+        voteplans,
+        mut challenges,
+        proposals: csv_proposals,
+        reviews,
+        mut goals,
+        groups,
+        votes: _,
+    } = data;
+
+    let (funds, data_fund) = prepare_funds(settings, funds)?;
+
+    let mut voteplans: Vec<Voteplan> = voteplans
+        .into_iter()
+        .map(|x| x.try_into().unwrap())
+        .collect();
+
+    check_schedule(&funds, data_fund, &voteplans, &challenges, sources)?;
+
+    let mut reviews = reviews
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<db::models::community_advisors_reviews::AdvisorReview>, _>>()
+        .unwrap();
+
+    let mut proposals: Vec<Proposal> = Vec::new();
+    let mut simple_proposals_data: Vec<simple::ChallengeSqlValues> = Vec::new();
+    let mut community_proposals_data: Vec<community_choice::ChallengeSqlValues> = Vec::new();
+
+    for proposal in csv_proposals.clone() {
+        let challenge_type = challenges
+            .iter()
+            .find(|c| proposal.challenge_id == c.id)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Challenge with id {} not found", proposal.challenge_id),
+                )
+            })?
+            .challenge_type
+            .clone();
+
+        let (proposal, challenge_info) =
+            proposal.into_db_proposal_and_challenge_info(challenge_type)?;
+        match challenge_info {
+            ProposalChallengeInfo::Simple(simple) => simple_proposals_data
+                .push(simple.to_sql_values_with_proposal_id(&proposal.proposal_id)),
+            ProposalChallengeInfo::CommunityChoice(community_choice) => community_proposals_data
+                .push(community_choice.to_sql_values_with_proposal_id(&proposal.proposal_id)),
+        };
+        proposals.push(proposal);
+    }
+
+    // start db connection
+    let pool = load_db_connection_pool(db_url)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
+    let db_conn = pool
+        .get()
+        .map_err(|e| io::Error::new(io::ErrorKind::NotConnected, format!("{}", e)))?;
+
+    // insert funds and retrieve the fund the data belongs to with its id
+    let mut fund = None;
+    for (position, data) in funds.into_iter().enumerate() {
+        let inserted = db::queries::funds::insert_fund(data, &db_conn)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+        if position == data_fund {
+            fund = Some(inserted);
+        }
+    }
+    let fund = fund.expect("the data fund is one of the loaded funds");
+
+    // apply fund id in voteplans
+    for voteplan in voteplans.iter_mut() {
+        voteplan.fund_id = fund.id;
+    }
+
+    let new_challenge_ids: HashMap<i32, i32> = challenges
+        .iter()
+        .map(|c| {
+            let url = url::Url::from_str(&c.challenge_url).unwrap();
+            //format: https://cardano.ideascale.com/c/campaigns/XXXXX/
+            let paths_segments = url.path_segments().map(|c| c.collect::<Vec<_>>()).unwrap();
+            let id = paths_segments.get(2).unwrap();
+            (c.id, id.parse().unwrap())
+        })
+        .collect();
+
+    // apply fund id to challenges
+    for challenge in challenges.iter_mut() {
+        challenge.id = *new_challenge_ids.get(&challenge.id).unwrap();
+    }
+
+    // apply fund id in proposals
+    for proposal in proposals.iter_mut() {
+        proposal.fund_id = fund.id;
+        proposal.challenge_id = *new_challenge_ids.get(&proposal.challenge_id).unwrap();
+        proposal.internal_id = proposal.proposal_id.parse().unwrap();
+    }
+
+    for goal in goals.iter_mut() {
+        goal.fund_id = fund.id;
+    }
+
+    let advisors = db::schema::community_advisors_reviews::dsl::community_advisors_reviews
+        .select(community_advisors_reviews_dsl::id)
+        .load::<i32>(&db_conn)?;
+    let max_id = advisors.iter().max().unwrap_or(&0i32);
+
+    for review in reviews.iter_mut() {
+        review.id += max_id;
+    }
+
+    vit_servicing_station_lib::db::queries::voteplans::batch_insert_voteplans(&voteplans, &db_conn)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    vit_servicing_station_lib::db::queries::proposals::batch_insert_proposals(&proposals, &db_conn)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    let proposals_voteplans = csv_proposals
+        .iter()
+        .cloned()
+        .map(|proposal| ProposalVotePlan {
+            proposal_id: proposal.proposal_id.clone(),
+            common: ProposalVotePlanCommon {
+                chain_voteplan_id: proposal.chain_voteplan_id.to_string(),
+                chain_proposal_index: proposal.chain_proposal_index,
+            },
+        });
+
+    vit_servicing_station_lib::db::queries::proposals::batch_insert_proposals_voteplans(
+        proposals_voteplans,
+        &db_conn,
+    )
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    vit_servicing_station_lib::db::queries::proposals::batch_insert_simple_challenge_data(
+        &simple_proposals_data,
+        &db_conn,
+    )
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    vit_servicing_station_lib::db::queries::proposals::batch_insert_community_choice_challenge_data(
+            &community_proposals_data,
+            &db_conn,
+        )
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    vit_servicing_station_lib::db::queries::challenges::batch_insert_challenges(
+        &challenges
+            .into_iter()
+            .map(|c| c.into_db_challenge_values())
+            .collect::<Vec<_>>(),
+        &db_conn,
+    )
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    vit_servicing_station_lib::db::queries::community_advisors_reviews::batch_insert_advisor_reviews(&reviews, &db_conn)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    let groups = if let Some(groups) = groups {
+        groups
+    } else {
+        voteplans
+            .iter()
+            .cloned()
+            .map(|v| Group {
+                fund_id: v.fund_id,
+                token_identifier: v.token_identifier,
+                group_id: "direct".to_string(),
+            })
+            .collect()
+    };
+
+    vit_servicing_station_lib::db::queries::groups::batch_insert(
+        &groups.into_iter().map(|c| c.values()).collect::<Vec<_>>(),
+        &db_conn,
+    )
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+
+    Ok(())
+}
+
+fn load_votes(db_url: &str, votes: Vec<Vote>) -> Result<(), Error> {
+    // start db connection
+    let pool = load_db_connection_pool(db_url)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
+    let db_conn = pool
+        .get()
+        .map_err(|e| io::Error::new(io::ErrorKind::NotConnected, format!("{}", e)))?;
+
+    vit_servicing_station_lib::db::queries::votes::batch_insert_votes_data(
+        &votes.into_iter().map(|c| c.values()).collect::<Vec<_>>(),
+        &db_conn,
+    )
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
+    Ok(())
+}
@@ -1,3 +1,5 @@
 pub mod deser;
+pub mod exporters;
 pub mod loaders;
 mod models;
+mod validation;
@@ -63,6 +63,11 @@ pub struct Fund {
     pub survey_url: String,
     #[serde(default = "BTreeSet::new")]
     pub groups: BTreeSet<Group>,
+    #[serde(default)]
+    pub snapshot_tag: Option<String>,
+    /// Assigns the voteplans, proposals and goals to this fund instead of picking it by its dates
+    #[serde(default)]
+    pub data_fund: bool,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Eq, Default)]
@@ -176,6 +181,7 @@ impl TryInto<db::models::funds::Fund> for Fund {
             results_url: self.results_url,
             survey_url: self.survey_url,
             groups: self.groups,
+            snapshot_tag: self.snapshot_tag,
         })
     }
 }
@@ -247,6 +253,8 @@ pub struct Challenge {
     pub fund_id: i32,
     #[serde(alias = "challengeUrl")]
     pub challenge_url: String,
+    #[serde(default)]
+    #[serde(deserialize_with = "crate::csv::deser::deserialize_highlights_from_json")]
     pub highlights: Option<ChallengeHighlights>,
 }
 
@@ -314,7 +322,7 @@ pub struct Proposal {
     pub chain_committee_end_time: i64,
     #[serde(alias = "chainVoteplanPayload", default = "Default::default")]
     pub chain_voteplan_payload: String,
-    #[serde(alias = "chainVoteplanPayload", default = "Default::default")]
+    #[serde(alias = "chainVoteplanId", default = "Default::default")]
     pub chain_voteplan_id: String,
     #[serde(alias = "chainVoteEncryptionKey", default = "Default::default")]
     pub chain_vote_encryption_key: String,
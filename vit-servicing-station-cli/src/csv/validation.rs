@@ -0,0 +1,155 @@
+use super::loaders::{load_import_settings, prepare_funds, Error, LoadCmd};
+use super::models;
+use std::convert::TryInto;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+use vit_servicing_station_lib::db::models::{
+    challenges::Challenge, funds::Fund, voteplans::Voteplan,
+};
+use vit_servicing_station_lib::db::validation::{
+    challenge_violations, fund_violations, voteplan_violations, ScheduleViolation,
+};
+
+/// Checks the schedule of the funds, voteplans and challenges files accepted by `load`
+/// without touching any db
+#[derive(Debug, Eq, PartialEq, StructOpt)]
+pub struct ValidateCmd {
+    /// Additional import settings
+    #[structopt(long = "additional-settings")]
+    settings: Option<PathBuf>,
+
+    /// Path to the csv containing funds information
+    #[structopt(long = "funds")]
+    funds: PathBuf,
+
+    /// Path to the csv containing voteplans information
+    #[structopt(long = "voteplans")]
+    voteplans: PathBuf,
+
+    /// Path to the csv containing challenges information
+    #[structopt(long = "challenges")]
+    challenges: PathBuf,
+}
+
+impl ValidateCmd {
+    pub fn exec(&self) -> Result<(), Error> {
+        let settings = load_import_settings(self.settings.as_deref())?;
+        let (funds, data_fund) = prepare_funds(&settings, LoadCmd::load_from_csv(&self.funds)?)?;
+        let voteplans = LoadCmd::load_from_csv::<models::Voteplan>(&self.voteplans)?
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<Voteplan>, _>>()?;
+        let challenges = LoadCmd::load_from_csv::<models::Challenge>(&self.challenges)?;
+
+        let sources = RecordSources::csv_files(&self.funds, &self.voteplans, &self.challenges);
+        check_schedule(&funds, data_fund, &voteplans, &challenges, &sources)?;
+        println!(
+            "{} funds, {} voteplans and {} challenges checked, no violation found",
+            funds.len(),
+            voteplans.len(),
+            challenges.len()
+        );
+        Ok(())
+    }
+}
+
+/// Where the records come from, to tell in which file and row a violation is found
+pub struct RecordSources {
+    funds: String,
+    voteplans: String,
+    challenges: String,
+}
+
+impl RecordSources {
+    pub fn csv_files(funds: &Path, voteplans: &Path, challenges: &Path) -> Self {
+        Self {
+            funds: funds.display().to_string(),
+            voteplans: voteplans.display().to_string(),
+            challenges: challenges.display().to_string(),
+        }
+    }
+
+    pub fn bundle(bundle: &Path) -> Self {
+        let source = |records: &str| format!("{} {}", bundle.display(), records);
+        Self {
+            funds: source("funds"),
+            voteplans: source("voteplans"),
+            challenges: source("challenges"),
+        }
+    }
+}
+
+/// Check the schedule of the records about to be loaded, failing with every violation found.
+///
+/// The voteplans are checked against the fund at `data_fund`, which they are assigned to. The
+/// fund of the challenges is only checked if every fund has an id, the other ones get it on insert.
+pub fn check_schedule(
+    funds: &[Fund],
+    data_fund: usize,
+    voteplans: &[Voteplan],
+    challenges: &[models::Challenge],
+    sources: &RecordSources,
+) -> Result<(), Error> {
+    let fund_ids: Option<Vec<i32>> = funds
+        .iter()
+        .map(|fund| Some(fund.id).filter(|id| *id != 0))
+        .collect();
+    let stage_dates = &funds[data_fund].stage_dates;
+    let challenges = challenges
+        .iter()
+        .cloned()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<Challenge>, _>>()?;
+
+    // rows are counted from 1, not including the csv header
+    let funds = funds.iter().enumerate().map(|(index, fund)| {
+        (
+            format!(
+                "{} row {} (fund {})",
+                sources.funds,
+                index + 1,
+                fund.fund_name
+            ),
+            fund_violations(fund),
+        )
+    });
+    let voteplans = voteplans.iter().enumerate().map(|(index, voteplan)| {
+        (
+            format!(
+                "{} row {} (voteplan {})",
+                sources.voteplans,
+                index + 1,
+                voteplan.chain_voteplan_id
+            ),
+            voteplan_violations(stage_dates, voteplan),
+        )
+    });
+    let challenges = challenges.iter().enumerate().map(|(index, challenge)| {
+        (
+            format!(
+                "{} row {} (challenge {})",
+                sources.challenges,
+                index + 1,
+                challenge.id
+            ),
+            challenge_violations(fund_ids.as_deref(), challenge),
+        )
+    });
+
+    let violations: Vec<ScheduleViolation> = funds
+        .chain(voteplans)
+        .chain(challenges)
+        .flat_map(|(location, reasons)| {
+            reasons.into_iter().map(move |reason| ScheduleViolation {
+                location: location.clone(),
+                reason,
+            })
+        })
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidSchedule(violations))
+    }
+}
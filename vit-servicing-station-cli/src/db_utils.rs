@@ -1,6 +1,5 @@
-use std::fs;
 use std::io;
-use std::io::{Read, Write};
+use vit_servicing_station_lib::db::backup::{backup_db, restore_db};
 
 pub fn db_file_exists(db_url: &str) -> io::Result<()> {
     // check if db file exists
@@ -15,45 +14,67 @@ pub fn db_file_exists(db_url: &str) -> io::Result<()> {
 
 pub fn backup_db_file(db_url: &str) -> io::Result<tempfile::NamedTempFile> {
     db_file_exists(db_url)?;
-    let mut tmp_file = tempfile::NamedTempFile::new()?;
-    let content = fs::read(db_url)?;
-    tmp_file.write_all(&content)?;
+    let tmp_file = tempfile::NamedTempFile::new()?;
+    backup_db(db_url, &tmp_file.path().to_string_lossy())
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
     Ok(tmp_file)
 }
 
 pub fn restore_db_file(backup_file: tempfile::NamedTempFile, db_url: &str) -> io::Result<()> {
-    let mut backup_file = backup_file.reopen()?;
-    let mut buff = Vec::new();
-    backup_file.read_to_end(&mut buff)?;
-    fs::write(db_url, &buff)
+    restore_db(&backup_file.path().to_string_lossy(), db_url)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
 }
 
 #[cfg(test)]
 mod test {
     use crate::db_utils::{backup_db_file, restore_db_file};
-    use std::{fs, io};
+    use diesel::{QueryDsl, RunQueryDsl};
+    use std::io;
+    use vit_servicing_station_lib::db::{
+        load_db_connection_pool, migrations::initialize_db_with_migration,
+        queries::api_tokens::insert_token_data, schema::api_tokens, DbConnectionPool,
+    };
+    use vit_servicing_station_lib::{
+        db::models::api_tokens::ApiTokenData, v0::api_token::ApiToken,
+    };
+
+    fn insert_token(token: &[u8], pool: &DbConnectionPool) {
+        let token_data = ApiTokenData {
+            token: ApiToken::new(token.to_vec()),
+            creation_time: 0,
+            expire_time: 0,
+        };
+        insert_token_data(token_data, &pool.get().unwrap()).unwrap();
+    }
+
+    fn count_tokens(pool: &DbConnectionPool) -> i64 {
+        api_tokens::table
+            .count()
+            .get_result(&pool.get().unwrap())
+            .unwrap()
+    }
 
     #[test]
     fn backup_file() -> io::Result<()> {
-        let file_path = "./tmp_db.db";
-        let content = b"foo bar";
-        let content_vec = content.to_vec();
-        // create a file with some content
-        fs::write(file_path, content)?;
+        let tmp_dir = tempfile::tempdir()?;
+        let file_path = tmp_dir.path().join("tmp_db.db");
+        let file_path = file_path.to_str().unwrap();
+
+        // create a db with some content
+        let pool = load_db_connection_pool(file_path).unwrap();
+        initialize_db_with_migration(&pool.get().unwrap());
+        insert_token(b"foo bar", &pool);
 
-        // backup the file
+        // backup the db
         let tmp_file = backup_db_file(file_path)?;
 
-        // write nonsense in old file
-        fs::write(file_path, b"bar foo")?;
+        // write more data in the old db
+        insert_token(b"bar foo", &pool);
+        assert_eq!(count_tokens(&pool), 2);
 
-        // restore file and read content, hopefully is the old one
+        // restore db and read content, hopefully is the old one
         restore_db_file(tmp_file, file_path)?;
-        let backup_content = fs::read(file_path)?;
-        fs::remove_file(file_path)?;
-
-        // check written and actual content
-        assert_eq!(&content_vec, &backup_content);
+        assert_eq!(count_tokens(&pool), 1);
 
         Ok(())
     }
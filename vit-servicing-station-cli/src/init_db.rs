@@ -2,7 +2,14 @@ use crate::task::ExecTask;
 use structopt::StructOpt;
 use thiserror::Error;
 use vit_servicing_station_lib::db::{
-    load_db_connection_pool, migrations::initialize_db_with_migration, Error as DbPoolError,
+    backup::{backup_db, restore_db, BackupError},
+    integrity::check_integrity,
+    load_db_connection_pool,
+    migrations::{
+        initialize_db_with_migration, migrations_status, revert_migrations, run_pending_migrations,
+        RunMigrationsError,
+    },
+    Error as DbPoolError,
 };
 
 #[derive(Error, Debug)]
@@ -12,6 +19,21 @@ pub enum Error {
 
     #[error("Error connecting to db")]
     DbConnectionError(#[from] r2d2::Error),
+
+    #[error("Error running migrations")]
+    MigrationsError(#[from] RunMigrationsError),
+
+    #[error(transparent)]
+    BackupError(#[from] BackupError),
+
+    #[error("Error querying db")]
+    QueryError(#[from] diesel::result::Error),
+
+    #[error("Error serializing integrity report")]
+    SerializeError(#[from] serde_json::Error),
+
+    #[error("DB integrity check failed with {0} errors")]
+    IntegrityError(usize),
 }
 
 #[derive(Debug, PartialEq, Eq, StructOpt)]
@@ -22,6 +44,55 @@ pub enum Db {
         #[structopt(long = "db-url")]
         db_url: String,
     },
+    /// Print the applied and pending migrations of an existing DB
+    Status {
+        /// URL of the vit-servicing-station database to interact with
+        #[structopt(long = "db-url")]
+        db_url: String,
+    },
+    /// Apply the pending migrations to an existing DB
+    Migrate {
+        /// URL of the vit-servicing-station database to interact with
+        #[structopt(long = "db-url")]
+        db_url: String,
+    },
+    /// Revert the last applied migrations of an existing DB
+    Rollback {
+        /// URL of the vit-servicing-station database to interact with
+        #[structopt(long = "db-url")]
+        db_url: String,
+
+        /// Number of migrations to revert
+        #[structopt(short = "n", long = "count", default_value = "1")]
+        count: usize,
+    },
+    /// Write a consistent copy of a DB, it is safe to run while the server is using the DB
+    Backup {
+        /// URL of the vit-servicing-station database to interact with
+        #[structopt(long = "db-url")]
+        db_url: String,
+
+        /// Path of the backup file
+        #[structopt(long = "out")]
+        out: String,
+    },
+    /// Overwrite a DB with the content of a backup
+    Restore {
+        /// URL of the vit-servicing-station database to interact with
+        #[structopt(long = "db-url")]
+        db_url: String,
+
+        /// Path of the backup file
+        #[structopt(long = "backup")]
+        backup: String,
+    },
+    /// Look for broken references between tables and print a JSON report,
+    /// fails if any is found
+    Check {
+        /// URL of the vit-servicing-station database to interact with
+        #[structopt(long = "db-url")]
+        db_url: String,
+    },
 }
 
 impl Db {
@@ -31,6 +102,49 @@ impl Db {
         initialize_db_with_migration(&db_conn);
         Ok(())
     }
+
+    fn status(db_url: &str) -> Result<(), Error> {
+        let pool = load_db_connection_pool(db_url)?;
+        let db_conn = pool.get()?;
+        for migration in migrations_status(&db_conn)? {
+            let status = if migration.applied {
+                "applied"
+            } else {
+                "pending"
+            };
+            println!("[{}] {}", status, migration.name);
+        }
+        Ok(())
+    }
+
+    fn migrate(db_url: &str) -> Result<(), Error> {
+        let pool = load_db_connection_pool(db_url)?;
+        let db_conn = pool.get()?;
+        for migration in run_pending_migrations(&db_conn)? {
+            println!("applied {}", migration);
+        }
+        Ok(())
+    }
+
+    fn rollback(db_url: &str, count: usize) -> Result<(), Error> {
+        let pool = load_db_connection_pool(db_url)?;
+        let db_conn = pool.get()?;
+        for migration in revert_migrations(&db_conn, count)? {
+            println!("reverted {}", migration);
+        }
+        Ok(())
+    }
+
+    fn check(db_url: &str) -> Result<(), Error> {
+        let pool = load_db_connection_pool(db_url)?;
+        let db_conn = pool.get()?;
+        let report = check_integrity(&db_conn)?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.is_ok() {
+            return Err(Error::IntegrityError(report.errors_count()));
+        }
+        Ok(())
+    }
 }
 
 impl ExecTask for Db {
@@ -39,6 +153,12 @@ impl ExecTask for Db {
     fn exec(&self) -> Result<Self::ResultValue, Error> {
         match self {
             Db::Init { db_url } => Db::init_with_migrations(db_url),
+            Db::Status { db_url } => Db::status(db_url),
+            Db::Migrate { db_url } => Db::migrate(db_url),
+            Db::Rollback { db_url, count } => Db::rollback(db_url, *count),
+            Db::Backup { db_url, out } => backup_db(db_url, out).map_err(Error::from),
+            Db::Restore { db_url, backup } => restore_db(backup, db_url).map_err(Error::from),
+            Db::Check { db_url } => Db::check(db_url),
         }
     }
 }
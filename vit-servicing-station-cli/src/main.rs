@@ -3,6 +3,7 @@ mod app;
 mod csv;
 mod db_utils;
 mod init_db;
+mod snapshot;
 mod task;
 
 use app::*;
@@ -0,0 +1,57 @@
+use crate::task::ExecTask;
+use structopt::StructOpt;
+use thiserror::Error;
+use vit_servicing_station_lib::{
+    db::{load_db_connection_pool, queries::snapshot::delete_snapshot, Error as DbPoolError},
+    v0::errors::HandleError,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Error connecting db pool")]
+    DbPoolError(#[from] DbPoolError),
+
+    #[error("Error connecting to db")]
+    DbConnectionError(#[from] r2d2::Error),
+
+    #[error(transparent)]
+    QueryError(#[from] HandleError),
+
+    #[error("Snapshot tag {0} not found")]
+    TagNotFound(String),
+}
+
+#[derive(Debug, PartialEq, Eq, StructOpt)]
+pub enum SnapshotCmd {
+    /// Delete a snapshot tag along with its voters and contributions
+    Delete {
+        /// URL of the vit-servicing-station database to interact with
+        #[structopt(long = "db-url")]
+        db_url: String,
+
+        /// Snapshot tag to delete
+        #[structopt(long = "tag")]
+        tag: String,
+    },
+}
+
+impl SnapshotCmd {
+    fn delete(db_url: &str, tag: &str) -> Result<(), Error> {
+        let pool = load_db_connection_pool(db_url)?;
+        let db_conn = pool.get()?;
+        if !delete_snapshot(tag, &db_conn)? {
+            return Err(Error::TagNotFound(tag.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl ExecTask for SnapshotCmd {
+    type ResultValue = ();
+    type Error = Error;
+    fn exec(&self) -> Result<Self::ResultValue, Error> {
+        match self {
+            SnapshotCmd::Delete { db_url, tag } => SnapshotCmd::delete(db_url, tag),
+        }
+    }
+}
@@ -88,119 +88,3 @@ create table api_tokens
     creation_time BIGINT NOT NULL,
     expire_time BIGINT NOT NULL
 );
-
-create table challenges
-(
-    internal_id INTEGER NOT NULL
-        primary key autoincrement,
-    id INTEGER NOT NULL UNIQUE,
-    challenge_type VARCHAR NOT NULL,
-    title VARCHAR NOT NULL,
-    description VARCHAR NOT NULL,
-    rewards_total BIGINT NOT NULL,
-    proposers_rewards BIGINT NOT NULL,
-    fund_id INTEGER NOT NULL,
-    challenge_url VARCHAR NOT NULL,
-    highlights VARCHAR
-);
-
-create table community_advisors_reviews (
-  id INTEGER NOT NULL primary key autoincrement,
-  proposal_id INTEGER NOT NULL,
-  assessor VARCHAR NOT NULL,
-  impact_alignment_rating_given INTEGER NOT NULL,
-  impact_alignment_note VARCHAR NOT NULL,
-  feasibility_rating_given INTEGER NOT NULL,
-  feasibility_note VARCHAR NOT NULL,
-  auditability_rating_given INTEGER NOT NULL,
-  auditability_note VARCHAR NOT NULL,
-  ranking INTEGER NOT NULL
-);
-
-create table goals
-(
-    id INTEGER NOT NULL
-        primary key autoincrement,
-    goal_name VARCHAR NOT NULL,
-    fund_id INTEGER NOT NULL,
-    FOREIGN KEY(fund_id) REFERENCES funds(id)
-);
-
-create table groups (
-    fund_id INTEGER NOT NULL,
-    token_identifier VARCHAR NOT NULL,
-    group_id VARCHAR NOT NULL,
-    PRIMARY KEY(token_identifier, fund_id)
-);
-
-
-create table votes (
-    "fragment_id" TEXT,
-    "caster" TEXT,
-    "proposal" INTEGER,
-    "voteplan_id" TEXT,
-    "time" REAL,
-    "choice" TEXT,
-    "raw_fragment" TEXT,
-    PRIMARY KEY("fragment_id")
-);
-
-create table snapshots (
-    tag TEXT NOT NULL primary key,
-    last_updated BIGINT NOT NULL
-);
-
-create table voters (
-    voting_key TEXT NOT NULL,
-    voting_power BIGINT NOT NULL,
-    voting_group TEXT NOT NULL,
-    snapshot_tag TEXT NOT NULL,
-    PRIMARY KEY(voting_key, voting_group, snapshot_tag)
-    FOREIGN KEY(snapshot_tag) REFERENCES snapshots(tag) ON DELETE CASCADE
-);
-
-create table contributions (
-    stake_public_key TEXT NOT NULL,
-    reward_address TEXT NOT NULL,
-    value BIGINT NOT NULL,
-    voting_key TEXT NOT NULL,
-    voting_group TEXT NOT NULL,
-    snapshot_tag TEXT NOT NULL,
-    PRIMARY KEY(stake_public_key, voting_key, voting_group, snapshot_tag),
-    FOREIGN KEY(snapshot_tag) REFERENCES snapshots(tag) ON DELETE CASCADE
-);
-
-CREATE VIEW full_proposals_info
-AS
-SELECT
-    proposals.*,
-    ifnull(reviews_count, 0) as reviews_count,
-    voteplans.chain_vote_start_time,
-    voteplans.chain_vote_end_time,
-    voteplans.chain_committee_end_time,
-    voteplans.chain_voteplan_payload,
-    voteplans.chain_vote_encryption_key,
-    voteplans.fund_id,
-    challenges.challenge_type,
-    proposal_simple_challenge.proposal_solution,
-    proposal_community_choice_challenge.proposal_brief,
-    proposal_community_choice_challenge.proposal_importance,
-    proposal_community_choice_challenge.proposal_goal,
-    proposal_community_choice_challenge.proposal_metrics,
-    proposals_voteplans.chain_proposal_index,
-    proposals_voteplans.chain_voteplan_id,
-    groups.group_id
-FROM
-    proposals
-        INNER JOIN proposals_voteplans ON proposals.proposal_id = proposals_voteplans.proposal_id
-        INNER JOIN voteplans ON proposals_voteplans.chain_voteplan_id = voteplans.chain_voteplan_id
-        INNER JOIN challenges on challenges.id = proposals.challenge_id
-        INNER JOIN groups on voteplans.token_identifier = groups.token_identifier
-        LEFT JOIN proposal_simple_challenge
-            on proposals.proposal_id = proposal_simple_challenge.proposal_id
-            and challenges.challenge_type = 'simple'
-        LEFT JOIN proposal_community_choice_challenge
-            on proposals.proposal_id = proposal_community_choice_challenge.proposal_id
-            and challenges.challenge_type = 'community-choice'
-        LEFT JOIN (SELECT proposal_id as review_proposal_id, COUNT (DISTINCT assessor) as reviews_count FROM community_advisors_reviews GROUP BY proposal_id)
-            on proposals.proposal_id = review_proposal_id;
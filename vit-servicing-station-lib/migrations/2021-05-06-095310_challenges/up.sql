@@ -0,0 +1,15 @@
+-- Databases created before migrations were split already contain this table
+create table if not exists challenges
+(
+    internal_id INTEGER NOT NULL
+        primary key autoincrement,
+    id INTEGER NOT NULL UNIQUE,
+    challenge_type VARCHAR NOT NULL,
+    title VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    rewards_total BIGINT NOT NULL,
+    proposers_rewards BIGINT NOT NULL,
+    fund_id INTEGER NOT NULL,
+    challenge_url VARCHAR NOT NULL,
+    highlights VARCHAR
+);
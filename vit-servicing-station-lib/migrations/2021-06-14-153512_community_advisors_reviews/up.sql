@@ -0,0 +1,13 @@
+-- Databases created before migrations were split already contain this table
+create table if not exists community_advisors_reviews (
+  id INTEGER NOT NULL primary key autoincrement,
+  proposal_id INTEGER NOT NULL,
+  assessor VARCHAR NOT NULL,
+  impact_alignment_rating_given INTEGER NOT NULL,
+  impact_alignment_note VARCHAR NOT NULL,
+  feasibility_rating_given INTEGER NOT NULL,
+  feasibility_note VARCHAR NOT NULL,
+  auditability_rating_given INTEGER NOT NULL,
+  auditability_note VARCHAR NOT NULL,
+  ranking INTEGER NOT NULL
+);
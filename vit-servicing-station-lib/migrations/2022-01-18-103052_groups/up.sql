@@ -0,0 +1,7 @@
+-- Databases created before migrations were split already contain this table
+create table if not exists groups (
+    fund_id INTEGER NOT NULL,
+    token_identifier VARCHAR NOT NULL,
+    group_id VARCHAR NOT NULL,
+    PRIMARY KEY(token_identifier, fund_id)
+);
@@ -0,0 +1,11 @@
+-- Databases created before migrations were split already contain this table
+create table if not exists votes (
+    "fragment_id" TEXT,
+    "caster" TEXT,
+    "proposal" INTEGER,
+    "voteplan_id" TEXT,
+    "time" REAL,
+    "choice" TEXT,
+    "raw_fragment" TEXT,
+    PRIMARY KEY("fragment_id")
+);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS contributions;
+DROP TABLE IF EXISTS voters;
+DROP TABLE IF EXISTS snapshots;
@@ -0,0 +1,15 @@
+DROP TRIGGER IF EXISTS challenges_fts_delete;
+DROP TRIGGER IF EXISTS challenges_fts_update;
+DROP TRIGGER IF EXISTS challenges_fts_insert;
+DROP TRIGGER IF EXISTS proposal_community_choice_challenge_fts_delete;
+DROP TRIGGER IF EXISTS proposal_community_choice_challenge_fts_update;
+DROP TRIGGER IF EXISTS proposal_community_choice_challenge_fts_insert;
+DROP TRIGGER IF EXISTS proposal_simple_challenge_fts_delete;
+DROP TRIGGER IF EXISTS proposal_simple_challenge_fts_update;
+DROP TRIGGER IF EXISTS proposal_simple_challenge_fts_insert;
+DROP TRIGGER IF EXISTS proposals_fts_delete;
+DROP TRIGGER IF EXISTS proposals_fts_update;
+DROP TRIGGER IF EXISTS proposals_fts_insert;
+DROP VIEW IF EXISTS proposals_fts_source;
+DROP TABLE IF EXISTS challenges_fts;
+DROP TABLE IF EXISTS proposals_fts;
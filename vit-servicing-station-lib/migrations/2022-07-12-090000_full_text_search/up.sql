@@ -0,0 +1,153 @@
+-- Full text search indexes, the rowid of every entry is the id of the indexed row
+-- (`proposals.id` and `challenges.internal_id`).
+CREATE VIRTUAL TABLE proposals_fts USING fts5(
+    title,
+    summary,
+    author,
+    solution,
+    brief,
+    importance,
+    goal,
+    metrics,
+    tokenize = 'porter unicode61'
+);
+
+CREATE VIRTUAL TABLE challenges_fts USING fts5(
+    title,
+    description,
+    challenge_type,
+    tokenize = 'porter unicode61'
+);
+
+-- Text indexed for each proposal, including its challenge specific information
+CREATE VIEW proposals_fts_source
+AS
+SELECT
+    proposals.id,
+    proposals.proposal_id,
+    proposals.proposal_title AS title,
+    proposals.proposal_summary AS summary,
+    proposals.proposer_name AS author,
+    proposal_simple_challenge.proposal_solution AS solution,
+    proposal_community_choice_challenge.proposal_brief AS brief,
+    proposal_community_choice_challenge.proposal_importance AS importance,
+    proposal_community_choice_challenge.proposal_goal AS goal,
+    proposal_community_choice_challenge.proposal_metrics AS metrics
+FROM
+    proposals
+        LEFT JOIN proposal_simple_challenge
+            ON proposals.proposal_id = proposal_simple_challenge.proposal_id
+        LEFT JOIN proposal_community_choice_challenge
+            ON proposals.proposal_id = proposal_community_choice_challenge.proposal_id;
+
+-- The index entry of a proposal is rebuilt whenever the proposal or its challenge
+-- specific information changes. Entries are always removed before being inserted so
+-- rows overwritten by `REPLACE` do not end up indexed twice.
+CREATE TRIGGER proposals_fts_insert AFTER INSERT ON proposals
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid = NEW.id;
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER proposals_fts_update AFTER UPDATE ON proposals
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid IN (OLD.id, NEW.id);
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER proposals_fts_delete AFTER DELETE ON proposals
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid = OLD.id;
+END;
+
+CREATE TRIGGER proposal_simple_challenge_fts_insert AFTER INSERT ON proposal_simple_challenge
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid IN (
+        SELECT id FROM proposals WHERE proposal_id = NEW.proposal_id
+    );
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE proposal_id = NEW.proposal_id;
+END;
+
+CREATE TRIGGER proposal_simple_challenge_fts_update AFTER UPDATE ON proposal_simple_challenge
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid IN (
+        SELECT id FROM proposals WHERE proposal_id IN (OLD.proposal_id, NEW.proposal_id)
+    );
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE proposal_id IN (OLD.proposal_id, NEW.proposal_id);
+END;
+
+CREATE TRIGGER proposal_simple_challenge_fts_delete AFTER DELETE ON proposal_simple_challenge
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid IN (
+        SELECT id FROM proposals WHERE proposal_id = OLD.proposal_id
+    );
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE proposal_id = OLD.proposal_id;
+END;
+
+CREATE TRIGGER proposal_community_choice_challenge_fts_insert AFTER INSERT ON proposal_community_choice_challenge
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid IN (
+        SELECT id FROM proposals WHERE proposal_id = NEW.proposal_id
+    );
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE proposal_id = NEW.proposal_id;
+END;
+
+CREATE TRIGGER proposal_community_choice_challenge_fts_update AFTER UPDATE ON proposal_community_choice_challenge
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid IN (
+        SELECT id FROM proposals WHERE proposal_id IN (OLD.proposal_id, NEW.proposal_id)
+    );
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE proposal_id IN (OLD.proposal_id, NEW.proposal_id);
+END;
+
+CREATE TRIGGER proposal_community_choice_challenge_fts_delete AFTER DELETE ON proposal_community_choice_challenge
+BEGIN
+    DELETE FROM proposals_fts WHERE rowid IN (
+        SELECT id FROM proposals WHERE proposal_id = OLD.proposal_id
+    );
+    INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+    SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+    FROM proposals_fts_source WHERE proposal_id = OLD.proposal_id;
+END;
+
+CREATE TRIGGER challenges_fts_insert AFTER INSERT ON challenges
+BEGIN
+    DELETE FROM challenges_fts WHERE rowid = NEW.internal_id;
+    INSERT INTO challenges_fts (rowid, title, description, challenge_type)
+    VALUES (NEW.internal_id, NEW.title, NEW.description, NEW.challenge_type);
+END;
+
+CREATE TRIGGER challenges_fts_update AFTER UPDATE ON challenges
+BEGIN
+    DELETE FROM challenges_fts WHERE rowid IN (OLD.internal_id, NEW.internal_id);
+    INSERT INTO challenges_fts (rowid, title, description, challenge_type)
+    VALUES (NEW.internal_id, NEW.title, NEW.description, NEW.challenge_type);
+END;
+
+CREATE TRIGGER challenges_fts_delete AFTER DELETE ON challenges
+BEGIN
+    DELETE FROM challenges_fts WHERE rowid = OLD.internal_id;
+END;
+
+-- index the content already present in the database
+INSERT INTO proposals_fts (rowid, title, summary, author, solution, brief, importance, goal, metrics)
+SELECT id, title, summary, author, solution, brief, importance, goal, metrics
+FROM proposals_fts_source;
+
+INSERT INTO challenges_fts (rowid, title, description, challenge_type)
+SELECT internal_id, title, description, challenge_type
+FROM challenges;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS contributions_voters_idx;
+DROP TABLE IF EXISTS snapshot_voting_groups;
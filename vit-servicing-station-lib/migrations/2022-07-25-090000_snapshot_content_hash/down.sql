@@ -0,0 +1,8 @@
+-- Older sqlite versions can not drop columns
+CREATE TABLE snapshots_backup (
+    tag TEXT NOT NULL primary key,
+    last_updated BIGINT NOT NULL
+);
+INSERT INTO snapshots_backup SELECT tag, last_updated FROM snapshots;
+DROP TABLE snapshots;
+ALTER TABLE snapshots_backup RENAME TO snapshots;
@@ -0,0 +1,2 @@
+-- Hash of the JSON export of the snapshot content, computed once the content is written
+ALTER TABLE snapshots ADD COLUMN content_hash TEXT;
@@ -0,0 +1,21 @@
+-- Processing parameters of the snapshot tags imported from a raw snapshot
+create table raw_snapshots (
+    tag TEXT NOT NULL PRIMARY KEY,
+    min_stake_threshold BIGINT NOT NULL,
+    -- JSON serialization of the voting power cap fraction
+    voting_power_cap TEXT NOT NULL,
+    direct_voters_group TEXT,
+    representatives_group TEXT,
+    registrations_count BIGINT NOT NULL,
+    FOREIGN KEY(tag) REFERENCES snapshots(tag) ON DELETE CASCADE
+);
+
+-- Registrations of a raw snapshot in their original order
+create table raw_snapshot_registrations (
+    tag TEXT NOT NULL,
+    registration_index BIGINT NOT NULL,
+    -- JSON serialization of the registration
+    registration TEXT NOT NULL,
+    PRIMARY KEY(tag, registration_index),
+    FOREIGN KEY(tag) REFERENCES raw_snapshots(tag) ON DELETE CASCADE
+);
@@ -0,0 +1,33 @@
+-- Older sqlite versions can not drop columns
+CREATE TABLE funds_backup
+(
+    id INTEGER NOT NULL
+        primary key autoincrement,
+    fund_name VARCHAR NOT NULL,
+    fund_goal VARCHAR NOT NULL,
+    registration_snapshot_time BIGINT NOT NULL,
+    next_registration_snapshot_time BIGINT NOT NULL,
+    voting_power_threshold BIGINT NOT NULL,
+    fund_start_time BIGINT NOT NULL,
+    fund_end_time BIGINT NOT NULL,
+    next_fund_start_time BIGINT NOT NULL,
+    insight_sharing_start BIGINT NOT NULL,
+    proposal_submission_start BIGINT NOT NULL,
+    refine_proposals_start BIGINT NOT NULL,
+    finalize_proposals_start BIGINT NOT NULL,
+    proposal_assessment_start BIGINT NOT NULL,
+    assessment_qa_start BIGINT NOT NULL,
+    snapshot_start BIGINT NOT NULL,
+    voting_start BIGINT NOT NULL,
+    voting_end BIGINT NOT NULL,
+    tallying_end BIGINT NOT NULL,
+    results_url VARCHAR NOT NULL,
+    survey_url VARCHAR NOT NULL
+);
+INSERT INTO funds_backup SELECT id, fund_name, fund_goal, registration_snapshot_time,
+    next_registration_snapshot_time, voting_power_threshold, fund_start_time, fund_end_time,
+    next_fund_start_time, insight_sharing_start, proposal_submission_start, refine_proposals_start,
+    finalize_proposals_start, proposal_assessment_start, assessment_qa_start, snapshot_start,
+    voting_start, voting_end, tallying_end, results_url, survey_url FROM funds;
+DROP TABLE funds;
+ALTER TABLE funds_backup RENAME TO funds;
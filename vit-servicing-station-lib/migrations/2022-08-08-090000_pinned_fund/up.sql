@@ -0,0 +1,5 @@
+-- Fund pinned by an administrator as the current one, holds at most a single row.
+-- There is no foreign key on purpose: updating a fund replaces its row, which would drop the pin.
+create table pinned_fund (
+    fund_id INTEGER NOT NULL PRIMARY KEY
+);
@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS api_tokens;
+DROP TABLE IF EXISTS voteplans;
+DROP TABLE IF EXISTS proposal_community_choice_challenge;
+DROP TABLE IF EXISTS proposal_simple_challenge;
+DROP TABLE IF EXISTS proposals_voteplans;
+DROP TABLE IF EXISTS proposals;
+DROP TABLE IF EXISTS funds CASCADE;
@@ -0,0 +1,90 @@
+create table funds
+(
+    id SERIAL NOT NULL
+        primary key,
+    fund_name VARCHAR NOT NULL,
+    fund_goal VARCHAR NOT NULL,
+    registration_snapshot_time BIGINT NOT NULL,
+    next_registration_snapshot_time BIGINT NOT NULL,
+    voting_power_threshold BIGINT NOT NULL,
+    fund_start_time BIGINT NOT NULL,
+    fund_end_time BIGINT NOT NULL,
+    next_fund_start_time BIGINT NOT NULL,
+    insight_sharing_start BIGINT NOT NULL,
+    proposal_submission_start BIGINT NOT NULL,
+    refine_proposals_start BIGINT NOT NULL,
+    finalize_proposals_start BIGINT NOT NULL,
+    proposal_assessment_start BIGINT NOT NULL,
+    assessment_qa_start BIGINT NOT NULL,
+    snapshot_start BIGINT NOT NULL,
+    voting_start BIGINT NOT NULL,
+    voting_end BIGINT NOT NULL,
+    tallying_end BIGINT NOT NULL,
+    results_url VARCHAR NOT NULL,
+    survey_url VARCHAR NOT NULL
+);
+
+create table proposals
+(
+    id SERIAL NOT NULL
+        primary key,
+    proposal_id VARCHAR NOT NULL UNIQUE,
+    proposal_category VARCHAR NOT NULL,
+    proposal_title VARCHAR NOT NULL,
+    proposal_summary VARCHAR NOT NULL,
+    proposal_public_key VARCHAR NOT NULL,
+    proposal_funds BIGINT NOT NULL,
+    proposal_url VARCHAR NOT NULL,
+    proposal_files_url VARCHAR NOT NULL,
+    proposal_impact_score BIGINT NOT NULL,
+    proposer_name VARCHAR NOT NULL,
+    proposer_contact VARCHAR NOT NULL,
+    proposer_url VARCHAR NOT NULL,
+    proposer_relevant_experience VARCHAR NOT NULL,
+    chain_proposal_id BYTEA NOT NULL,
+    chain_vote_options VARCHAR NOT NULL,
+    challenge_id INTEGER NOT NULL
+);
+
+create table proposals_voteplans (
+    proposal_id VARCHAR NOT NULL,
+    chain_voteplan_id VARCHAR NOT NULL,
+    chain_proposal_index BIGINT NOT NULL,
+    FOREIGN KEY (proposal_id)
+        REFERENCES proposals (proposal_id)
+);
+
+create table proposal_simple_challenge (
+    proposal_id VARCHAR NOT NULL primary key,
+    proposal_solution VARCHAR
+);
+
+create table proposal_community_choice_challenge (
+    proposal_id VARCHAR NOT NULL primary key,
+    proposal_brief VARCHAR,
+    proposal_importance VARCHAR,
+    proposal_goal VARCHAR,
+    proposal_metrics VARCHAR
+);
+
+create table voteplans
+(
+    id SERIAL NOT NULL
+        primary key,
+    chain_voteplan_id VARCHAR NOT NULL
+        unique,
+    chain_vote_start_time BIGINT NOT NULL,
+    chain_vote_end_time BIGINT NOT NULL,
+    chain_committee_end_time BIGINT NOT NULL,
+    chain_voteplan_payload VARCHAR NOT NULL,
+    chain_vote_encryption_key VARCHAR NOT NULL,
+    fund_id INTEGER NOT NULL,
+    token_identifier VARCHAR NOT NULL
+);
+
+create table api_tokens
+(
+    token BYTEA NOT NULL UNIQUE PRIMARY KEY ,
+    creation_time BIGINT NOT NULL,
+    expire_time BIGINT NOT NULL
+);
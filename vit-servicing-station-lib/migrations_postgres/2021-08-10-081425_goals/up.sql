@@ -0,0 +1,9 @@
+-- Databases created before migrations were split already contain this table
+create table if not exists goals
+(
+    id SERIAL NOT NULL
+        primary key,
+    goal_name VARCHAR NOT NULL,
+    fund_id INTEGER NOT NULL,
+    FOREIGN KEY(fund_id) REFERENCES funds(id)
+);
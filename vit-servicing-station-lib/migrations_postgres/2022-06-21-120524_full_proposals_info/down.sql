@@ -0,0 +1 @@
+DROP VIEW IF EXISTS full_proposals_info;
@@ -0,0 +1,40 @@
+-- Views are not updatable, every migration changing any of the underlying tables must
+-- drop and re-create it.
+DROP VIEW IF EXISTS full_proposals_info;
+
+-- Postgres does not coerce between integer and text, neither returns an INTEGER from COUNT,
+-- so the reviews sub-query casts explicitly to keep the same column types as in sqlite.
+CREATE VIEW full_proposals_info
+AS
+SELECT
+    proposals.*,
+    CAST(COALESCE(reviews_count, 0) AS INTEGER) as reviews_count,
+    voteplans.chain_vote_start_time,
+    voteplans.chain_vote_end_time,
+    voteplans.chain_committee_end_time,
+    voteplans.chain_voteplan_payload,
+    voteplans.chain_vote_encryption_key,
+    voteplans.fund_id,
+    challenges.challenge_type,
+    proposal_simple_challenge.proposal_solution,
+    proposal_community_choice_challenge.proposal_brief,
+    proposal_community_choice_challenge.proposal_importance,
+    proposal_community_choice_challenge.proposal_goal,
+    proposal_community_choice_challenge.proposal_metrics,
+    proposals_voteplans.chain_proposal_index,
+    proposals_voteplans.chain_voteplan_id,
+    groups.group_id
+FROM
+    proposals
+        INNER JOIN proposals_voteplans ON proposals.proposal_id = proposals_voteplans.proposal_id
+        INNER JOIN voteplans ON proposals_voteplans.chain_voteplan_id = voteplans.chain_voteplan_id
+        INNER JOIN challenges on challenges.id = proposals.challenge_id
+        INNER JOIN groups on voteplans.token_identifier = groups.token_identifier
+        LEFT JOIN proposal_simple_challenge
+            on proposals.proposal_id = proposal_simple_challenge.proposal_id
+            and challenges.challenge_type = 'simple'
+        LEFT JOIN proposal_community_choice_challenge
+            on proposals.proposal_id = proposal_community_choice_challenge.proposal_id
+            and challenges.challenge_type = 'community-choice'
+        LEFT JOIN (SELECT CAST(proposal_id AS VARCHAR) as review_proposal_id, COUNT (DISTINCT assessor) as reviews_count FROM community_advisors_reviews GROUP BY proposal_id) AS reviews
+            on proposals.proposal_id = reviews.review_proposal_id;
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS challenges_description_fts;
+DROP INDEX IF EXISTS challenges_title_fts;
+DROP INDEX IF EXISTS proposals_author_fts;
+DROP INDEX IF EXISTS proposals_summary_fts;
+DROP INDEX IF EXISTS proposals_title_fts;
@@ -0,0 +1,13 @@
+-- Postgres builds the text search vectors on the fly, these indexes cover the
+-- expressions used when searching a single column.
+CREATE INDEX proposals_title_fts ON proposals
+    USING GIN (to_tsvector('english', proposal_title));
+CREATE INDEX proposals_summary_fts ON proposals
+    USING GIN (to_tsvector('english', proposal_summary));
+CREATE INDEX proposals_author_fts ON proposals
+    USING GIN (to_tsvector('english', proposer_name));
+
+CREATE INDEX challenges_title_fts ON challenges
+    USING GIN (to_tsvector('english', title));
+CREATE INDEX challenges_description_fts ON challenges
+    USING GIN (to_tsvector('english', description));
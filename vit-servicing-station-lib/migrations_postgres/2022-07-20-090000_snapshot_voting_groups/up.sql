@@ -0,0 +1,18 @@
+-- Totals of every voting group, written along with the voters of a snapshot tag
+create table snapshot_voting_groups (
+    snapshot_tag TEXT NOT NULL,
+    voting_group TEXT NOT NULL,
+    total_voting_power BIGINT NOT NULL,
+    voters_count BIGINT NOT NULL,
+    PRIMARY KEY(snapshot_tag, voting_group),
+    FOREIGN KEY(snapshot_tag) REFERENCES snapshots(tag) ON DELETE CASCADE
+);
+
+insert into snapshot_voting_groups (snapshot_tag, voting_group, total_voting_power, voters_count)
+select snapshot_tag, voting_group, SUM(voting_power), COUNT(*)
+from voters
+where snapshot_tag in (select tag from snapshots)
+group by snapshot_tag, voting_group;
+
+-- Delegations of a voter
+create index contributions_voters_idx on contributions (snapshot_tag, voting_key, voting_group);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS raw_snapshot_registrations;
+DROP TABLE IF EXISTS raw_snapshots;
@@ -0,0 +1,2 @@
+-- Snapshot tag holding the voting power of the fund, set once the snapshot is taken
+ALTER TABLE funds ADD COLUMN snapshot_tag TEXT;
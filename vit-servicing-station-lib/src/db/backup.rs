@@ -0,0 +1,255 @@
+use crate::db::DbConnection;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("invalid database path `{0}`")]
+    InvalidPath(String),
+
+    #[error("error opening database `{path}`: {message}")]
+    Open { path: String, message: String },
+
+    #[error("error copying database: {0}")]
+    Copy(String),
+
+    #[error("database kept busy by other connections for more than {0:?}")]
+    Busy(std::time::Duration),
+
+    #[error("error retrieving database file: {0}")]
+    DatabaseFile(#[from] diesel::result::Error),
+
+    #[error("in-memory databases cannot be backed up")]
+    InMemoryDatabase,
+
+    #[error("backups are only supported by the sqlite backend")]
+    Unsupported,
+}
+
+/// Copy the database at `db_url` into `out` using the sqlite online backup API.
+/// The copy is consistent even if the database is being written while it runs.
+pub fn backup_db(db_url: &str, out: &str) -> Result<(), BackupError> {
+    copy_database(db_url, out, BUSY_TIMEOUT)
+}
+
+/// Overwrite the database at `db_url` with the content of the `backup` one.
+pub fn restore_db(backup: &str, db_url: &str) -> Result<(), BackupError> {
+    copy_database(backup, db_url, BUSY_TIMEOUT)
+}
+
+/// Time the copy keeps retrying while either database is locked by other connections
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Path of the file backing the database the connection is attached to
+#[cfg(not(feature = "postgres"))]
+pub fn database_file(db_conn: &DbConnection) -> Result<String, BackupError> {
+    use diesel::RunQueryDsl;
+
+    #[derive(QueryableByName)]
+    struct DatabaseFile {
+        #[sql_type = "diesel::sql_types::Text"]
+        file: String,
+    }
+
+    let DatabaseFile { file } =
+        diesel::sql_query("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .get_result(db_conn)?;
+
+    if file.is_empty() {
+        return Err(BackupError::InMemoryDatabase);
+    }
+    Ok(file)
+}
+
+#[cfg(feature = "postgres")]
+pub fn database_file(_db_conn: &DbConnection) -> Result<String, BackupError> {
+    Err(BackupError::Unsupported)
+}
+
+#[cfg(not(feature = "postgres"))]
+fn copy_database(
+    from: &str,
+    to: &str,
+    busy_timeout: std::time::Duration,
+) -> Result<(), BackupError> {
+    use libsqlite3_sys as ffi;
+    use std::os::raw::{c_char, c_int};
+
+    // number of pages copied on each step, the source is only locked while stepping
+    const PAGES_PER_STEP: c_int = 1024;
+    const BUSY_WAIT: std::time::Duration = std::time::Duration::from_millis(10);
+    const MAIN_DB: *const c_char = b"main\0".as_ptr() as *const c_char;
+
+    let source = raw::Connection::open(from, ffi::SQLITE_OPEN_READONLY | ffi::SQLITE_OPEN_URI)?;
+    let destination = raw::Connection::open(
+        to,
+        ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_URI,
+    )?;
+
+    // Safety: both handles are valid until the connections are dropped at the end of the function
+    // and the backup handle is always released through `sqlite3_backup_finish`
+    unsafe {
+        let backup = ffi::sqlite3_backup_init(destination.handle, MAIN_DB, source.handle, MAIN_DB);
+        if backup.is_null() {
+            return Err(BackupError::Copy(destination.error_message()));
+        }
+
+        let started = std::time::Instant::now();
+        let mut timed_out = false;
+        loop {
+            match ffi::sqlite3_backup_step(backup, PAGES_PER_STEP) {
+                ffi::SQLITE_OK => {}
+                ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED if started.elapsed() < busy_timeout => {
+                    std::thread::sleep(BUSY_WAIT)
+                }
+                ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED => {
+                    timed_out = true;
+                    break;
+                }
+                // either done or failed, `sqlite3_backup_finish` reports which one
+                _ => break,
+            }
+        }
+
+        // the backup handle is released even when giving up on a busy database
+        let result = ffi::sqlite3_backup_finish(backup);
+        if timed_out {
+            return Err(BackupError::Busy(busy_timeout));
+        }
+        match result {
+            ffi::SQLITE_OK => Ok(()),
+            _ => Err(BackupError::Copy(destination.error_message())),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+fn copy_database(
+    _from: &str,
+    _to: &str,
+    _busy_timeout: std::time::Duration,
+) -> Result<(), BackupError> {
+    Err(BackupError::Unsupported)
+}
+
+#[cfg(not(feature = "postgres"))]
+mod raw {
+    use super::BackupError;
+    use libsqlite3_sys as ffi;
+    use std::ffi::{CStr, CString};
+    use std::os::raw::c_int;
+
+    /// Bare sqlite connection, diesel does not expose the handle needed by the backup API
+    pub struct Connection {
+        pub handle: *mut ffi::sqlite3,
+    }
+
+    impl Connection {
+        pub fn open(path: &str, flags: c_int) -> Result<Self, BackupError> {
+            let c_path =
+                CString::new(path).map_err(|_| BackupError::InvalidPath(path.to_string()))?;
+            let mut handle = std::ptr::null_mut();
+            let result = unsafe {
+                ffi::sqlite3_open_v2(c_path.as_ptr(), &mut handle, flags, std::ptr::null())
+            };
+            // the handle has to be released even if opening the database failed
+            let connection = Self { handle };
+            if result != ffi::SQLITE_OK {
+                return Err(BackupError::Open {
+                    path: path.to_string(),
+                    message: connection.error_message(),
+                });
+            }
+            Ok(connection)
+        }
+
+        pub fn error_message(&self) -> String {
+            if self.handle.is_null() {
+                return "out of memory".to_string();
+            }
+            unsafe {
+                CStr::from_ptr(ffi::sqlite3_errmsg(self.handle))
+                    .to_string_lossy()
+                    .into_owned()
+            }
+        }
+    }
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            // closing a null handle is a harmless no-op
+            unsafe {
+                ffi::sqlite3_close(self.handle);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "postgres")))]
+mod test {
+    use super::*;
+    use crate::db::{
+        load_db_connection_pool, migrations::initialize_db_with_migration,
+        models::funds::test as funds_testing, queries::funds::query_all_funds,
+    };
+
+    #[tokio::test]
+    async fn backup_and_restore() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let db_url = tmp_dir.path().join("db.sqlite3");
+        let db_url = db_url.to_str().unwrap();
+        let backup_url = tmp_dir.path().join("backup.sqlite3");
+        let backup_url = backup_url.to_str().unwrap();
+
+        let pool = load_db_connection_pool(db_url).unwrap();
+        initialize_db_with_migration(&pool.get().unwrap());
+        funds_testing::populate_db_with_fund(&funds_testing::get_test_fund(Some(1)), &pool);
+
+        // backup while the pool keeps the database open
+        assert!(database_file(&pool.get().unwrap())
+            .unwrap()
+            .ends_with("db.sqlite3"));
+        backup_db(db_url, backup_url).unwrap();
+
+        let backup_pool = load_db_connection_pool(backup_url).unwrap();
+        assert_eq!(query_all_funds(&backup_pool).await.unwrap(), vec![1]);
+
+        // changes done after the backup are lost when restoring it
+        funds_testing::populate_db_with_fund(&funds_testing::get_test_fund(Some(2)), &pool);
+        assert_eq!(query_all_funds(&pool).await.unwrap(), vec![1, 2]);
+        restore_db(backup_url, db_url).unwrap();
+        assert_eq!(query_all_funds(&pool).await.unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn give_up_on_locked_database() {
+        use diesel::Connection;
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let db_url = tmp_dir.path().join("db.sqlite3");
+        let db_url = db_url.to_str().unwrap();
+        let backup_url = tmp_dir.path().join("backup.sqlite3");
+        let backup_url = backup_url.to_str().unwrap();
+
+        let pool = load_db_connection_pool(db_url).unwrap();
+        initialize_db_with_migration(&pool.get().unwrap());
+        backup_db(db_url, backup_url).unwrap();
+
+        // the lock is held until the connection is dropped
+        let locking_conn = diesel::SqliteConnection::establish(db_url).unwrap();
+        locking_conn.execute("BEGIN EXCLUSIVE").unwrap();
+        let timeout = std::time::Duration::from_millis(100);
+        assert!(matches!(
+            copy_database(backup_url, db_url, timeout),
+            Err(BackupError::Busy(busy_timeout)) if busy_timeout == timeout
+        ));
+    }
+
+    #[test]
+    fn in_memory_database_file() {
+        let pool = load_db_connection_pool(&crate::v0::context::test::new_test_db_url()).unwrap();
+        assert!(matches!(
+            database_file(&pool.get().unwrap()),
+            Err(BackupError::InMemoryDatabase)
+        ));
+    }
+}
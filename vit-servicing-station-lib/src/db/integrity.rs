@@ -0,0 +1,274 @@
+use crate::db::DbConnection;
+use diesel::sql_types::{Integer, Text};
+use diesel::{QueryResult, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+
+/// Proposals that are not reachable through the `full_proposals_info` view
+const ORPHANED_PROPOSALS_QUERY: &str = "
+SELECT
+    proposals.id,
+    proposals.proposal_id,
+    CASE
+        WHEN challenges.id IS NULL THEN 'missing challenge'
+        WHEN proposals_voteplans.proposal_id IS NULL THEN 'missing voteplan link'
+        ELSE 'missing voteplan'
+    END AS reason
+FROM
+    proposals
+        LEFT JOIN challenges ON challenges.id = proposals.challenge_id
+        LEFT JOIN proposals_voteplans ON proposals_voteplans.proposal_id = proposals.proposal_id
+        LEFT JOIN voteplans ON voteplans.chain_voteplan_id = proposals_voteplans.chain_voteplan_id
+WHERE
+    challenges.id IS NULL OR voteplans.id IS NULL
+ORDER BY proposals.id;
+";
+
+const REVIEWS_WITHOUT_PROPOSAL_QUERY: &str = "
+SELECT
+    community_advisors_reviews.id,
+    community_advisors_reviews.proposal_id
+FROM
+    community_advisors_reviews
+WHERE NOT EXISTS (
+    SELECT 1 FROM proposals
+    WHERE proposals.proposal_id = CAST(community_advisors_reviews.proposal_id AS VARCHAR)
+)
+ORDER BY community_advisors_reviews.id;
+";
+
+const VOTEPLANS_WITHOUT_GROUP_QUERY: &str = "
+SELECT
+    voteplans.id,
+    voteplans.chain_voteplan_id,
+    voteplans.token_identifier
+FROM
+    voteplans
+WHERE NOT EXISTS (
+    SELECT 1 FROM groups WHERE groups.token_identifier = voteplans.token_identifier
+)
+ORDER BY voteplans.id;
+";
+
+const CHALLENGES_WITHOUT_FUND_QUERY: &str = "
+SELECT
+    challenges.id,
+    challenges.fund_id
+FROM
+    challenges
+WHERE NOT EXISTS (
+    SELECT 1 FROM funds WHERE funds.id = challenges.fund_id
+)
+ORDER BY challenges.id;
+";
+
+const VOTERS_WITHOUT_SNAPSHOT_QUERY: &str = "
+SELECT
+    voters.voting_key,
+    voters.voting_group,
+    voters.snapshot_tag
+FROM
+    voters
+WHERE NOT EXISTS (
+    SELECT 1 FROM snapshots WHERE snapshots.tag = voters.snapshot_tag
+)
+ORDER BY voters.snapshot_tag, voters.voting_key, voters.voting_group;
+";
+
+const CONTRIBUTIONS_WITHOUT_SNAPSHOT_QUERY: &str = "
+SELECT
+    contributions.stake_public_key,
+    contributions.voting_key,
+    contributions.voting_group,
+    contributions.snapshot_tag
+FROM
+    contributions
+WHERE NOT EXISTS (
+    SELECT 1 FROM snapshots WHERE snapshots.tag = contributions.snapshot_tag
+)
+ORDER BY
+    contributions.snapshot_tag,
+    contributions.stake_public_key,
+    contributions.voting_key,
+    contributions.voting_group;
+";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, QueryableByName)]
+pub struct OrphanedProposal {
+    #[sql_type = "Integer"]
+    pub id: i32,
+    #[sql_type = "Text"]
+    pub proposal_id: String,
+    #[sql_type = "Text"]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, QueryableByName)]
+pub struct ReviewWithoutProposal {
+    #[sql_type = "Integer"]
+    pub id: i32,
+    #[sql_type = "Integer"]
+    pub proposal_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, QueryableByName)]
+pub struct VoteplanWithoutGroup {
+    #[sql_type = "Integer"]
+    pub id: i32,
+    #[sql_type = "Text"]
+    pub chain_voteplan_id: String,
+    #[sql_type = "Text"]
+    pub token_identifier: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, QueryableByName)]
+pub struct ChallengeWithoutFund {
+    #[sql_type = "Integer"]
+    pub id: i32,
+    #[sql_type = "Integer"]
+    pub fund_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, QueryableByName)]
+pub struct VoterWithoutSnapshot {
+    #[sql_type = "Text"]
+    pub voting_key: String,
+    #[sql_type = "Text"]
+    pub voting_group: String,
+    #[sql_type = "Text"]
+    pub snapshot_tag: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, QueryableByName)]
+pub struct ContributionWithoutSnapshot {
+    #[sql_type = "Text"]
+    pub stake_public_key: String,
+    #[sql_type = "Text"]
+    pub voting_key: String,
+    #[sql_type = "Text"]
+    pub voting_group: String,
+    #[sql_type = "Text"]
+    pub snapshot_tag: String,
+}
+
+/// Rows referencing entries that do not exist in the database.
+/// Every entry is an error, the database is consistent only if all of them are empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub orphaned_proposals: Vec<OrphanedProposal>,
+    pub reviews_without_proposal: Vec<ReviewWithoutProposal>,
+    pub voteplans_without_group: Vec<VoteplanWithoutGroup>,
+    pub challenges_without_fund: Vec<ChallengeWithoutFund>,
+    pub voters_without_snapshot: Vec<VoterWithoutSnapshot>,
+    pub contributions_without_snapshot: Vec<ContributionWithoutSnapshot>,
+}
+
+impl IntegrityReport {
+    pub fn errors_count(&self) -> usize {
+        self.orphaned_proposals.len()
+            + self.reviews_without_proposal.len()
+            + self.voteplans_without_group.len()
+            + self.challenges_without_fund.len()
+            + self.voters_without_snapshot.len()
+            + self.contributions_without_snapshot.len()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.errors_count() == 0
+    }
+}
+
+/// Look for broken references between the tables of the database
+pub fn check_integrity(db_conn: &DbConnection) -> QueryResult<IntegrityReport> {
+    Ok(IntegrityReport {
+        orphaned_proposals: diesel::sql_query(ORPHANED_PROPOSALS_QUERY).load(db_conn)?,
+        reviews_without_proposal: diesel::sql_query(REVIEWS_WITHOUT_PROPOSAL_QUERY)
+            .load(db_conn)?,
+        voteplans_without_group: diesel::sql_query(VOTEPLANS_WITHOUT_GROUP_QUERY).load(db_conn)?,
+        challenges_without_fund: diesel::sql_query(CHALLENGES_WITHOUT_FUND_QUERY).load(db_conn)?,
+        voters_without_snapshot: diesel::sql_query(VOTERS_WITHOUT_SNAPSHOT_QUERY).load(db_conn)?,
+        contributions_without_snapshot: diesel::sql_query(CONTRIBUTIONS_WITHOUT_SNAPSHOT_QUERY)
+            .load(db_conn)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::{
+        load_db_connection_pool,
+        migrations::initialize_db_with_migration,
+        models::funds::test as funds_testing,
+        schema::{challenges, funds, groups, snapshots, voters},
+    };
+    use crate::v0::context::test::new_test_db_url;
+    use diesel::{ExpressionMethods, QueryDsl};
+
+    #[test]
+    fn consistent_and_broken_references() {
+        let pool = load_db_connection_pool(&new_test_db_url()).unwrap();
+        let db_conn = pool.get().unwrap();
+        initialize_db_with_migration(&db_conn);
+
+        let fund = funds_testing::get_test_fund(Some(1));
+        funds_testing::populate_db_with_fund(&fund, &pool);
+
+        // the test voteplan token is not part of the test fund groups
+        let report = check_integrity(&db_conn).unwrap();
+        assert_eq!(report.errors_count(), 1);
+        assert_eq!(
+            report.voteplans_without_group[0].token_identifier,
+            fund.chain_vote_plans[0].token_identifier
+        );
+
+        diesel::insert_into(groups::table)
+            .values((
+                groups::fund_id.eq(fund.id),
+                groups::token_identifier.eq(&fund.chain_vote_plans[0].token_identifier),
+                groups::group_id.eq("group"),
+            ))
+            .execute(&db_conn)
+            .unwrap();
+        let report = check_integrity(&db_conn).unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+
+        // a voter of a snapshot that was never imported
+        diesel::insert_into(voters::table)
+            .values((
+                voters::voting_key.eq("voting_key"),
+                voters::voting_power.eq(1),
+                voters::voting_group.eq("direct"),
+                voters::snapshot_tag.eq("missing"),
+            ))
+            .execute(&db_conn)
+            .unwrap();
+        diesel::insert_into(snapshots::table)
+            .values((snapshots::tag.eq("other"), snapshots::last_updated.eq(0)))
+            .execute(&db_conn)
+            .unwrap();
+
+        // removing the fund leaves its challenges dangling
+        diesel::delete(funds::table.filter(funds::id.eq(fund.id)))
+            .execute(&db_conn)
+            .unwrap();
+
+        let report = check_integrity(&db_conn).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.voters_without_snapshot,
+            vec![VoterWithoutSnapshot {
+                voting_key: "voting_key".to_string(),
+                voting_group: "direct".to_string(),
+                snapshot_tag: "missing".to_string(),
+            }]
+        );
+        let challenges_count: i64 = challenges::table.count().get_result(&db_conn).unwrap();
+        assert_eq!(
+            report.challenges_without_fund.len() as i64,
+            challenges_count
+        );
+        assert!(report
+            .challenges_without_fund
+            .iter()
+            .all(|challenge| challenge.fund_id == fund.id));
+    }
+}
@@ -1,7 +1,234 @@
 use crate::db::DbConnection;
+use diesel::connection::SimpleConnection;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel_migrations::{
+    run_migrations, setup_database, Migration, MigrationConnection, MigrationError,
+};
+use serde::Serialize;
 
-embed_migrations!("./migrations");
+pub use diesel_migrations::RunMigrationsError;
+
+// Each backend keeps its own set of migrations, they must be kept in sync so both
+// produce the same schema.
+#[cfg(not(feature = "postgres"))]
+macro_rules! migrations_dir {
+    () => {
+        "/migrations/"
+    };
+}
+
+#[cfg(feature = "postgres")]
+macro_rules! migrations_dir {
+    () => {
+        "/migrations_postgres/"
+    };
+}
+
+macro_rules! embed_migration {
+    ($name:literal) => {
+        EmbeddedMigration::new(
+            $name,
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                migrations_dir!(),
+                $name,
+                "/up.sql"
+            )),
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                migrations_dir!(),
+                $name,
+                "/down.sql"
+            )),
+        )
+    };
+}
+
+// Internal table diesel uses to keep track of the applied migrations
+table! {
+    __diesel_schema_migrations (version) {
+        version -> VarChar,
+    }
+}
+
+/// Migrations bundled in the binary, sorted from oldest to newest.
+/// Every new migration directory must be added at the end of this list.
+fn embedded_migrations() -> Vec<EmbeddedMigration> {
+    vec![
+        embed_migration!("2020-05-22-112032_setup_db"),
+        embed_migration!("2021-05-06-095310_challenges"),
+        embed_migration!("2021-06-14-153512_community_advisors_reviews"),
+        embed_migration!("2021-08-10-081425_goals"),
+        embed_migration!("2022-01-18-103052_groups"),
+        embed_migration!("2022-03-02-164410_votes"),
+        embed_migration!("2022-06-21-120523_snapshots"),
+        embed_migration!("2022-06-21-120524_full_proposals_info"),
+        embed_migration!("2022-07-12-090000_full_text_search"),
+        embed_migration!("2022-07-20-090000_snapshot_voting_groups"),
+        embed_migration!("2022-07-25-090000_snapshot_content_hash"),
+        embed_migration!("2022-07-28-090000_raw_snapshots"),
+        embed_migration!("2022-08-01-090000_fund_snapshot_tag"),
+        embed_migration!("2022-08-08-090000_pinned_fund"),
+    ]
+}
+
+struct EmbeddedMigration {
+    name: &'static str,
+    version: String,
+    up_sql: &'static str,
+    down_sql: &'static str,
+}
+
+impl EmbeddedMigration {
+    fn new(name: &'static str, up_sql: &'static str, down_sql: &'static str) -> Self {
+        // same versioning diesel uses: the directory name timestamp without separators
+        let version = name.split('_').next().unwrap_or(name).replace('-', "");
+        Self {
+            name,
+            version,
+            up_sql,
+            down_sql,
+        }
+    }
+}
+
+impl Migration for EmbeddedMigration {
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn run(&self, conn: &dyn SimpleConnection) -> Result<(), RunMigrationsError> {
+        conn.batch_execute(self.up_sql).map_err(Into::into)
+    }
+
+    fn revert(&self, conn: &dyn SimpleConnection) -> Result<(), RunMigrationsError> {
+        conn.batch_execute(self.down_sql).map_err(Into::into)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationStatus {
+    pub name: String,
+    pub version: String,
+    pub applied: bool,
+}
+
+/// Status of every migration known by this binary in the given database
+pub fn migrations_status(
+    db_conn: &DbConnection,
+) -> Result<Vec<MigrationStatus>, RunMigrationsError> {
+    setup_database(db_conn)?;
+    let applied = db_conn.previously_run_migration_versions()?;
+    Ok(embedded_migrations()
+        .into_iter()
+        .map(|migration| MigrationStatus {
+            applied: applied.contains(&migration.version),
+            name: migration.name.to_string(),
+            version: migration.version,
+        })
+        .collect())
+}
+
+/// Names of the migrations that are not yet applied to the given database
+pub fn pending_migrations(db_conn: &DbConnection) -> Result<Vec<String>, RunMigrationsError> {
+    Ok(migrations_status(db_conn)?
+        .into_iter()
+        .filter(|status| !status.applied)
+        .map(|status| status.name)
+        .collect())
+}
+
+/// Apply every pending migration, returns the names of the applied ones
+pub fn run_pending_migrations(db_conn: &DbConnection) -> Result<Vec<String>, RunMigrationsError> {
+    let pending = pending_migrations(db_conn)?;
+    run_migrations(
+        db_conn,
+        embedded_migrations()
+            .iter()
+            .map(|migration| migration as &dyn Migration),
+        &mut std::io::sink(),
+    )?;
+    Ok(pending)
+}
+
+/// Revert the last `n` applied migrations, returns the names of the reverted ones
+/// from newest to oldest
+pub fn revert_migrations(
+    db_conn: &DbConnection,
+    n: usize,
+) -> Result<Vec<String>, RunMigrationsError> {
+    setup_database(db_conn)?;
+    let migrations = embedded_migrations();
+    let mut reverted = Vec::new();
+
+    for _ in 0..n {
+        let version = match db_conn.latest_run_migration_version()? {
+            Some(version) => version,
+            None => break,
+        };
+        let migration = migrations
+            .iter()
+            .find(|migration| migration.version == version)
+            .ok_or_else(|| {
+                RunMigrationsError::MigrationError(MigrationError::UnknownMigrationVersion(
+                    version.clone(),
+                ))
+            })?;
+
+        db_conn.transaction::<_, RunMigrationsError, _>(|| {
+            migration.revert(db_conn)?;
+            diesel::delete(
+                __diesel_schema_migrations::table
+                    .filter(__diesel_schema_migrations::version.eq(&version)),
+            )
+            .execute(db_conn)?;
+            Ok(())
+        })?;
+        reverted.push(migration.name.to_string());
+    }
+
+    Ok(reverted)
+}
 
 pub fn initialize_db_with_migration(db_conn: &DbConnection) {
-    embedded_migrations::run(db_conn).unwrap();
+    run_pending_migrations(db_conn).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::load_db_connection_pool;
+    use crate::v0::context::test::new_test_db_url;
+
+    #[test]
+    fn migrate_and_rollback() {
+        let pool = load_db_connection_pool(&new_test_db_url()).unwrap();
+        let db_conn = pool.get().unwrap();
+        let total = embedded_migrations().len();
+
+        assert_eq!(pending_migrations(&db_conn).unwrap().len(), total);
+
+        let applied = run_pending_migrations(&db_conn).unwrap();
+        assert_eq!(applied.len(), total);
+        assert!(pending_migrations(&db_conn).unwrap().is_empty());
+
+        let reverted = revert_migrations(&db_conn, 2).unwrap();
+        assert_eq!(
+            reverted,
+            vec![
+                "2022-08-08-090000_pinned_fund".to_string(),
+                "2022-08-01-090000_fund_snapshot_tag".to_string(),
+            ]
+        );
+        let status = migrations_status(&db_conn).unwrap();
+        assert!(status[..total - 2].iter().all(|status| status.applied));
+        assert!(status[total - 2..].iter().all(|status| !status.applied));
+
+        // re-applying the reverted migrations must succeed
+        assert_eq!(run_pending_migrations(&db_conn).unwrap().len(), 2);
+
+        // reverting more migrations than applied reverts all of them
+        assert_eq!(revert_migrations(&db_conn, total + 1).unwrap().len(), total);
+        assert_eq!(pending_migrations(&db_conn).unwrap().len(), total);
+    }
 }
@@ -1,22 +1,33 @@
+pub mod backup;
+pub mod integrity;
 pub mod migrations;
 pub mod models;
 pub mod queries;
 pub mod schema;
+pub mod validation;
 pub mod views_schema;
 
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
-use diesel::sqlite::SqliteConnection;
 use diesel::Connection;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
-// TODO: Right now this is forced as the current backend. But it should be abstracted so it works for any diesel::Backend
+
+// The storage backend is selected at compile time. Sqlite is the default one, postgres can be
+// enabled through the `postgres` feature.
+#[cfg(not(feature = "postgres"))]
 type Db = diesel::sqlite::Sqlite;
-pub type DbConnection = SqliteConnection;
+#[cfg(not(feature = "postgres"))]
+pub type DbConnection = diesel::sqlite::SqliteConnection;
+
+#[cfg(feature = "postgres")]
+type Db = diesel::pg::Pg;
+#[cfg(feature = "postgres")]
+pub type DbConnection = diesel::pg::PgConnection;
 
 pub type DbPoolConn = PooledConnection<ConnectionManager<DbConnection>>;
 pub type DbConnectionPool = Pool<ConnectionManager<DbConnection>>;
 
-// ⚠ WARNING ⚠ : This query is sqlite specific, would need to be changed if backend changes
+#[cfg(not(feature = "postgres"))]
 const TEST_CONN_QUERY: &str = "
 SELECT
     name
@@ -27,8 +38,18 @@ WHERE
     name NOT LIKE 'sqlite_%';
 ";
 
+#[cfg(feature = "postgres")]
+const TEST_CONN_QUERY: &str = "
+SELECT
+    table_name
+FROM
+    information_schema.tables
+WHERE
+    table_schema = 'public';
+";
+
 pub fn load_db_connection_pool(db_url: &str) -> Result<DbConnectionPool, Error> {
-    let manager = ConnectionManager::<SqliteConnection>::new(db_url);
+    let manager = ConnectionManager::<DbConnection>::new(db_url);
     let pool = Pool::builder().build(manager)?;
 
     // test db connection or bubble up error
@@ -53,6 +53,21 @@ pub struct Fund {
     pub survey_url: String,
     #[serde(default = "BTreeSet::new")]
     pub groups: BTreeSet<Group>,
+    /// Tag of the snapshot holding the voting power of the fund
+    #[serde(alias = "snapshotTag", default)]
+    pub snapshot_tag: Option<String>,
+}
+
+impl Fund {
+    /// Whether the snapshot voting group takes part in the fund, any group does if the fund has
+    /// none
+    pub fn has_voting_group(&self, voting_group: &str) -> bool {
+        self.groups.is_empty()
+            || self
+                .groups
+                .iter()
+                .any(|group| group.group_id == voting_group)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -89,6 +104,89 @@ pub struct FundStageDates {
     pub tallying_end: i64,
 }
 
+/// Stages of a fund, in chronological order
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FundStage {
+    Upcoming,
+    InsightSharing,
+    ProposalSubmission,
+    RefineProposals,
+    FinalizeProposals,
+    ProposalAssessment,
+    AssessmentQa,
+    Snapshot,
+    Voting,
+    Tallying,
+    Finished,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NextFundStage {
+    pub stage: FundStage,
+    #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
+    #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
+    pub start: i64,
+    /// Seconds left until the stage starts
+    pub time_remaining: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FundStageInfo {
+    pub stage: FundStage,
+    /// Stage following the current one, none once the fund is finished
+    pub next: Option<NextFundStage>,
+}
+
+impl FundStageDates {
+    /// Start time of every stage following `Upcoming`
+    fn stage_starts(&self) -> [(FundStage, i64); 10] {
+        [
+            (FundStage::InsightSharing, self.insight_sharing_start),
+            (
+                FundStage::ProposalSubmission,
+                self.proposal_submission_start,
+            ),
+            (FundStage::RefineProposals, self.refine_proposals_start),
+            (FundStage::FinalizeProposals, self.finalize_proposals_start),
+            (
+                FundStage::ProposalAssessment,
+                self.proposal_assessment_start,
+            ),
+            (FundStage::AssessmentQa, self.assessment_qa_start),
+            (FundStage::Snapshot, self.snapshot_start),
+            (FundStage::Voting, self.voting_start),
+            (FundStage::Tallying, self.voting_end),
+            (FundStage::Finished, self.tallying_end),
+        ]
+    }
+
+    /// Stage of the fund at the given unix timestamp. A stage lasts from its start time until the
+    /// start time of the following stage, stages starting at the same time as the following one
+    /// are skipped.
+    pub fn stage_at(&self, timestamp: i64) -> FundStageInfo {
+        let starts = self.stage_starts();
+        let current = starts.iter().rposition(|(_, start)| *start <= timestamp);
+        let stage = current.map_or(FundStage::Upcoming, |current| starts[current].0);
+
+        let following = &starts[current.map_or(0, |current| current + 1)..];
+        let next = following.first().and_then(|(_, next_start)| {
+            following
+                .iter()
+                .take_while(|(_, start)| start == next_start)
+                .last()
+        });
+        FundStageInfo {
+            stage,
+            next: next.map(|(stage, start)| NextFundStage {
+                stage: *stage,
+                start: *start,
+                time_remaining: start.saturating_sub(timestamp).max(0),
+            }),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct FundWithLegacyFields {
     id: i32,
@@ -173,6 +271,8 @@ impl Queryable<funds::SqlType, Db> for Fund {
         String,
         // survey_url
         String,
+        // snapshot_tag
+        Option<String>,
     );
 
     fn build(row: Self::Row) -> Self {
@@ -204,6 +304,7 @@ impl Queryable<funds::SqlType, Db> for Fund {
             results_url: row.19,
             survey_url: row.20,
             groups: Default::default(),
+            snapshot_tag: row.21,
         }
     }
 }
@@ -234,6 +335,7 @@ impl Insertable<funds::table> for Fund {
         diesel::dsl::Eq<funds::tallying_end, i64>,
         diesel::dsl::Eq<funds::results_url, String>,
         diesel::dsl::Eq<funds::survey_url, String>,
+        diesel::dsl::Eq<funds::snapshot_tag, Option<String>>,
     );
 
     fn values(self) -> Self::Values {
@@ -264,6 +366,7 @@ impl Insertable<funds::table> for Fund {
             funds::tallying_end.eq(self.stage_dates.tallying_end),
             funds::results_url.eq(self.results_url),
             funds::survey_url.eq(self.survey_url),
+            funds::snapshot_tag.eq(self.snapshot_tag),
         )
     }
 }
@@ -273,10 +376,10 @@ pub mod test {
     use crate::db::{
         models::{
             challenges::test as challenges_testing,
-            funds::{Fund, FundStageDates},
+            funds::{Fund, FundStage, FundStageDates, FundStageInfo, NextFundStage},
             goals::{Goal, InsertGoal},
             groups::Group,
-            voteplans::test as voteplans_testing,
+            voteplans::{test as voteplans_testing, Voteplan},
         },
         schema::{funds, goals, groups},
         DbConnectionPool,
@@ -288,6 +391,7 @@ pub mod test {
     pub fn get_test_fund(fund_id: Option<i32>) -> Fund {
         const FUND_ID: i32 = 42;
         let fund_id = fund_id.unwrap_or(FUND_ID);
+        let now = OffsetDateTime::now_utc().unix_timestamp();
 
         Fund {
             id: fund_id,
@@ -298,22 +402,27 @@ pub mod test {
             next_registration_snapshot_time: (OffsetDateTime::now_utc() + Duration::days(30))
                 .unix_timestamp(),
             voting_power_threshold: 100,
-            fund_start_time: OffsetDateTime::now_utc().unix_timestamp(),
-            fund_end_time: OffsetDateTime::now_utc().unix_timestamp(),
-            next_fund_start_time: OffsetDateTime::now_utc().unix_timestamp(),
-            chain_vote_plans: vec![voteplans_testing::get_test_voteplan_with_fund_id(fund_id)],
+            fund_start_time: now,
+            fund_end_time: now,
+            next_fund_start_time: now,
+            chain_vote_plans: vec![Voteplan {
+                chain_vote_start_time: now,
+                chain_vote_end_time: now,
+                chain_committee_end_time: now,
+                ..voteplans_testing::get_test_voteplan_with_fund_id(fund_id)
+            }],
             challenges: vec![challenges_testing::get_test_challenge_with_fund_id(fund_id)],
             stage_dates: FundStageDates {
-                insight_sharing_start: OffsetDateTime::now_utc().unix_timestamp(),
-                proposal_submission_start: OffsetDateTime::now_utc().unix_timestamp(),
-                refine_proposals_start: OffsetDateTime::now_utc().unix_timestamp(),
-                finalize_proposals_start: OffsetDateTime::now_utc().unix_timestamp(),
-                proposal_assessment_start: OffsetDateTime::now_utc().unix_timestamp(),
-                assessment_qa_start: OffsetDateTime::now_utc().unix_timestamp(),
-                snapshot_start: OffsetDateTime::now_utc().unix_timestamp(),
-                voting_start: OffsetDateTime::now_utc().unix_timestamp(),
-                voting_end: OffsetDateTime::now_utc().unix_timestamp(),
-                tallying_end: OffsetDateTime::now_utc().unix_timestamp(),
+                insight_sharing_start: now,
+                proposal_submission_start: now,
+                refine_proposals_start: now,
+                finalize_proposals_start: now,
+                proposal_assessment_start: now,
+                assessment_qa_start: now,
+                snapshot_start: now,
+                voting_start: now,
+                voting_end: now,
+                tallying_end: now,
             },
             goals: vec![Goal {
                 id: 1,
@@ -335,6 +444,7 @@ pub mod test {
                 },
             ])
             .collect(),
+            snapshot_tag: Some("fund_snapshot".to_string()),
         }
     }
 
@@ -380,4 +490,84 @@ pub mod test {
                 .unwrap();
         }
     }
+
+    #[test]
+    fn fund_stage_boundaries() {
+        let dates = FundStageDates {
+            insight_sharing_start: 10,
+            proposal_submission_start: 20,
+            refine_proposals_start: 30,
+            finalize_proposals_start: 40,
+            proposal_assessment_start: 50,
+            assessment_qa_start: 60,
+            snapshot_start: 70,
+            voting_start: 80,
+            voting_end: 90,
+            tallying_end: 100,
+        };
+        let stages = [
+            FundStage::InsightSharing,
+            FundStage::ProposalSubmission,
+            FundStage::RefineProposals,
+            FundStage::FinalizeProposals,
+            FundStage::ProposalAssessment,
+            FundStage::AssessmentQa,
+            FundStage::Snapshot,
+            FundStage::Voting,
+            FundStage::Tallying,
+            FundStage::Finished,
+        ];
+
+        let mut previous = FundStage::Upcoming;
+        for (i, stage) in stages.into_iter().enumerate() {
+            let start = 10 * (i as i64 + 1);
+            let before_start = dates.stage_at(start - 1);
+            assert_eq!(before_start.stage, previous);
+            assert_eq!(
+                before_start.next,
+                Some(NextFundStage {
+                    stage,
+                    start,
+                    time_remaining: 1,
+                })
+            );
+            assert_eq!(dates.stage_at(start).stage, stage);
+            previous = stage;
+        }
+
+        let finished = FundStageInfo {
+            stage: FundStage::Finished,
+            next: None,
+        };
+        assert_eq!(dates.stage_at(100), finished);
+        assert_eq!(dates.stage_at(i64::MAX), finished);
+        assert_eq!(dates.stage_at(i64::MIN).stage, FundStage::Upcoming);
+    }
+
+    #[test]
+    fn fund_stage_skips_empty_stages() {
+        let dates = FundStageDates {
+            insight_sharing_start: 10,
+            proposal_submission_start: 20,
+            refine_proposals_start: 20,
+            finalize_proposals_start: 20,
+            proposal_assessment_start: 50,
+            assessment_qa_start: 60,
+            snapshot_start: 70,
+            voting_start: 80,
+            voting_end: 90,
+            tallying_end: 100,
+        };
+
+        assert_eq!(
+            dates.stage_at(15).next,
+            Some(NextFundStage {
+                stage: FundStage::FinalizeProposals,
+                start: 20,
+                time_remaining: 5,
+            })
+        );
+        assert_eq!(dates.stage_at(20).stage, FundStage::FinalizeProposals);
+        assert_eq!(dates.stage_at(49).stage, FundStage::FinalizeProposals);
+    }
 }
@@ -1,4 +1,6 @@
-use crate::db::schema::{contributions, snapshots, voters};
+use crate::db::schema::{
+    contributions, raw_snapshot_registrations, raw_snapshots, snapshots, voters,
+};
 use diesel::{Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +14,9 @@ pub struct Snapshot {
     #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
     #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
     pub last_updated: i64,
+    /// SHA-256 of the JSON export of the snapshot content, missing until it is computed
+    #[serde(default)]
+    pub content_hash: Option<String>,
 }
 
 // impl Insertable<snapshots::table> for Snapshot {
@@ -67,3 +72,28 @@ pub struct Contribution {
     pub voting_group: String,
     pub snapshot_tag: String,
 }
+
+/// Processing parameters of a snapshot tag imported from a raw snapshot
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = "raw_snapshots")]
+#[serde(rename_all = "camelCase")]
+pub struct RawSnapshot {
+    pub tag: String,
+    pub min_stake_threshold: i64,
+    /// JSON serialization of the voting power cap fraction
+    pub voting_power_cap: String,
+    pub direct_voters_group: Option<String>,
+    pub representatives_group: Option<String>,
+    pub registrations_count: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = "raw_snapshot_registrations")]
+#[serde(rename_all = "camelCase")]
+pub struct RawSnapshotRegistration {
+    pub tag: String,
+    /// Position of the registration in the raw snapshot
+    pub registration_index: i64,
+    /// JSON serialization of the registration
+    pub registration: String,
+}
@@ -80,11 +80,12 @@ mod test {
         load_db_connection_pool, migrations as db_testing, models::api_tokens::ApiTokenData,
         DbConnectionPool,
     };
+    use crate::v0::context::test::new_test_db_url;
 
     #[tokio::test]
     async fn api_token_insert_and_retrieve() {
         // initialize db
-        let pool: DbConnectionPool = load_db_connection_pool("").unwrap();
+        let pool: DbConnectionPool = load_db_connection_pool(&new_test_db_url()).unwrap();
         db_testing::initialize_db_with_migration(&pool.get().unwrap());
 
         // checks
@@ -1,28 +1,27 @@
 use crate::db::{
     models::{
         challenges::Challenge,
-        funds::{Fund, FundStageDates},
+        funds::{Fund, FundStageDates, FundStageInfo},
         goals::Goal,
         groups::Group,
         voteplans::Voteplan,
     },
     schema::{
         challenges::dsl as challenges_dsl, funds, funds::dsl as fund_dsl, goals::dsl as goals_dsl,
-        groups::dsl as groups_dsl, voteplans::dsl as voteplans_dsl,
+        groups::dsl as groups_dsl, pinned_fund, pinned_fund::dsl as pinned_fund_dsl,
+        voteplans::dsl as voteplans_dsl,
     },
-    DbConnection, DbConnectionPool,
+    DbConnection, DbConnectionPool, DbPoolConn,
 };
 use crate::v0::errors::HandleError;
 use diesel::{
-    r2d2::{ConnectionManager, PooledConnection},
-    ExpressionMethods, Insertable, QueryDsl, QueryResult, RunQueryDsl, SqliteConnection,
+    Connection, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, QueryResult,
+    RunQueryDsl,
 };
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
-fn join_fund(
-    mut fund: Fund,
-    db_conn: &PooledConnection<ConnectionManager<SqliteConnection>>,
-) -> Result<Fund, HandleError> {
+fn join_fund(mut fund: Fund, db_conn: &DbPoolConn) -> Result<Fund, HandleError> {
     let id = fund.id;
 
     fund.chain_vote_plans = voteplans_dsl::voteplans
@@ -71,6 +70,8 @@ pub struct FundWithNext {
     pub fund: Fund,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub next: Option<FundNextInfo>,
+    /// Stage of the fund at the time of the request
+    pub stage: FundStageInfo,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -81,29 +82,81 @@ pub struct FundNextInfo {
     pub stage_dates: FundStageDates,
 }
 
-pub async fn query_current_fund(pool: &DbConnectionPool) -> Result<FundWithNext, HandleError> {
+/// Fund pinned as the current one by an administrator, if any
+fn pinned_fund(db_conn: &DbPoolConn) -> Result<Option<Fund>, HandleError> {
+    let pinned = pinned_fund_dsl::pinned_fund
+        .select(pinned_fund_dsl::fund_id)
+        .first::<i32>(db_conn)
+        .optional()
+        .map_err(|e| HandleError::InternalError(e.to_string()))?;
+
+    match pinned {
+        Some(id) => fund_dsl::funds
+            .filter(fund_dsl::id.eq(id))
+            .first::<Fund>(db_conn)
+            .optional()
+            .map_err(|e| HandleError::InternalError(e.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Select the current fund: the pinned one if any, otherwise the one running at `now`.
+/// If no fund is running and `fallback` is set, the latest fund that already ended is used.
+fn select_current_fund(
+    now: i64,
+    fallback: bool,
+    db_conn: &DbPoolConn,
+) -> Result<Fund, HandleError> {
+    if let Some(fund) = pinned_fund(db_conn)? {
+        return Ok(fund);
+    }
+
+    let running = fund_dsl::funds
+        .filter(fund_dsl::fund_start_time.le(now))
+        .filter(fund_dsl::fund_end_time.gt(now))
+        .order((fund_dsl::fund_start_time.desc(), fund_dsl::id.desc()))
+        .first::<Fund>(db_conn)
+        .optional()
+        .map_err(|e| HandleError::InternalError(e.to_string()))?;
+
+    let current = match running {
+        Some(fund) => Some(fund),
+        None if fallback => fund_dsl::funds
+            .filter(fund_dsl::fund_end_time.le(now))
+            .order((fund_dsl::fund_end_time.desc(), fund_dsl::id.desc()))
+            .first::<Fund>(db_conn)
+            .optional()
+            .map_err(|e| HandleError::InternalError(e.to_string()))?,
+        None => None,
+    };
+
+    current.ok_or_else(|| HandleError::NotFound("current fund not found".to_string()))
+}
+
+pub async fn query_current_fund(
+    pool: &DbConnectionPool,
+    fallback: bool,
+) -> Result<FundWithNext, HandleError> {
     let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
     tokio::task::spawn_blocking(move || {
-        let funds: Vec<Fund> = fund_dsl::funds
-            // TODO: Not sure if sorting by the PK is actually necessary
-            //
-            // this assumes that the next will be the second inserted
-            // and that the current is the first.
-            .order(fund_dsl::id)
-            .limit(2)
-            .load(&db_conn)
-            .map_err(|_e| HandleError::NotFound("fund".to_string()))?;
-
-        let mut funds = funds.into_iter();
-        let current = funds
-            .next()
-            .ok_or_else(|| HandleError::NotFound("current found not found".to_string()))?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let current = select_current_fund(now, fallback, &db_conn)?;
 
-        let next = funds.next();
+        // the next fund is the first one starting once the current one announced it would
+        let next: Option<Fund> = fund_dsl::funds
+            .filter(fund_dsl::id.ne(current.id))
+            .filter(fund_dsl::fund_start_time.gt(current.fund_start_time))
+            .filter(fund_dsl::fund_start_time.ge(current.next_fund_start_time))
+            .order((fund_dsl::fund_start_time.asc(), fund_dsl::id.asc()))
+            .first(&db_conn)
+            .optional()
+            .map_err(|e| HandleError::InternalError(e.to_string()))?;
 
         let current = join_fund(current, &db_conn)?;
+        let stage = current.stage_dates.stage_at(now);
 
         Ok(FundWithNext {
+            stage,
             fund: current,
             next: next.map(|f| FundNextInfo {
                 id: f.id,
@@ -116,6 +169,48 @@ pub async fn query_current_fund(pool: &DbConnectionPool) -> Result<FundWithNext,
     .map_err(|_e| HandleError::InternalError("Error executing request".to_string()))?
 }
 
+/// Pin the fund as the current one regardless of its dates
+pub async fn pin_current_fund(id: i32, pool: &DbConnectionPool) -> Result<(), HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        let exists = fund_dsl::funds
+            .filter(fund_dsl::id.eq(id))
+            .select(fund_dsl::id)
+            .first::<i32>(&db_conn)
+            .optional()
+            .map_err(|e| HandleError::InternalError(e.to_string()))?
+            .is_some();
+        if !exists {
+            return Err(HandleError::NotFound(format!("fund with id {}", id)));
+        }
+
+        db_conn
+            .transaction(|| {
+                diesel::delete(pinned_fund::table).execute(&db_conn)?;
+                diesel::insert_into(pinned_fund::table)
+                    .values(pinned_fund_dsl::fund_id.eq(id))
+                    .execute(&db_conn)
+            })
+            .map_err(|e| HandleError::InternalError(format!("Error pinning fund: {}", e)))?;
+        Ok(())
+    })
+    .await
+    .map_err(|_e| HandleError::InternalError("Error executing request".to_string()))?
+}
+
+/// Go back to selecting the current fund from the fund dates
+pub async fn unpin_current_fund(pool: &DbConnectionPool) -> Result<(), HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        diesel::delete(pinned_fund::table)
+            .execute(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error unpinning fund: {}", e)))?;
+        Ok(())
+    })
+    .await
+    .map_err(|_e| HandleError::InternalError("Error executing request".to_string()))?
+}
+
 pub async fn query_all_funds(pool: &DbConnectionPool) -> Result<Vec<i32>, HandleError> {
     let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
     tokio::task::spawn_blocking(move || {
@@ -128,6 +223,24 @@ pub async fn query_all_funds(pool: &DbConnectionPool) -> Result<Vec<i32>, Handle
     .map_err(|_e| HandleError::InternalError("Error executing request".to_string()))?
 }
 
+/// Snapshot tags the funds vote with
+pub async fn query_funds_snapshot_tags(
+    pool: &DbConnectionPool,
+) -> Result<Vec<String>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        fund_dsl::funds
+            .select(fund_dsl::snapshot_tag)
+            .filter(fund_dsl::snapshot_tag.is_not_null())
+            .distinct()
+            .load::<Option<String>>(&db_conn)
+            .map(|tags| tags.into_iter().flatten().collect())
+            .map_err(|_| HandleError::InternalError("Error retrieving funds".to_string()))
+    })
+    .await
+    .map_err(|_e| HandleError::InternalError("Error executing request".to_string()))?
+}
+
 pub fn insert_fund(fund: Fund, db_conn: &DbConnection) -> QueryResult<Fund> {
     diesel::insert_into(funds::table)
         .values(fund.values())
@@ -139,8 +252,47 @@ pub fn insert_fund(fund: Fund, db_conn: &DbConnection) -> QueryResult<Fund> {
 
 pub fn put_fund(fund: Fund, pool: &DbConnectionPool) -> Result<(), HandleError> {
     let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
-    diesel::replace_into(funds::table)
-        .values(fund.values())
+
+    #[cfg(not(feature = "postgres"))]
+    let query = diesel::replace_into(funds::table).values(fund.values());
+
+    #[cfg(feature = "postgres")]
+    let query = {
+        use diesel::pg::upsert::excluded;
+        diesel::insert_into(funds::table)
+            .values(fund.values())
+            .on_conflict(fund_dsl::id)
+            .do_update()
+            .set((
+                fund_dsl::fund_name.eq(excluded(fund_dsl::fund_name)),
+                fund_dsl::fund_goal.eq(excluded(fund_dsl::fund_goal)),
+                fund_dsl::registration_snapshot_time
+                    .eq(excluded(fund_dsl::registration_snapshot_time)),
+                fund_dsl::next_registration_snapshot_time
+                    .eq(excluded(fund_dsl::next_registration_snapshot_time)),
+                fund_dsl::voting_power_threshold.eq(excluded(fund_dsl::voting_power_threshold)),
+                fund_dsl::fund_start_time.eq(excluded(fund_dsl::fund_start_time)),
+                fund_dsl::fund_end_time.eq(excluded(fund_dsl::fund_end_time)),
+                fund_dsl::next_fund_start_time.eq(excluded(fund_dsl::next_fund_start_time)),
+                fund_dsl::insight_sharing_start.eq(excluded(fund_dsl::insight_sharing_start)),
+                fund_dsl::proposal_submission_start
+                    .eq(excluded(fund_dsl::proposal_submission_start)),
+                fund_dsl::refine_proposals_start.eq(excluded(fund_dsl::refine_proposals_start)),
+                fund_dsl::finalize_proposals_start.eq(excluded(fund_dsl::finalize_proposals_start)),
+                fund_dsl::proposal_assessment_start
+                    .eq(excluded(fund_dsl::proposal_assessment_start)),
+                fund_dsl::assessment_qa_start.eq(excluded(fund_dsl::assessment_qa_start)),
+                fund_dsl::snapshot_start.eq(excluded(fund_dsl::snapshot_start)),
+                fund_dsl::voting_start.eq(excluded(fund_dsl::voting_start)),
+                fund_dsl::voting_end.eq(excluded(fund_dsl::voting_end)),
+                fund_dsl::tallying_end.eq(excluded(fund_dsl::tallying_end)),
+                fund_dsl::results_url.eq(excluded(fund_dsl::results_url)),
+                fund_dsl::survey_url.eq(excluded(fund_dsl::survey_url)),
+                fund_dsl::snapshot_tag.eq(excluded(fund_dsl::snapshot_tag)),
+            ))
+    };
+
+    query
         .execute(&db_conn)
         .map_err(|_e| HandleError::InternalError("Error executing request".to_string()))?;
 
@@ -0,0 +1,269 @@
+//! Full text search expressions. Sqlite relies on the FTS5 indexes kept in sync by the
+//! `full_text_search` migration while postgres builds the text search vectors on the fly.
+//!
+//! Search terms only keep letters and digits, so they can be safely embedded in the
+//! generated SQL and never clash with the FTS query syntax.
+
+use crate::v0::{
+    endpoints::search::requests::{Column, Table},
+    errors::HandleError,
+};
+use diesel::sql_types::{Integer, Text};
+
+/// Text search over a single column (or every indexed column for `Column::Any`)
+#[derive(Debug, Clone)]
+pub(super) struct TextMatch {
+    column: Option<&'static str>,
+    terms: Vec<String>,
+}
+
+#[derive(Debug, QueryableByName)]
+pub(super) struct Snippet {
+    #[sql_type = "Integer"]
+    pub id: i32,
+    #[sql_type = "Text"]
+    pub snippet: String,
+}
+
+impl TextMatch {
+    pub fn new(table: Table, column: Column, search: &str) -> Result<Self, HandleError> {
+        let terms: Vec<String> = search
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|term| !term.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        // ignoring it would match every row
+        if terms.is_empty() && !search.is_empty() {
+            return Err(HandleError::BadRequest(format!(
+                "search {:?} has no letters or digits to match",
+                search
+            )));
+        }
+        Ok(Self {
+            column: indexed_column(table, column)?,
+            terms,
+        })
+    }
+
+    /// Empty searches match every row
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+fn order(descending: bool) -> &'static str {
+    if descending {
+        "DESC"
+    } else {
+        "ASC"
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+fn indexed_column(table: Table, column: Column) -> Result<Option<&'static str>, HandleError> {
+    match (table, column) {
+        (_, Column::Any) => Ok(None),
+        (_, Column::Title) => Ok(Some("title")),
+        (Table::Challenges, Column::Desc) => Ok(Some("description")),
+        (Table::Challenges, Column::Type) => Ok(Some("challenge_type")),
+        (Table::Proposals, Column::Desc) => Ok(Some("summary")),
+        (Table::Proposals, Column::Author) => Ok(Some("author")),
+        _ => Err(HandleError::BadRequest("invalid column".to_string())),
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+fn fts_table(table: Table) -> (&'static str, &'static str) {
+    match table {
+        Table::Challenges => ("challenges_fts", "challenges.internal_id"),
+        Table::Proposals => ("proposals_fts", "full_proposals_info.id"),
+    }
+}
+
+/// FTS5 query matching every term as a prefix, restricted to the match column if any
+#[cfg(not(feature = "postgres"))]
+fn fts_query(matches: &[TextMatch]) -> String {
+    matches
+        .iter()
+        .flat_map(|text_match| {
+            text_match
+                .terms
+                .iter()
+                .map(move |term| match text_match.column {
+                    Some(column) => format!("{} : \"{}\"*", column, term),
+                    None => format!("\"{}\"*", term),
+                })
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Condition selecting the rows matching the search
+#[cfg(not(feature = "postgres"))]
+pub(super) fn filter(table: Table, text_match: &TextMatch) -> String {
+    let (fts, id) = fts_table(table);
+    format!(
+        "{} IN (SELECT rowid FROM {} WHERE {} MATCH '{}')",
+        id,
+        fts,
+        fts,
+        fts_query(std::slice::from_ref(text_match))
+    )
+}
+
+/// Ordering by relevance of the matches, the higher the better
+#[cfg(not(feature = "postgres"))]
+pub(super) fn order_by_relevance(table: Table, matches: &[TextMatch], descending: bool) -> String {
+    let (fts, id) = fts_table(table);
+    // bm25 is negative, the lower the better
+    format!(
+        "(SELECT -bm25({}) FROM {} WHERE {} MATCH '{}' AND rowid = {}) {}",
+        fts,
+        fts,
+        fts,
+        fts_query(matches),
+        id,
+        order(descending)
+    )
+}
+
+/// Highlighted fragments of the indexed text of the given rows
+#[cfg(not(feature = "postgres"))]
+pub(super) fn snippets(table: Table, matches: &[TextMatch], ids: &[i32]) -> String {
+    let (fts, _) = fts_table(table);
+    format!(
+        "SELECT rowid AS id, snippet({}, -1, '<mark>', '</mark>', '...', 16) AS snippet \
+         FROM {} WHERE {} MATCH '{}' AND rowid IN ({})",
+        fts,
+        fts,
+        fts,
+        fts_query(matches),
+        join_ids(ids)
+    )
+}
+
+#[cfg(feature = "postgres")]
+const PROPOSALS_DOCUMENT: &str = "concat_ws(' ', full_proposals_info.proposal_title, \
+    full_proposals_info.proposal_summary, full_proposals_info.proposer_name, \
+    full_proposals_info.proposal_solution, full_proposals_info.proposal_brief, \
+    full_proposals_info.proposal_importance, full_proposals_info.proposal_goal, \
+    full_proposals_info.proposal_metrics)";
+
+#[cfg(feature = "postgres")]
+const CHALLENGES_DOCUMENT: &str =
+    "concat_ws(' ', challenges.title, challenges.description, challenges.challenge_type)";
+
+#[cfg(feature = "postgres")]
+fn indexed_column(table: Table, column: Column) -> Result<Option<&'static str>, HandleError> {
+    match (table, column) {
+        (_, Column::Any) => Ok(None),
+        (Table::Challenges, Column::Title) => Ok(Some("challenges.title")),
+        (Table::Challenges, Column::Desc) => Ok(Some("challenges.description")),
+        (Table::Challenges, Column::Type) => Ok(Some("challenges.challenge_type")),
+        (Table::Proposals, Column::Title) => Ok(Some("full_proposals_info.proposal_title")),
+        (Table::Proposals, Column::Desc) => Ok(Some("full_proposals_info.proposal_summary")),
+        (Table::Proposals, Column::Author) => Ok(Some("full_proposals_info.proposer_name")),
+        _ => Err(HandleError::BadRequest("invalid column".to_string())),
+    }
+}
+
+#[cfg(feature = "postgres")]
+fn document(table: Table) -> (&'static str, &'static str) {
+    match table {
+        Table::Challenges => (CHALLENGES_DOCUMENT, "challenges.internal_id"),
+        Table::Proposals => (PROPOSALS_DOCUMENT, "full_proposals_info.id"),
+    }
+}
+
+/// tsquery matching every term as a prefix
+#[cfg(feature = "postgres")]
+fn ts_query(matches: &[TextMatch]) -> String {
+    let terms = matches
+        .iter()
+        .flat_map(|text_match| text_match.terms.iter())
+        .map(|term| format!("{}:*", term))
+        .collect::<Vec<_>>()
+        .join(" & ");
+    format!("to_tsquery('english', '{}')", terms)
+}
+
+#[cfg(feature = "postgres")]
+pub(super) fn filter(table: Table, text_match: &TextMatch) -> String {
+    let text = text_match.column.unwrap_or_else(|| document(table).0);
+    format!(
+        "to_tsvector('english', {}) @@ {}",
+        text,
+        ts_query(std::slice::from_ref(text_match))
+    )
+}
+
+#[cfg(feature = "postgres")]
+pub(super) fn order_by_relevance(table: Table, matches: &[TextMatch], descending: bool) -> String {
+    format!(
+        "ts_rank(to_tsvector('english', {}), {}) {}",
+        document(table).0,
+        ts_query(matches),
+        order(descending)
+    )
+}
+
+#[cfg(feature = "postgres")]
+pub(super) fn snippets(table: Table, matches: &[TextMatch], ids: &[i32]) -> String {
+    let (text, id) = document(table);
+    let from = match table {
+        Table::Challenges => "challenges",
+        Table::Proposals => "full_proposals_info",
+    };
+    format!(
+        "SELECT {} AS id, ts_headline('english', {}, {}, \
+         'StartSel=<mark>, StopSel=</mark>, MaxWords=16, MinWords=8') AS snippet \
+         FROM {} WHERE {} IN ({})",
+        id,
+        text,
+        ts_query(matches),
+        from,
+        id,
+        join_ids(ids)
+    )
+}
+
+fn join_ids(ids: &[i32]) -> String {
+    ids.iter()
+        .map(i32::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(all(test, not(feature = "postgres")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn terms_are_sanitized() {
+        let text_match = TextMatch::new(
+            Table::Proposals,
+            Column::Title,
+            "Fund' OR 1=1; --\"Catalyst*",
+        )
+        .unwrap();
+        assert_eq!(text_match.terms, vec!["fund", "or", "1", "1", "catalyst"]);
+        assert_eq!(
+            fts_query(&[text_match]),
+            "title : \"fund\"* AND title : \"or\"* AND title : \"1\"* AND title : \"1\"* AND title : \"catalyst\"*"
+        );
+
+        let text_match = TextMatch::new(Table::Challenges, Column::Any, "").unwrap();
+        assert!(text_match.is_empty());
+        // punctuation only searches cannot match anything
+        assert!(TextMatch::new(Table::Challenges, Column::Any, "  -- ").is_err());
+        assert!(TextMatch::new(Table::Proposals, Column::Title, "!!!").is_err());
+    }
+
+    #[test]
+    fn invalid_columns() {
+        assert!(TextMatch::new(Table::Challenges, Column::Author, "foo").is_err());
+        assert!(TextMatch::new(Table::Proposals, Column::Type, "foo").is_err());
+        assert!(TextMatch::new(Table::Proposals, Column::Funds, "foo").is_err());
+        assert!(TextMatch::new(Table::Proposals, Column::Relevance, "foo").is_err());
+    }
+}
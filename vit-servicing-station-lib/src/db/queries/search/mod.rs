@@ -1,20 +1,30 @@
+mod full_text;
+
+use std::collections::HashMap;
 use std::convert::TryInto;
 
 use crate::{
-    db::{schema, DbConnection, DbConnectionPool},
+    db::{
+        models::{challenges::Challenge, proposals::FullProposalInfo},
+        schema, DbConnection, DbConnectionPool,
+    },
     v0::{
         endpoints::search::requests::{
-            Column, Constraint, OrderBy, SearchCountQuery, SearchQuery, SearchResponse, Table,
+            Column, Constraint, OrderBy, SearchCountQuery, SearchQuery, SearchResponse,
+            SearchResult, Table,
         },
         errors::HandleError,
     },
 };
 use diesel::{
     backend::Backend,
+    dsl::sql,
     expression_methods::ExpressionMethods,
     r2d2::{ConnectionManager, PooledConnection},
-    QueryDsl, RunQueryDsl, TextExpressionMethods,
+    sql_types::{Bool, Double},
+    QueryDsl, RunQueryDsl,
 };
+use full_text::{Snippet, TextMatch};
 
 pub async fn search_db(
     query: SearchQuery,
@@ -53,31 +63,41 @@ fn build_challenges_query<'a, DB: 'a + Backend>(
     filter: Vec<Constraint>,
     order_by: Vec<OrderBy>,
 ) -> Result<
-    diesel::query_builder::BoxedSelectStatement<
-        'a,
-        ChallengesSelectST,
-        schema::challenges::table,
-        DB,
-    >,
+    (
+        diesel::query_builder::BoxedSelectStatement<
+            'a,
+            ChallengesSelectST,
+            schema::challenges::table,
+            DB,
+        >,
+        Vec<TextMatch>,
+    ),
     HandleError,
 > {
     use crate::db::schema::challenges::dsl::*;
     use Column::*;
 
     let mut query = challenges.into_boxed();
+    let mut matches = Vec::new();
 
     for Constraint { search, column } in filter {
-        let search = format!("%{search}%");
-        query = match column {
-            Title => query.filter(title.like(search)),
-            Desc => query.filter(description.like(search)),
-            Type => query.filter(challenge_type.like(search)),
-            _ => return Err(HandleError::BadRequest("invalid column".to_string())),
+        let text_match = TextMatch::new(Table::Challenges, column, &search)?;
+        if !text_match.is_empty() {
+            query = query.filter(sql::<Bool>(&full_text::filter(
+                Table::Challenges,
+                &text_match,
+            )));
+            matches.push(text_match);
         }
     }
 
     for OrderBy { column, descending } in order_by {
         query = match (descending, column) {
+            (_, Relevance) => query.then_order_by(sql::<Double>(&order_by_relevance(
+                Table::Challenges,
+                &matches,
+                descending,
+            )?)),
             (false, Title) => query.then_order_by(title),
             (false, Desc) => query.then_order_by(description),
             (false, Type) => query.then_order_by(challenge_type),
@@ -87,7 +107,7 @@ fn build_challenges_query<'a, DB: 'a + Backend>(
             _ => return Err(HandleError::BadRequest("invalid column".to_string())),
         }
     }
-    Ok(query)
+    Ok((query, matches))
 }
 
 type SelectProposalsST = (
@@ -130,12 +150,15 @@ fn build_proposals_query<'a, DB: 'a + Backend>(
     filter: Vec<Constraint>,
     order_by: Vec<OrderBy>,
 ) -> Result<
-    diesel::query_builder::BoxedSelectStatement<
-        'a,
-        SelectProposalsST,
-        crate::db::views_schema::full_proposals_info::table,
-        DB,
-    >,
+    (
+        diesel::query_builder::BoxedSelectStatement<
+            'a,
+            SelectProposalsST,
+            crate::db::views_schema::full_proposals_info::table,
+            DB,
+        >,
+        Vec<TextMatch>,
+    ),
     HandleError,
 > {
     use crate::db::views_schema::full_proposals_info::dsl::*;
@@ -143,19 +166,26 @@ fn build_proposals_query<'a, DB: 'a + Backend>(
     use Column::*;
 
     let mut query = proposals.into_boxed();
+    let mut matches = Vec::new();
 
     for Constraint { search, column } in filter {
-        let search = format!("%{search}%");
-        query = match column {
-            Title => query.filter(proposal_title.like(search)),
-            Desc => query.filter(proposal_summary.like(search)),
-            Author => query.filter(proposer_name.like(search)),
-            _ => return Err(HandleError::BadRequest("invalid column".to_string())),
+        let text_match = TextMatch::new(Table::Proposals, column, &search)?;
+        if !text_match.is_empty() {
+            query = query.filter(sql::<Bool>(&full_text::filter(
+                Table::Proposals,
+                &text_match,
+            )));
+            matches.push(text_match);
         }
     }
 
     for OrderBy { column, descending } in order_by {
         query = match (descending, column) {
+            (_, Relevance) => query.then_order_by(sql::<Double>(&order_by_relevance(
+                Table::Proposals,
+                &matches,
+                descending,
+            )?)),
             (false, Title) => query.then_order_by(proposal_title),
             (false, Desc) => query.then_order_by(proposal_summary),
             (false, Author) => query.then_order_by(proposer_name),
@@ -167,7 +197,39 @@ fn build_proposals_query<'a, DB: 'a + Backend>(
             _ => return Err(HandleError::BadRequest("invalid column".to_string())),
         }
     }
-    Ok(query)
+    Ok((query, matches))
+}
+
+fn order_by_relevance(
+    table: Table,
+    matches: &[TextMatch],
+    descending: bool,
+) -> Result<String, HandleError> {
+    if matches.is_empty() {
+        return Err(HandleError::BadRequest(
+            "ordering by relevance requires a search filter".to_string(),
+        ));
+    }
+    Ok(full_text::order_by_relevance(table, matches, descending))
+}
+
+/// Highlighted fragments of the matched text indexed by row id
+fn load_snippets(
+    table: Table,
+    matches: &[TextMatch],
+    ids: &[i32],
+    conn: &PooledConnection<ConnectionManager<DbConnection>>,
+) -> Result<HashMap<i32, String>, HandleError> {
+    if matches.is_empty() || ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let snippets: Vec<Snippet> = diesel::sql_query(full_text::snippets(table, matches, ids))
+        .load(conn)
+        .map_err(|_| HandleError::InternalError("error searching".to_string()))?;
+    Ok(snippets
+        .into_iter()
+        .map(|Snippet { id, snippet }| (id, snippet))
+        .collect())
 }
 
 fn search(
@@ -186,7 +248,7 @@ fn search(
 
     match table {
         Table::Challenges => {
-            let mut query = build_challenges_query(filter, order_by)?;
+            let (mut query, matches) = build_challenges_query(filter, order_by)?;
 
             if let Some(limit) = limit {
                 query = query.limit(map_limit(limit)?);
@@ -196,13 +258,22 @@ fn search(
                 query = query.offset(map_offset(offset)?);
             }
 
-            let vec = query
+            let vec: Vec<Challenge> = query
                 .load(conn)
                 .map_err(|_| HandleError::InternalError("error searching".to_string()))?;
-            Ok(SearchResponse::Challenge(vec))
+            let ids: Vec<i32> = vec.iter().map(|challenge| challenge.internal_id).collect();
+            let mut snippets = load_snippets(Table::Challenges, &matches, &ids, conn)?;
+            Ok(SearchResponse::Challenge(
+                vec.into_iter()
+                    .map(|challenge| SearchResult {
+                        snippet: snippets.remove(&challenge.internal_id),
+                        item: challenge,
+                    })
+                    .collect(),
+            ))
         }
         Table::Proposals => {
-            let mut query = build_proposals_query(filter, order_by)?;
+            let (mut query, matches) = build_proposals_query(filter, order_by)?;
 
             if let Some(limit) = limit {
                 query = query.limit(map_limit(limit)?);
@@ -212,10 +283,22 @@ fn search(
                 query = query.offset(map_offset(offset)?);
             }
 
-            let vec = query
+            let vec: Vec<FullProposalInfo> = query
                 .load(conn)
                 .map_err(|_| HandleError::InternalError("error searching".to_string()))?;
-            Ok(SearchResponse::Proposal(vec))
+            let ids: Vec<i32> = vec
+                .iter()
+                .map(|proposal| proposal.proposal.internal_id)
+                .collect();
+            let mut snippets = load_snippets(Table::Proposals, &matches, &ids, conn)?;
+            Ok(SearchResponse::Proposal(
+                vec.into_iter()
+                    .map(|proposal| SearchResult {
+                        snippet: snippets.remove(&proposal.proposal.internal_id),
+                        item: proposal,
+                    })
+                    .collect(),
+            ))
         }
     }
 }
@@ -242,7 +325,7 @@ fn search_count(
 ) -> Result<i64, HandleError> {
     match table {
         Table::Challenges => {
-            let query = build_challenges_query(filter, order_by)?;
+            let (query, _) = build_challenges_query(filter, order_by)?;
 
             let count = query
                 .count()
@@ -251,7 +334,7 @@ fn search_count(
             Ok(count)
         }
         Table::Proposals => {
-            let query = build_proposals_query(filter, order_by)?;
+            let (query, _) = build_proposals_query(filter, order_by)?;
 
             let count = query
                 .count()
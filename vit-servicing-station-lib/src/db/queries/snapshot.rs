@@ -1,12 +1,219 @@
 use crate::{
     db::{
-        models::snapshot::{Contribution, Snapshot, Voter},
-        schema::{contributions, snapshots, voters},
+        models::snapshot::{Contribution, RawSnapshot, RawSnapshotRegistration, Snapshot, Voter},
+        schema::{
+            contributions, raw_snapshot_registrations, raw_snapshots, snapshot_voting_groups,
+            snapshots, voters,
+        },
         DbConnection, DbConnectionPool,
     },
     v0::errors::HandleError,
 };
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+#[cfg(feature = "postgres")]
+use diesel::pg::upsert::excluded;
+use diesel::sql_types::{BigInt, Double, Text};
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, QueryDsl, QueryResult, RunQueryDsl,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// Voting keys with a different set of (voting group, voting power) entries in each tag
+const CHANGED_VOTING_KEYS_QUERY: &str = "
+SELECT voting_key FROM (
+    SELECT voting_key, voting_group, voting_power FROM voters WHERE snapshot_tag = {from}
+    EXCEPT
+    SELECT voting_key, voting_group, voting_power FROM voters WHERE snapshot_tag = {to}
+) AS removed_voters
+UNION
+SELECT voting_key FROM (
+    SELECT voting_key, voting_group, voting_power FROM voters WHERE snapshot_tag = {to}
+    EXCEPT
+    SELECT voting_key, voting_group, voting_power FROM voters WHERE snapshot_tag = {from}
+) AS added_voters
+";
+
+/// Stake keys with a different set of contributions in each tag
+const CHANGED_STAKE_KEYS_QUERY: &str = "
+SELECT stake_public_key FROM (
+    SELECT stake_public_key, reward_address, value, voting_key, voting_group
+    FROM contributions WHERE snapshot_tag = {from}
+    EXCEPT
+    SELECT stake_public_key, reward_address, value, voting_key, voting_group
+    FROM contributions WHERE snapshot_tag = {to}
+) AS removed_contributions
+UNION
+SELECT stake_public_key FROM (
+    SELECT stake_public_key, reward_address, value, voting_key, voting_group
+    FROM contributions WHERE snapshot_tag = {to}
+    EXCEPT
+    SELECT stake_public_key, reward_address, value, voting_key, voting_group
+    FROM contributions WHERE snapshot_tag = {from}
+) AS added_contributions
+";
+
+const VOTING_GROUPS_SUMMARY_QUERY: &str = "
+SELECT voting_group, total_voting_power, voters_count
+FROM snapshot_voting_groups
+WHERE snapshot_tag = {tag}
+ORDER BY voting_group;
+";
+
+/// Totals of every voting group of a snapshot tag, written once all of its voters are
+const REFRESH_VOTING_GROUPS_QUERY: &str = "
+INSERT INTO snapshot_voting_groups (snapshot_tag, voting_group, total_voting_power, voters_count)
+SELECT snapshot_tag, voting_group, SUM(voting_power), COUNT(*)
+FROM voters
+WHERE snapshot_tag = {tag}
+GROUP BY snapshot_tag, voting_group;
+";
+
+/// Voting power of a voting key in each of its voting groups along with its delegations and
+/// the total voting power of the group, ordered by snapshot update time
+const VOTER_AGGREGATES_QUERY: &str = "
+SELECT
+    voters.snapshot_tag,
+    snapshots.last_updated,
+    voters.voting_key,
+    voters.voting_group,
+    voters.voting_power,
+    COUNT(contributions.stake_public_key) AS delegations_count,
+    CAST(COALESCE(SUM(contributions.value), 0) AS BIGINT) AS delegations_power,
+    COALESCE(MAX(snapshot_voting_groups.total_voting_power), 0) AS total_voting_power
+FROM voters
+    INNER JOIN snapshots ON snapshots.tag = voters.snapshot_tag
+    LEFT JOIN contributions ON contributions.snapshot_tag = voters.snapshot_tag
+        AND contributions.voting_key = voters.voting_key
+        AND contributions.voting_group = voters.voting_group
+    LEFT JOIN snapshot_voting_groups ON snapshot_voting_groups.snapshot_tag = voters.snapshot_tag
+        AND snapshot_voting_groups.voting_group = voters.voting_group
+WHERE {condition}
+GROUP BY
+    voters.snapshot_tag,
+    snapshots.last_updated,
+    voters.voting_key,
+    voters.voting_group,
+    voters.voting_power
+ORDER BY snapshots.last_updated, voters.snapshot_tag, voters.voting_key, voters.voting_group;
+";
+
+/// Aggregates of the voting power distribution of every voting group. Voters are ranked by
+/// ascending voting power, percentiles use the nearest rank method and the Gini coefficient is
+/// computed from the rank weighted voting power.
+const VOTING_GROUPS_STATS_QUERY: &str = "
+WITH ranked_voters AS (
+    SELECT
+        voting_group,
+        voting_power,
+        ROW_NUMBER() OVER (PARTITION BY voting_group ORDER BY voting_power, voting_key) AS power_rank,
+        COUNT(*) OVER (PARTITION BY voting_group) AS voters_count
+    FROM voters
+    WHERE snapshot_tag = {tag}
+),
+group_delegators AS (
+    SELECT voting_group, COUNT(DISTINCT stake_public_key) AS delegators_count
+    FROM contributions
+    WHERE snapshot_tag = {tag}
+    GROUP BY voting_group
+)
+SELECT
+    ranked_voters.voting_group,
+    ranked_voters.voters_count,
+    CAST(SUM(voting_power) AS BIGINT) AS total_voting_power,
+    CAST(COALESCE(MAX(group_delegators.delegators_count), 0) AS BIGINT) AS delegators_count,
+    MIN(CASE WHEN power_rank * 100 >= ranked_voters.voters_count * 10 THEN voting_power END) AS p10,
+    MIN(CASE WHEN power_rank * 100 >= ranked_voters.voters_count * 25 THEN voting_power END) AS p25,
+    MIN(CASE WHEN power_rank * 100 >= ranked_voters.voters_count * 50 THEN voting_power END) AS p50,
+    MIN(CASE WHEN power_rank * 100 >= ranked_voters.voters_count * 75 THEN voting_power END) AS p75,
+    MIN(CASE WHEN power_rank * 100 >= ranked_voters.voters_count * 90 THEN voting_power END) AS p90,
+    MIN(CASE WHEN power_rank * 100 >= ranked_voters.voters_count * 99 THEN voting_power END) AS p99,
+    CAST(SUM(CASE WHEN power_rank > ranked_voters.voters_count - 10 THEN voting_power ELSE 0 END) AS BIGINT)
+        AS top_10_voting_power,
+    CAST(SUM(CASE WHEN power_rank > ranked_voters.voters_count - 100 THEN voting_power ELSE 0 END) AS BIGINT)
+        AS top_100_voting_power,
+    CASE
+        WHEN SUM(voting_power) = 0 THEN 0.0
+        ELSE 2.0 * SUM(CAST(power_rank AS DOUBLE PRECISION) * voting_power)
+            / (CAST(ranked_voters.voters_count AS DOUBLE PRECISION) * SUM(voting_power))
+            - CAST(ranked_voters.voters_count + 1 AS DOUBLE PRECISION) / ranked_voters.voters_count
+    END AS gini_coefficient
+FROM ranked_voters
+    LEFT JOIN group_delegators ON group_delegators.voting_group = ranked_voters.voting_group
+GROUP BY ranked_voters.voting_group, ranked_voters.voters_count
+ORDER BY ranked_voters.voting_group;
+";
+
+#[derive(Debug, QueryableByName)]
+struct ChangedKey {
+    #[sql_type = "Text"]
+    changed_key: String,
+}
+
+#[derive(Debug, QueryableByName)]
+struct KeysCount {
+    #[sql_type = "BigInt"]
+    count: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, QueryableByName)]
+pub struct VotingGroupSummary {
+    #[sql_type = "Text"]
+    pub voting_group: String,
+    #[sql_type = "BigInt"]
+    pub total_voting_power: i64,
+    #[sql_type = "BigInt"]
+    pub voters_count: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, QueryableByName)]
+pub struct VoterAggregates {
+    #[sql_type = "Text"]
+    pub snapshot_tag: String,
+    #[sql_type = "BigInt"]
+    pub last_updated: i64,
+    #[sql_type = "Text"]
+    pub voting_key: String,
+    #[sql_type = "Text"]
+    pub voting_group: String,
+    #[sql_type = "BigInt"]
+    pub voting_power: i64,
+    #[sql_type = "BigInt"]
+    pub delegations_count: i64,
+    #[sql_type = "BigInt"]
+    pub delegations_power: i64,
+    #[sql_type = "BigInt"]
+    pub total_voting_power: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, QueryableByName)]
+pub struct VotingGroupStatsRow {
+    #[sql_type = "Text"]
+    pub voting_group: String,
+    #[sql_type = "BigInt"]
+    pub voters_count: i64,
+    #[sql_type = "BigInt"]
+    pub total_voting_power: i64,
+    #[sql_type = "BigInt"]
+    pub delegators_count: i64,
+    #[sql_type = "BigInt"]
+    pub p10: i64,
+    #[sql_type = "BigInt"]
+    pub p25: i64,
+    #[sql_type = "BigInt"]
+    pub p50: i64,
+    #[sql_type = "BigInt"]
+    pub p75: i64,
+    #[sql_type = "BigInt"]
+    pub p90: i64,
+    #[sql_type = "BigInt"]
+    pub p99: i64,
+    #[sql_type = "BigInt"]
+    pub top_10_voting_power: i64,
+    #[sql_type = "BigInt"]
+    pub top_100_voting_power: i64,
+    #[sql_type = "Double"]
+    pub gini_coefficient: f64,
+}
 
 pub async fn query_all_snapshots(pool: &DbConnectionPool) -> Result<Vec<Snapshot>, HandleError> {
     let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
@@ -35,12 +242,21 @@ pub async fn query_snapshot_by_tag(
     .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
 }
 
-pub fn put_snapshot(snapshot: Snapshot, pool: &DbConnectionPool) -> Result<(), HandleError> {
-    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
-    diesel::replace_into(snapshots::table)
+pub fn put_snapshot(snapshot: Snapshot, db_conn: &DbConnection) -> QueryResult<()> {
+    #[cfg(not(feature = "postgres"))]
+    let query = diesel::replace_into(snapshots::table).values(snapshot);
+
+    #[cfg(feature = "postgres")]
+    let query = diesel::insert_into(snapshots::table)
         .values(snapshot)
-        .execute(&db_conn)
-        .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?;
+        .on_conflict(snapshots::tag)
+        .do_update()
+        .set((
+            snapshots::last_updated.eq(excluded(snapshots::last_updated)),
+            snapshots::content_hash.eq(excluded(snapshots::content_hash)),
+        ));
+
+    query.execute(db_conn)?;
     Ok(())
 }
 
@@ -61,51 +277,120 @@ pub async fn query_voters_by_voting_key_and_snapshot_tag(
     .map_err(|e| HandleError::InternalError(format!("Error executing voters: {}", e)))?
 }
 
-pub async fn query_total_voting_power_by_voting_group_and_snapshot_tag(
-    voting_group: String,
+/// Voter information matching `condition` in a single query, see `VOTER_AGGREGATES_QUERY`
+fn load_voter_aggregates(
+    condition: &str,
+    binds: Vec<String>,
+    db_conn: &DbConnection,
+) -> Result<Vec<VoterAggregates>, HandleError> {
+    let mut query =
+        diesel::sql_query(VOTER_AGGREGATES_QUERY.replace("{condition}", condition)).into_boxed();
+    for bind in binds {
+        query = query.bind::<Text, _>(bind);
+    }
+    query
+        .load(db_conn)
+        .map_err(|e| HandleError::InternalError(format!("Error loading voters: {}", e)))
+}
+
+pub async fn query_voter_aggregates_by_voting_key_and_snapshot_tag(
+    voting_key: String,
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<VoterAggregates>, HandleError> {
+    query_voter_aggregates_by_voting_keys_and_snapshot_tag(vec![voting_key], tag, pool).await
+}
+
+/// Maximum number of keys bound in a single query, sqlite fails above 999 bind parameters
+const MAX_BOUND_KEYS: usize = 500;
+
+/// Voter information of several voting keys, looked up `MAX_BOUND_KEYS` keys at a time
+pub async fn query_voter_aggregates_by_voting_keys_and_snapshot_tag(
+    mut voting_keys: Vec<String>,
     tag: String,
     pool: &DbConnectionPool,
-) -> Result<i64, HandleError> {
+) -> Result<Vec<VoterAggregates>, HandleError> {
+    if voting_keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    // sorted so the concatenated chunks keep the order of the query
+    voting_keys.sort_unstable();
     let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
     tokio::task::spawn_blocking(move || {
-        voters::dsl::voters
-            .filter(voters::dsl::voting_group.eq(voting_group))
-            .filter(voters::dsl::snapshot_tag.eq(tag))
-            .load::<Voter>(&db_conn)
-            .map_err(|e| HandleError::NotFound(format!("Error loading voters: {}", e)))
-            .map(|voters| voters.iter().map(|voter| voter.voting_power).sum())
+        let mut voters = Vec::new();
+        for chunk in voting_keys.chunks(MAX_BOUND_KEYS) {
+            let condition = format!(
+                "voters.snapshot_tag = {} AND voters.voting_key IN ({})",
+                bind_param(1),
+                (2..chunk.len() + 2).map(bind_param).join(", ")
+            );
+            let binds = std::iter::once(tag.clone())
+                .chain(chunk.iter().cloned())
+                .collect();
+            voters.extend(load_voter_aggregates(&condition, binds, &db_conn)?);
+        }
+        Ok(voters)
     })
     .await
-    .map_err(|e| HandleError::InternalError(format!("Error executing voters: {}", e)))?
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
 }
 
-pub fn batch_put_voters(voters: &[Voter], db_conn: &DbConnection) -> Result<(), HandleError> {
-    diesel::replace_into(voters::table)
-        .values(voters)
-        .execute(db_conn)
-        .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?;
-    Ok(())
+/// Voter information of a voting key in every snapshot tag
+pub async fn query_voter_aggregates_by_voting_key(
+    voting_key: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<VoterAggregates>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        load_voter_aggregates(
+            &format!("voters.voting_key = {}", bind_param(1)),
+            vec![voting_key],
+            &db_conn,
+        )
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
 }
 
-pub async fn query_contributions_by_voting_key_and_voter_group_and_snapshot_tag(
-    voting_key: String,
-    voting_group: String,
-    tag: String,
+pub async fn query_contributions_by_stake_public_key(
+    stake_public_key: String,
     pool: &DbConnectionPool,
 ) -> Result<Vec<Contribution>, HandleError> {
     let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
     tokio::task::spawn_blocking(move || {
         contributions::dsl::contributions
-            .filter(contributions::dsl::voting_key.eq(voting_key))
-            .filter(contributions::dsl::voting_group.eq(voting_group))
-            .filter(contributions::dsl::snapshot_tag.eq(tag))
+            .filter(contributions::dsl::stake_public_key.eq(stake_public_key))
+            .order_by((
+                contributions::dsl::snapshot_tag,
+                contributions::dsl::voting_key,
+                contributions::dsl::voting_group,
+            ))
             .load(&db_conn)
-            .map_err(|e| HandleError::NotFound(format!("Error loading contributions: {}", e)))
+            .map_err(|e| HandleError::InternalError(format!("Error loading contributions: {}", e)))
     })
     .await
     .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
 }
 
+pub fn batch_put_voters(voters: &[Voter], db_conn: &DbConnection) -> QueryResult<()> {
+    #[cfg(not(feature = "postgres"))]
+    let query = diesel::replace_into(voters::table).values(voters);
+
+    #[cfg(feature = "postgres")]
+    let query = diesel::insert_into(voters::table)
+        .values(voters)
+        .on_conflict((
+            voters::voting_key,
+            voters::voting_group,
+            voters::snapshot_tag,
+        ))
+        .do_update()
+        .set(voters::voting_power.eq(excluded(voters::voting_power)));
+
+    query.execute(db_conn)?;
+    Ok(())
+}
+
 pub async fn query_contributions_by_stake_public_key_and_snapshot_tag(
     stake_public_key: String,
     tag: String,
@@ -126,10 +411,548 @@ pub async fn query_contributions_by_stake_public_key_and_snapshot_tag(
 pub fn batch_put_contributions(
     contributions: &[Contribution],
     db_conn: &DbConnection,
-) -> Result<(), HandleError> {
-    diesel::replace_into(contributions::table)
+) -> QueryResult<()> {
+    #[cfg(not(feature = "postgres"))]
+    let query = diesel::replace_into(contributions::table).values(contributions);
+
+    #[cfg(feature = "postgres")]
+    let query = diesel::insert_into(contributions::table)
         .values(contributions)
-        .execute(db_conn)
-        .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?;
+        .on_conflict((
+            contributions::stake_public_key,
+            contributions::voting_key,
+            contributions::voting_group,
+            contributions::snapshot_tag,
+        ))
+        .do_update()
+        .set((
+            contributions::reward_address.eq(excluded(contributions::reward_address)),
+            contributions::value.eq(excluded(contributions::value)),
+        ));
+
+    query.execute(db_conn)?;
+    Ok(())
+}
+
+fn delete_snapshot_entries(tag: &str, db_conn: &DbConnection) -> QueryResult<()> {
+    diesel::delete(contributions::table.filter(contributions::snapshot_tag.eq(tag)))
+        .execute(db_conn)?;
+    diesel::delete(voters::table.filter(voters::snapshot_tag.eq(tag))).execute(db_conn)?;
+    diesel::delete(
+        snapshot_voting_groups::table.filter(snapshot_voting_groups::snapshot_tag.eq(tag)),
+    )
+    .execute(db_conn)?;
+    diesel::delete(
+        raw_snapshot_registrations::table.filter(raw_snapshot_registrations::tag.eq(tag)),
+    )
+    .execute(db_conn)?;
+    diesel::delete(raw_snapshots::table.filter(raw_snapshots::tag.eq(tag))).execute(db_conn)?;
+    Ok(())
+}
+
+/// Stores the registrations of a raw snapshot, its previous ones must have been deleted
+fn put_raw_snapshot(raw_snapshot: RawSnapshotContent, db_conn: &DbConnection) -> QueryResult<()> {
+    diesel::insert_into(raw_snapshots::table)
+        .values(raw_snapshot.raw_snapshot)
+        .execute(db_conn)?;
+    for registrations in raw_snapshot.registrations.chunks(MAX_INSERT_ROWS) {
+        diesel::insert_into(raw_snapshot_registrations::table)
+            .values(registrations)
+            .execute(db_conn)?;
+    }
+    Ok(())
+}
+
+/// Computes the voting groups totals of a tag, its previous totals must have been deleted
+fn put_voting_groups(tag: &str, db_conn: &DbConnection) -> QueryResult<()> {
+    diesel::sql_query(REFRESH_VOTING_GROUPS_QUERY.replace("{tag}", &bind_param(1)))
+        .bind::<Text, _>(tag)
+        .execute(db_conn)?;
+    Ok(())
+}
+
+/// Maximum number of rows written by a single insert statement, postgres only accepts up to
+/// 65535 bind parameters per statement
+const MAX_INSERT_ROWS: usize = 5000;
+
+/// Voters and contributions of a part of a snapshot
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotChunk {
+    pub voters: Vec<Voter>,
+    pub contributions: Vec<Contribution>,
+}
+
+/// Registrations and processing parameters of a snapshot tag imported from a raw snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawSnapshotContent {
+    pub raw_snapshot: RawSnapshot,
+    pub registrations: Vec<RawSnapshotRegistration>,
+}
+
+enum ReplaceSnapshotError {
+    Query(diesel::result::Error),
+    Input(HandleError),
+}
+
+impl From<diesel::result::Error> for ReplaceSnapshotError {
+    fn from(e: diesel::result::Error) -> Self {
+        ReplaceSnapshotError::Query(e)
+    }
+}
+
+/// Replace the whole content of a snapshot tag in a single transaction, so entries missing in
+/// the new content are dropped and readers see either the previous content or the new one.
+/// Chunks are consumed one at a time and the transaction is rolled back on the first error.
+/// Snapshots imported from a raw snapshot keep their registrations so they can be reprocessed.
+/// The hash of the new content is computed with `content_hash` and stored in the same transaction.
+pub fn replace_snapshot(
+    snapshot: Snapshot,
+    raw_snapshot: Option<RawSnapshotContent>,
+    chunks: impl IntoIterator<Item = Result<SnapshotChunk, HandleError>>,
+    content_hash: impl FnOnce(&str, &DbConnection) -> Result<String, HandleError>,
+    db_conn: &DbConnection,
+) -> Result<(), HandleError> {
+    let snapshot_tag = snapshot.tag.clone();
+    db_conn
+        .transaction(|| {
+            delete_snapshot_entries(&snapshot.tag, db_conn)?;
+            put_snapshot(snapshot, db_conn)?;
+            if let Some(raw_snapshot) = raw_snapshot {
+                put_raw_snapshot(raw_snapshot, db_conn)?;
+            }
+            for chunk in chunks {
+                let chunk = chunk.map_err(ReplaceSnapshotError::Input)?;
+                for voters in chunk.voters.chunks(MAX_INSERT_ROWS) {
+                    batch_put_voters(voters, db_conn)?;
+                }
+                for contributions in chunk.contributions.chunks(MAX_INSERT_ROWS) {
+                    batch_put_contributions(contributions, db_conn)?;
+                }
+            }
+            put_voting_groups(&snapshot_tag, db_conn)?;
+            let content_hash =
+                content_hash(&snapshot_tag, db_conn).map_err(ReplaceSnapshotError::Input)?;
+            put_snapshot_content_hash(&snapshot_tag, &content_hash, db_conn)?;
+            Ok(())
+        })
+        .map_err(|e| match e {
+            ReplaceSnapshotError::Query(e) => {
+                HandleError::InternalError(format!("Error replacing snapshot: {}", e))
+            }
+            ReplaceSnapshotError::Input(e) => e,
+        })
+}
+
+pub async fn query_raw_snapshots(pool: &DbConnectionPool) -> Result<Vec<RawSnapshot>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        raw_snapshots::dsl::raw_snapshots
+            .order_by(raw_snapshots::dsl::tag)
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading raw snapshots: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+pub async fn query_raw_snapshot_by_tag(
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<RawSnapshot, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        raw_snapshots::dsl::raw_snapshots
+            .filter(raw_snapshots::dsl::tag.eq(tag))
+            .first(&db_conn)
+            .map_err(|e| HandleError::NotFound(format!("Error loading raw snapshot: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+/// Registrations of a raw snapshot in their original order
+pub async fn query_raw_snapshot_registrations_by_tag(
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<RawSnapshotRegistration>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        raw_snapshot_registrations::dsl::raw_snapshot_registrations
+            .filter(raw_snapshot_registrations::dsl::tag.eq(tag))
+            .order_by(raw_snapshot_registrations::dsl::registration_index)
+            .load(&db_conn)
+            .map_err(|e| {
+                HandleError::InternalError(format!(
+                    "Error loading raw snapshot registrations: {}",
+                    e
+                ))
+            })
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+/// Delete a snapshot tag along with its voters and contributions, returns whether the tag existed.
+/// Entries are removed explicitly as sqlite only enforces `ON DELETE CASCADE` when foreign keys
+/// are enabled in the connection.
+pub fn delete_snapshot(tag: &str, db_conn: &DbConnection) -> Result<bool, HandleError> {
+    db_conn
+        .transaction(|| {
+            delete_snapshot_entries(tag, db_conn)?;
+            diesel::delete(snapshots::table.filter(snapshots::tag.eq(tag))).execute(db_conn)
+        })
+        .map(|deleted| deleted > 0)
+        .map_err(|e| HandleError::InternalError(format!("Error deleting snapshot: {}", e)))
+}
+
+/// Numbered bind parameter, each one can be used several times in the same query
+#[cfg(not(feature = "postgres"))]
+fn bind_param(index: usize) -> String {
+    format!("?{}", index)
+}
+
+#[cfg(feature = "postgres")]
+fn bind_param(index: usize) -> String {
+    format!("${}", index)
+}
+
+/// Page of the keys returned by `changed_keys_query`, along with the total count of them
+fn query_changed_keys(
+    changed_keys_query: &str,
+    key_column: &str,
+    from_tag: String,
+    to_tag: String,
+    limit: i64,
+    offset: i64,
+    db_conn: &DbConnection,
+) -> Result<(i64, Vec<String>), HandleError> {
+    let changed_keys = changed_keys_query
+        .replace("{from}", &bind_param(1))
+        .replace("{to}", &bind_param(2));
+
+    let KeysCount { count } = diesel::sql_query(format!(
+        "SELECT COUNT(*) AS count FROM ({}) AS changed_keys",
+        changed_keys
+    ))
+    .bind::<Text, _>(&from_tag)
+    .bind::<Text, _>(&to_tag)
+    .get_result(db_conn)
+    .map_err(|e| HandleError::InternalError(format!("Error counting changes: {}", e)))?;
+
+    let keys: Vec<ChangedKey> = diesel::sql_query(format!(
+        "SELECT {key} AS changed_key FROM ({changed}) AS changed_keys ORDER BY {key} LIMIT {limit} OFFSET {offset}",
+        key = key_column,
+        changed = changed_keys,
+        limit = bind_param(3),
+        offset = bind_param(4),
+    ))
+    .bind::<Text, _>(&from_tag)
+    .bind::<Text, _>(&to_tag)
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .load(db_conn)
+    .map_err(|e| HandleError::InternalError(format!("Error loading changes: {}", e)))?;
+
+    Ok((
+        count,
+        keys.into_iter()
+            .map(|changed| changed.changed_key)
+            .collect(),
+    ))
+}
+
+/// Voting keys whose voters differ between both tags, ordered by voting key
+pub async fn query_changed_voting_keys(
+    from_tag: String,
+    to_tag: String,
+    limit: i64,
+    offset: i64,
+    pool: &DbConnectionPool,
+) -> Result<(i64, Vec<String>), HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        query_changed_keys(
+            CHANGED_VOTING_KEYS_QUERY,
+            "voting_key",
+            from_tag,
+            to_tag,
+            limit,
+            offset,
+            &db_conn,
+        )
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+/// Stake keys whose contributions differ between both tags, ordered by stake key
+pub async fn query_changed_stake_public_keys(
+    from_tag: String,
+    to_tag: String,
+    limit: i64,
+    offset: i64,
+    pool: &DbConnectionPool,
+) -> Result<(i64, Vec<String>), HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        query_changed_keys(
+            CHANGED_STAKE_KEYS_QUERY,
+            "stake_public_key",
+            from_tag,
+            to_tag,
+            limit,
+            offset,
+            &db_conn,
+        )
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+pub async fn query_voting_groups_summary_by_snapshot_tag(
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<VotingGroupSummary>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        diesel::sql_query(VOTING_GROUPS_SUMMARY_QUERY.replace("{tag}", &bind_param(1)))
+            .bind::<Text, _>(tag)
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading voters: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+pub async fn query_voting_groups_stats_by_snapshot_tag(
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<VotingGroupStatsRow>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        diesel::sql_query(VOTING_GROUPS_STATS_QUERY.replace("{tag}", &bind_param(1)))
+            .bind::<Text, _>(tag)
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error computing voters stats: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+pub async fn query_voters_by_voting_keys_and_snapshot_tags(
+    voting_keys: Vec<String>,
+    tags: Vec<String>,
+    pool: &DbConnectionPool,
+) -> Result<Vec<Voter>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        voters::dsl::voters
+            .filter(voters::dsl::voting_key.eq_any(voting_keys))
+            .filter(voters::dsl::snapshot_tag.eq_any(tags))
+            .order_by((voters::dsl::voting_key, voters::dsl::voting_group))
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading voters: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+/// Contributions of several stake keys, looked up `MAX_BOUND_KEYS` keys at a time
+pub async fn query_contributions_by_stake_public_keys_and_snapshot_tags(
+    mut stake_public_keys: Vec<String>,
+    tags: Vec<String>,
+    pool: &DbConnectionPool,
+) -> Result<Vec<Contribution>, HandleError> {
+    // sorted so the concatenated chunks keep the order of the query
+    stake_public_keys.sort_unstable();
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        let mut contributions = Vec::new();
+        for chunk in stake_public_keys.chunks(MAX_BOUND_KEYS) {
+            let chunk_contributions: Vec<Contribution> = contributions::dsl::contributions
+                .filter(contributions::dsl::stake_public_key.eq_any(chunk))
+                .filter(contributions::dsl::snapshot_tag.eq_any(&tags))
+                .order_by((
+                    contributions::dsl::stake_public_key,
+                    contributions::dsl::voting_key,
+                    contributions::dsl::voting_group,
+                ))
+                .load(&db_conn)
+                .map_err(|e| {
+                    HandleError::InternalError(format!("Error loading contributions: {}", e))
+                })?;
+            contributions.extend(chunk_contributions);
+        }
+        Ok(contributions)
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+/// Position of the last voter of a page, the following page starts right after it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VotersCursor {
+    pub voting_power: i64,
+    pub voting_key: String,
+    pub voting_group: String,
+}
+
+/// Selection of the voters of a snapshot tag. Voters are sorted by voting power, ties are broken
+/// by voting key and voting group so every voter has a stable position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VotersFilter {
+    pub voting_group: Option<String>,
+    pub min_voting_power: Option<i64>,
+    pub descending: bool,
+    pub after: Option<VotersCursor>,
+    pub limit: i64,
+}
+
+pub async fn query_voters_by_snapshot_tag(
+    tag: String,
+    filter: VotersFilter,
+    pool: &DbConnectionPool,
+) -> Result<Vec<Voter>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        let mut query = voters::table
+            .filter(voters::snapshot_tag.eq(tag))
+            .into_boxed();
+
+        if let Some(voting_group) = filter.voting_group {
+            query = query.filter(voters::voting_group.eq(voting_group));
+        }
+        if let Some(min_voting_power) = filter.min_voting_power {
+            query = query.filter(voters::voting_power.ge(min_voting_power));
+        }
+        if let Some(cursor) = filter.after {
+            let same_power_after = voters::voting_power.eq(cursor.voting_power).and(
+                voters::voting_key
+                    .gt(cursor.voting_key.clone())
+                    .or(voters::voting_key
+                        .eq(cursor.voting_key)
+                        .and(voters::voting_group.gt(cursor.voting_group))),
+            );
+            query = if filter.descending {
+                query.filter(
+                    voters::voting_power
+                        .lt(cursor.voting_power)
+                        .or(same_power_after),
+                )
+            } else {
+                query.filter(
+                    voters::voting_power
+                        .gt(cursor.voting_power)
+                        .or(same_power_after),
+                )
+            };
+        }
+
+        query = if filter.descending {
+            query.order_by((
+                voters::voting_power.desc(),
+                voters::voting_key.asc(),
+                voters::voting_group.asc(),
+            ))
+        } else {
+            query.order_by((
+                voters::voting_power.asc(),
+                voters::voting_key.asc(),
+                voters::voting_group.asc(),
+            ))
+        };
+
+        query
+            .limit(filter.limit)
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading voters: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+pub async fn query_contributions_by_snapshot_tag(
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<Contribution>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        contributions::dsl::contributions
+            .filter(contributions::dsl::snapshot_tag.eq(tag))
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading contributions: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+pub async fn query_contributions_by_voting_keys_and_snapshot_tag(
+    voting_keys: Vec<String>,
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<Contribution>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        contributions::dsl::contributions
+            .filter(contributions::dsl::voting_key.eq_any(voting_keys))
+            .filter(contributions::dsl::snapshot_tag.eq(tag))
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading contributions: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+pub fn put_snapshot_content_hash(
+    tag: &str,
+    content_hash: &str,
+    db_conn: &DbConnection,
+) -> QueryResult<()> {
+    diesel::update(snapshots::table.filter(snapshots::tag.eq(tag)))
+        .set(snapshots::content_hash.eq(content_hash))
+        .execute(db_conn)?;
     Ok(())
 }
+
+/// Voters of a snapshot tag ordered by voting key and voting group, starting right after the
+/// given (voting key, voting group)
+pub fn load_voters_page(
+    tag: &str,
+    after: Option<(String, String)>,
+    limit: i64,
+    db_conn: &DbConnection,
+) -> QueryResult<Vec<Voter>> {
+    let mut query = voters::table
+        .filter(voters::snapshot_tag.eq(tag))
+        .into_boxed();
+    if let Some((voting_key, voting_group)) = after {
+        query = query.filter(
+            voters::voting_key
+                .gt(voting_key.clone())
+                .or(voters::voting_key
+                    .eq(voting_key)
+                    .and(voters::voting_group.gt(voting_group))),
+        );
+    }
+    query
+        .order_by((voters::voting_key.asc(), voters::voting_group.asc()))
+        .limit(limit)
+        .load(db_conn)
+}
+
+/// Contributions of the given voting keys, ordered by voting key, voting group and stake key
+pub fn load_contributions_by_voting_keys(
+    tag: &str,
+    voting_keys: Vec<String>,
+    db_conn: &DbConnection,
+) -> QueryResult<Vec<Contribution>> {
+    contributions::table
+        .filter(contributions::snapshot_tag.eq(tag))
+        .filter(contributions::voting_key.eq_any(voting_keys))
+        .order_by((
+            contributions::voting_key.asc(),
+            contributions::voting_group.asc(),
+            contributions::stake_public_key.asc(),
+        ))
+        .load(db_conn)
+}
@@ -1,4 +1,4 @@
-use crate::db::schema::votes;
+use crate::db::schema::{funds, voteplans, votes};
 use crate::db::DbConnection;
 use crate::{
     db::{models::vote::Vote, schema::votes::dsl as vote_dsl, DbConnectionPool},
@@ -22,6 +22,34 @@ pub async fn query_votes_by_caster_and_voteplan_id(
     .map_err(|_e| HandleError::InternalError("Error executing request".to_string()))?
 }
 
+/// Distinct casters of the votes in the voteplans of the funds using the snapshot tag
+pub async fn query_vote_casters_by_snapshot_tag(
+    tag: String,
+    pool: &DbConnectionPool,
+) -> Result<Vec<String>, HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        let fund_ids = funds::table
+            .filter(funds::snapshot_tag.eq(tag))
+            .select(funds::id);
+        let voteplan_ids: Vec<String> = voteplans::table
+            .filter(voteplans::fund_id.eq_any(fund_ids))
+            .select(voteplans::chain_voteplan_id)
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading voteplans: {}", e)))?;
+
+        vote_dsl::votes
+            .filter(vote_dsl::voteplan_id.eq_any(voteplan_ids))
+            .select(vote_dsl::caster)
+            .distinct()
+            .order_by(vote_dsl::caster)
+            .load(&db_conn)
+            .map_err(|e| HandleError::InternalError(format!("Error loading votes: {}", e)))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
 pub fn batch_insert_votes_data(
     votes: &[<Vote as Insertable<votes::table>>::Values],
     db_conn: &DbConnection,
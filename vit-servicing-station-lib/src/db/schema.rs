@@ -40,6 +40,7 @@ table! {
     snapshots (tag) {
         tag -> Text,
         last_updated -> BigInt,
+        content_hash -> Nullable<Text>,
     }
 }
 
@@ -53,6 +54,37 @@ table! {
     }
 }
 
+table! {
+    // totals of the voters of each voting group, written along with the voters
+    snapshot_voting_groups (snapshot_tag, voting_group) {
+        snapshot_tag -> Text,
+        voting_group -> Text,
+        total_voting_power -> BigInt,
+        voters_count -> BigInt,
+    }
+}
+
+table! {
+    // processing parameters of the snapshot tags imported from a raw snapshot
+    raw_snapshots (tag) {
+        tag -> Text,
+        min_stake_threshold -> BigInt,
+        voting_power_cap -> Text,
+        direct_voters_group -> Nullable<Text>,
+        representatives_group -> Nullable<Text>,
+        registrations_count -> BigInt,
+    }
+}
+
+table! {
+    raw_snapshot_registrations (tag, registration_index) {
+        // should corresponds to the raw_snapshots::tag,
+        tag -> Text,
+        registration_index -> BigInt,
+        registration -> Text,
+    }
+}
+
 table! {
     contributions (stake_public_key, voting_key, voting_group, snapshot_tag) {
         stake_public_key -> Text,
@@ -90,6 +122,7 @@ table! {
         tallying_end -> BigInt,
         results_url -> Text,
         survey_url -> Text,
+        snapshot_tag -> Nullable<Text>,
     }
 }
 
@@ -109,6 +142,12 @@ table! {
     }
 }
 
+table! {
+    pinned_fund (fund_id) {
+        fund_id -> Integer,
+    }
+}
+
 table! {
     proposal_community_choice_challenge (proposal_id) {
         proposal_id -> Text,
@@ -191,6 +230,7 @@ allow_tables_to_appear_in_same_query!(
     funds,
     goals,
     groups,
+    pinned_fund,
     proposal_community_choice_challenge,
     proposal_simple_challenge,
     proposals,
@@ -0,0 +1,189 @@
+use crate::db::models::{
+    challenges::Challenge,
+    funds::{Fund, FundStageDates},
+    voteplans::Voteplan,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Inconsistency in the schedule of a fund, along with the record it was found in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleViolation {
+    /// Record the violation was found in, e.g. `voteplans.csv row 3 (voteplan abc)`
+    pub location: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ScheduleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.reason)
+    }
+}
+
+/// Stage dates in the order the stages take place
+fn ordered_stage_dates(stage_dates: &FundStageDates) -> [(&'static str, i64); 10] {
+    [
+        ("insight_sharing_start", stage_dates.insight_sharing_start),
+        (
+            "proposal_submission_start",
+            stage_dates.proposal_submission_start,
+        ),
+        ("refine_proposals_start", stage_dates.refine_proposals_start),
+        (
+            "finalize_proposals_start",
+            stage_dates.finalize_proposals_start,
+        ),
+        (
+            "proposal_assessment_start",
+            stage_dates.proposal_assessment_start,
+        ),
+        ("assessment_qa_start", stage_dates.assessment_qa_start),
+        ("snapshot_start", stage_dates.snapshot_start),
+        ("voting_start", stage_dates.voting_start),
+        ("voting_end", stage_dates.voting_end),
+        ("tallying_end", stage_dates.tallying_end),
+    ]
+}
+
+/// The fund must end after it starts and its stages must follow each other in order.
+/// Consecutive stages may start at the same time.
+pub fn fund_violations(fund: &Fund) -> Vec<String> {
+    let mut violations = Vec::new();
+    if fund.fund_end_time < fund.fund_start_time {
+        violations.push(format!(
+            "fund_end_time {} is before fund_start_time {}",
+            fund.fund_end_time, fund.fund_start_time
+        ));
+    }
+
+    let stage_dates = ordered_stage_dates(&fund.stage_dates);
+    for pair in stage_dates.windows(2) {
+        let ((previous, previous_time), (stage, time)) = (pair[0], pair[1]);
+        if time < previous_time {
+            violations.push(format!(
+                "{} {} is before {} {}",
+                stage, time, previous, previous_time
+            ));
+        }
+    }
+    violations
+}
+
+/// The voteplan must run within the voting window of its fund, and its committee must end
+/// after the vote does.
+pub fn voteplan_violations(stage_dates: &FundStageDates, voteplan: &Voteplan) -> Vec<String> {
+    let mut violations = Vec::new();
+    let voting_window = stage_dates.voting_start..=stage_dates.voting_end;
+    for (name, time) in [
+        ("chain_vote_start_time", voteplan.chain_vote_start_time),
+        ("chain_vote_end_time", voteplan.chain_vote_end_time),
+    ] {
+        if !voting_window.contains(&time) {
+            violations.push(format!(
+                "{} {} is outside of the fund voting window {}..={}",
+                name, time, stage_dates.voting_start, stage_dates.voting_end
+            ));
+        }
+    }
+    if voteplan.chain_vote_end_time < voteplan.chain_vote_start_time {
+        violations.push(format!(
+            "chain_vote_end_time {} is before chain_vote_start_time {}",
+            voteplan.chain_vote_end_time, voteplan.chain_vote_start_time
+        ));
+    }
+    if voteplan.chain_committee_end_time < voteplan.chain_vote_end_time {
+        violations.push(format!(
+            "chain_committee_end_time {} is before chain_vote_end_time {}",
+            voteplan.chain_committee_end_time, voteplan.chain_vote_end_time
+        ));
+    }
+    violations
+}
+
+/// The challenge must belong to one of the given funds and cannot give more to proposers than
+/// its total rewards. The fund is not checked if `fund_ids` is none, e.g. when the ids of the
+/// funds are only assigned once they are inserted.
+pub fn challenge_violations(fund_ids: Option<&[i32]>, challenge: &Challenge) -> Vec<String> {
+    let mut violations = Vec::new();
+    if let Some(fund_ids) = fund_ids {
+        if !fund_ids.contains(&challenge.fund_id) {
+            violations.push(format!("fund_id {} is not a known fund", challenge.fund_id));
+        }
+    }
+    if challenge.proposers_rewards > challenge.rewards_total {
+        violations.push(format!(
+            "proposers_rewards {} exceed rewards_total {}",
+            challenge.proposers_rewards, challenge.rewards_total
+        ));
+    }
+    violations
+}
+
+/// Check the schedule of the fund along with the voteplans and challenges stored for it
+pub fn validate_fund(
+    fund: &Fund,
+    voteplans: &[Voteplan],
+    challenges: &[Challenge],
+) -> Vec<ScheduleViolation> {
+    let fund_location = format!("fund {}", fund.id);
+    let voteplans = voteplans.iter().map(|voteplan| {
+        (
+            format!("voteplan {}", voteplan.chain_voteplan_id),
+            voteplan_violations(&fund.stage_dates, voteplan),
+        )
+    });
+    let challenges = challenges.iter().map(|challenge| {
+        (
+            format!("challenge {}", challenge.id),
+            challenge_violations(Some(&[fund.id]), challenge),
+        )
+    });
+
+    std::iter::once((fund_location, fund_violations(fund)))
+        .chain(voteplans)
+        .chain(challenges)
+        .flat_map(|(location, reasons)| {
+            reasons.into_iter().map(move |reason| ScheduleViolation {
+                location: location.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::models::funds::test as funds_testing;
+
+    #[test]
+    fn fund_schedule_violations() {
+        let fund = funds_testing::get_test_fund(Some(1));
+        assert!(validate_fund(&fund, &fund.chain_vote_plans, &fund.challenges).is_empty());
+
+        let mut fund = funds_testing::get_test_fund(Some(1));
+        fund.stage_dates.voting_start = fund.stage_dates.voting_end + 10;
+        fund.stage_dates.tallying_end = fund.stage_dates.voting_start;
+        fund.challenges[0].fund_id = 2;
+        fund.challenges[0].proposers_rewards = fund.challenges[0].rewards_total + 1;
+
+        let violations = validate_fund(&fund, &fund.chain_vote_plans, &fund.challenges);
+        let locations: Vec<&str> = violations
+            .iter()
+            .map(|violation| violation.location.as_str())
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                // voting_end before voting_start
+                "fund 1",
+                // both voteplan times before the voting window
+                "voteplan test_vote_plan1",
+                "voteplan test_vote_plan1",
+                "challenge 9001",
+                "challenge 9001",
+            ]
+        );
+        assert!(violations[0].reason.starts_with("voting_end"));
+    }
+}
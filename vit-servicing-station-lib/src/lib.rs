@@ -3,9 +3,6 @@ extern crate diesel;
 #[macro_use]
 extern crate structopt;
 
-#[macro_use]
-extern crate diesel_migrations;
-
 pub mod db;
 pub mod server;
 pub mod utils;
@@ -6,6 +6,8 @@ pub enum ApplicationExitCode {
     ServiceVersionError,
     SnapshotWatcherError,
     EmptyBlock0FolderError,
+    OutdatedDbSchemaError,
+    DbIntegrityError,
 }
 
 impl ApplicationExitCode {
@@ -20,6 +22,8 @@ impl ApplicationExitCode {
             13 => Some(Self::ServiceVersionError),
             14 => Some(Self::SnapshotWatcherError),
             15 => Some(Self::EmptyBlock0FolderError),
+            16 => Some(Self::OutdatedDbSchemaError),
+            17 => Some(Self::DbIntegrityError),
             _ => None,
         }
     }
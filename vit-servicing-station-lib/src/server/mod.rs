@@ -2,5 +2,6 @@ pub mod bootstrapping;
 pub mod exit_codes;
 pub mod settings;
 pub mod signals;
+pub mod snapshot_watcher;
 
 pub use bootstrapping::start_server;
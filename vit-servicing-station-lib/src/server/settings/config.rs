@@ -61,6 +61,34 @@ pub struct ServiceSettings {
     #[structopt(long)]
     pub enable_api_tokens: bool,
 
+    /// Refuse to start if the database contains broken references between tables
+    #[serde(default)]
+    #[structopt(long)]
+    pub check_db_integrity: bool,
+
+    /// Do not use the latest past fund as the current one when no fund is running
+    #[serde(default)]
+    #[structopt(long)]
+    pub no_current_fund_fallback: bool,
+
+    #[serde(default)]
+    #[structopt(flatten)]
+    pub snapshot_retention: SnapshotRetention,
+
+    /// Maximum number of keys looked up in a single batch snapshot request
+    #[serde(default)]
+    #[structopt(long)]
+    pub snapshot_max_batch_size: Option<usize>,
+
+    /// Directory the admin backup endpoint writes database backups into, backups are disabled if none is set
+    #[serde(default)]
+    #[structopt(long)]
+    pub backup_dir: Option<PathBuf>,
+
+    #[serde(default)]
+    #[structopt(flatten)]
+    pub snapshot_watcher: SnapshotWatcher,
+
     #[serde(default)]
     #[structopt(flatten)]
     pub log: Log,
@@ -101,6 +129,40 @@ pub struct Cors {
     pub max_age_secs: Option<u64>,
 }
 
+/// Snapshot tags to keep after each import, older tags are deleted.
+/// Every tag is kept if none is set, the imported tag is never deleted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, StructOpt)]
+#[serde(deny_unknown_fields)]
+#[structopt(rename_all = "kebab-case")]
+pub struct SnapshotRetention {
+    /// Only keep the given number of most recently updated snapshot tags
+    #[structopt(long = "snapshot-keep-last")]
+    pub keep_last: Option<u64>,
+
+    /// Only keep the snapshot tags updated less than the given number of seconds ago
+    #[structopt(long = "snapshot-max-age-secs")]
+    pub max_age_secs: Option<u64>,
+}
+
+/// Directory watched for snapshot files to import automatically, disabled if none is set.
+/// Each `<tag>.json` file is imported into the `<tag>` snapshot tag.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, StructOpt)]
+#[serde(deny_unknown_fields)]
+#[structopt(rename_all = "kebab-case")]
+pub struct SnapshotWatcher {
+    /// Directory watched for snapshot files to import
+    #[structopt(long = "snapshot-watch-dir")]
+    pub watch_dir: Option<PathBuf>,
+
+    /// Directory imported snapshot files are moved to, `<watch-dir>/archive` if none is set
+    #[structopt(long = "snapshot-archive-dir")]
+    pub archive_dir: Option<PathBuf>,
+
+    /// Directory malformed snapshot files are moved to, `<watch-dir>/rejected` if none is set
+    #[structopt(long = "snapshot-rejected-dir")]
+    pub rejected_dir: Option<PathBuf>,
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
@@ -173,6 +235,39 @@ impl ServiceSettings {
             return_settings.block0_paths = other_settings.block0_paths.clone();
         }
 
+        if other_settings.snapshot_retention.keep_last.is_some() {
+            return_settings.snapshot_retention.keep_last =
+                other_settings.snapshot_retention.keep_last;
+        }
+
+        if other_settings.snapshot_retention.max_age_secs.is_some() {
+            return_settings.snapshot_retention.max_age_secs =
+                other_settings.snapshot_retention.max_age_secs;
+        }
+
+        if other_settings.snapshot_max_batch_size.is_some() {
+            return_settings.snapshot_max_batch_size = other_settings.snapshot_max_batch_size;
+        }
+
+        if other_settings.backup_dir.is_some() {
+            return_settings.backup_dir = other_settings.backup_dir.clone();
+        }
+
+        if other_settings.snapshot_watcher.watch_dir.is_some() {
+            return_settings.snapshot_watcher.watch_dir =
+                other_settings.snapshot_watcher.watch_dir.clone();
+        }
+
+        if other_settings.snapshot_watcher.archive_dir.is_some() {
+            return_settings.snapshot_watcher.archive_dir =
+                other_settings.snapshot_watcher.archive_dir.clone();
+        }
+
+        if other_settings.snapshot_watcher.rejected_dir.is_some() {
+            return_settings.snapshot_watcher.rejected_dir =
+                other_settings.snapshot_watcher.rejected_dir.clone();
+        }
+
         if other_settings.log.log_level.is_some() {
             return_settings.log.log_level = other_settings.log.log_level;
         }
@@ -186,6 +281,8 @@ impl ServiceSettings {
         }
 
         return_settings.enable_api_tokens = other_settings.enable_api_tokens;
+        return_settings.check_db_integrity = other_settings.check_db_integrity;
+        return_settings.no_current_fund_fallback = other_settings.no_current_fund_fallback;
 
         return_settings
     }
@@ -395,6 +492,18 @@ mod test {
             "db_url": "",
             "block0_path": "./test/bin.test",
             "enable_api_tokens" : true,
+            "check_db_integrity" : true,
+            "no_current_fund_fallback" : true,
+            "snapshot_retention" : {
+                "keep_last" : 3,
+                "max_age_secs" : 86400
+            },
+            "snapshot_max_batch_size" : 200,
+            "backup_dir" : "./backups",
+            "snapshot_watcher" : {
+                "watch_dir" : "./snapshots",
+                "archive_dir" : "./snapshots_archive"
+            },
             "log" : {
                 "log_output_path" : "./server.log",
                 "log_level" : "error"    
@@ -410,6 +519,21 @@ mod test {
         );
         assert_eq!(config.block0_path, Some("./test/bin.test".to_string()));
         assert!(config.enable_api_tokens);
+        assert!(config.check_db_integrity);
+        assert!(config.no_current_fund_fallback);
+        assert_eq!(config.snapshot_retention.keep_last, Some(3));
+        assert_eq!(config.snapshot_retention.max_age_secs, Some(86400));
+        assert_eq!(config.snapshot_max_batch_size, Some(200));
+        assert_eq!(config.backup_dir, Some(PathBuf::from("./backups")));
+        assert_eq!(
+            config.snapshot_watcher.watch_dir,
+            Some(PathBuf::from("./snapshots"))
+        );
+        assert_eq!(
+            config.snapshot_watcher.archive_dir,
+            Some(PathBuf::from("./snapshots_archive"))
+        );
+        assert_eq!(config.snapshot_watcher.rejected_dir, None);
         assert_eq!(
             config.log.log_output_path.unwrap(),
             std::path::PathBuf::from_str("./server.log").unwrap()
@@ -458,6 +582,18 @@ mod test {
             "--log-level",
             "error",
             "--enable-api-tokens",
+            "--check-db-integrity",
+            "--no-current-fund-fallback",
+            "--snapshot-keep-last",
+            "3",
+            "--snapshot-max-age-secs",
+            "86400",
+            "--snapshot-max-batch-size",
+            "200",
+            "--backup-dir",
+            "./backups",
+            "--snapshot-watch-dir",
+            "./snapshots",
             "--service-version",
             "v0.2.0",
         ]);
@@ -469,6 +605,16 @@ mod test {
 
         assert!(settings.tls.is_loaded());
         assert!(settings.enable_api_tokens);
+        assert!(settings.check_db_integrity);
+        assert!(settings.no_current_fund_fallback);
+        assert_eq!(settings.snapshot_retention.keep_last, Some(3));
+        assert_eq!(settings.snapshot_retention.max_age_secs, Some(86400));
+        assert_eq!(settings.snapshot_max_batch_size, Some(200));
+        assert_eq!(settings.backup_dir, Some(PathBuf::from("./backups")));
+        assert_eq!(
+            settings.snapshot_watcher.watch_dir,
+            Some(PathBuf::from("./snapshots"))
+        );
         assert_eq!(settings.tls.cert_file.unwrap(), "foo.bar");
         assert_eq!(settings.tls.priv_key_file.unwrap(), "bar.foo");
         assert_eq!(settings.db_url, "database.sqlite3");
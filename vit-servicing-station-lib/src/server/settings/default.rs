@@ -1,5 +1,8 @@
 use super::config::ServiceSettings;
-use super::config::{Cors, Log, Tls, ADDRESS_DEFAULT, BLOCK0_PATH_DEFAULT, DB_URL_DEFAULT};
+use super::config::{
+    Cors, Log, SnapshotRetention, SnapshotWatcher, Tls, ADDRESS_DEFAULT, BLOCK0_PATH_DEFAULT,
+    DB_URL_DEFAULT,
+};
 use std::net::SocketAddr;
 use std::str::FromStr;
 
@@ -15,6 +18,12 @@ impl Default for ServiceSettings {
             block0_path: Some(BLOCK0_PATH_DEFAULT.to_string()),
             block0_paths: None,
             enable_api_tokens: false,
+            check_db_integrity: false,
+            no_current_fund_fallback: false,
+            snapshot_retention: SnapshotRetention::default(),
+            snapshot_max_batch_size: None,
+            backup_dir: None,
+            snapshot_watcher: SnapshotWatcher::default(),
             log: Log::default(),
             service_version: "".to_string(),
         }
@@ -3,5 +3,5 @@ mod default;
 
 pub use config::{
     dump_settings_to_file, load_settings_from_file, Cors, CorsOrigin, LogLevel, ServiceSettings,
-    Tls,
+    SnapshotRetention, SnapshotWatcher, Tls,
 };
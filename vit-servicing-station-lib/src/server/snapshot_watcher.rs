@@ -0,0 +1,415 @@
+//! Automatic snapshot imports from a watched directory. Every `<tag>.json` file holding either
+//! snapshot information or a raw snapshot, in the same format as the admin snapshot endpoints, is
+//! imported into `<tag>` and moved to the archive directory. Files with an invalid name or a
+//! malformed content are moved to the rejected directory instead, while files that fail to be read
+//! or imported (e.g. the database is unavailable) are left in place and retried on the next scan.
+//!
+//! Files are imported as soon as they appear, so they must be written under another name (e.g.
+//! `<tag>.json.part`) and renamed once complete.
+
+use crate::server::settings::SnapshotWatcher as SnapshotWatcherSettings;
+use crate::v0::context::SharedContext;
+use crate::v0::endpoints::snapshot::{
+    update_from_raw_snapshot, update_from_shanpshot_info, RawSnapshotInput, SnapshotInfoInput,
+};
+use crate::v0::errors::HandleError;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
+
+const SNAPSHOT_FILE_EXTENSION: &str = "json";
+
+/// Field only present in raw snapshot files
+const RAW_SNAPSHOT_FIELD: &str = "min_stake_threshold";
+
+#[derive(Error, Debug)]
+pub enum SnapshotWatcherError {
+    #[error("error creating directory `{path}`: {source}")]
+    CreateDir {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("error watching snapshot directory: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+#[derive(Error, Debug)]
+enum SnapshotFileError {
+    #[error("file name is not a valid snapshot tag")]
+    InvalidName,
+
+    #[error("error reading file: {0}")]
+    Read(#[from] std::io::Error),
+
+    #[error("malformed snapshot: {0}")]
+    Malformed(#[from] serde_json::Error),
+
+    #[error("error importing snapshot: {0}")]
+    Import(#[from] HandleError),
+}
+
+impl SnapshotFileError {
+    /// Whether the file can never be imported, other errors may go away on a later attempt
+    fn is_rejected(&self) -> bool {
+        matches!(self, Self::InvalidName | Self::Malformed(_))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Directories {
+    watch: PathBuf,
+    archive: PathBuf,
+    rejected: PathBuf,
+}
+
+impl Directories {
+    fn new(watch: PathBuf, settings: &SnapshotWatcherSettings) -> Self {
+        Self {
+            archive: settings
+                .archive_dir
+                .clone()
+                .unwrap_or_else(|| watch.join("archive")),
+            rejected: settings
+                .rejected_dir
+                .clone()
+                .unwrap_or_else(|| watch.join("rejected")),
+            watch,
+        }
+    }
+
+    fn create(&self) -> Result<(), SnapshotWatcherError> {
+        for dir in [&self.watch, &self.archive, &self.rejected] {
+            std::fs::create_dir_all(dir).map_err(|source| SnapshotWatcherError::CreateDir {
+                path: dir.clone(),
+                source,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Imports the snapshot files of the watched directory until dropped
+pub struct SnapshotWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl SnapshotWatcher {
+    /// Starts watching the configured directory, if any. The files already in the directory are
+    /// imported right away.
+    pub fn start(
+        settings: &SnapshotWatcherSettings,
+        context: SharedContext,
+    ) -> Result<Option<Self>, SnapshotWatcherError> {
+        let watch_dir = match &settings.watch_dir {
+            Some(watch_dir) => watch_dir.clone(),
+            None => return Ok(None),
+        };
+        let dirs = Directories::new(watch_dir, settings);
+        dirs.create()?;
+
+        let (sender, events) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = RecommendedWatcher::new(move |event: notify::Result<Event>| {
+            // the receiver is only dropped along with the watcher
+            let _ = sender.send(event);
+        })?;
+        watcher.watch(&dirs.watch, RecursiveMode::NonRecursive)?;
+        tracing::info!("watching {} for snapshot files", dirs.watch.display());
+
+        let task = tokio::spawn(watch_snapshot_files(dirs, events, context));
+        Ok(Some(Self {
+            _watcher: watcher,
+            task,
+        }))
+    }
+}
+
+impl Drop for SnapshotWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn watch_snapshot_files(
+    dirs: Directories,
+    mut events: UnboundedReceiver<notify::Result<Event>>,
+    context: SharedContext,
+) {
+    let mut unmovable = HashMap::new();
+    import_snapshot_files(&dirs, &context, &mut unmovable).await;
+    while let Some(event) = events.recv().await {
+        let mut changed = is_change(event);
+        // a single scan takes care of every event received in the meantime
+        while let Ok(event) = events.try_recv() {
+            changed |= is_change(event);
+        }
+        if changed {
+            import_snapshot_files(&dirs, &context, &mut unmovable).await;
+        }
+    }
+}
+
+fn is_change(event: notify::Result<Event>) -> bool {
+    match event {
+        Ok(event) => matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)),
+        Err(e) => {
+            tracing::error!("error watching snapshot directory: {}", e);
+            false
+        }
+    }
+}
+
+/// Snapshot files of the watched directory, ordered by name
+fn snapshot_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path.extension().and_then(|extension| extension.to_str())
+                == Some(SNAPSHOT_FILE_EXTENSION)
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Last modification time of the file, if available
+fn modified(file: &Path) -> Option<SystemTime> {
+    std::fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Imports the snapshot files of the watched directory. Files that could not be moved out of the
+/// directory are kept in `unmovable` along with their modification time, and skipped until they
+/// change so they are not imported again on every scan.
+async fn import_snapshot_files(
+    dirs: &Directories,
+    context: &SharedContext,
+    unmovable: &mut HashMap<PathBuf, Option<SystemTime>>,
+) {
+    let files = match snapshot_files(&dirs.watch) {
+        Ok(files) => files,
+        Err(e) => {
+            tracing::error!("error listing snapshot files: {}", e);
+            return;
+        }
+    };
+    unmovable.retain(|file, _| files.contains(file));
+
+    for file in files {
+        let modified = modified(&file);
+        if unmovable.get(&file) == Some(&modified) {
+            continue;
+        }
+        let destination = match import_snapshot_file(&file, context).await {
+            Ok(tag) => {
+                tracing::info!("imported snapshot {} from {}", tag, file.display());
+                &dirs.archive
+            }
+            Err(e) if e.is_rejected() => {
+                tracing::error!("rejected snapshot file {}: {}", file.display(), e);
+                &dirs.rejected
+            }
+            Err(e) => {
+                tracing::error!(
+                    "error importing snapshot file {}, retrying on the next scan: {}",
+                    file.display(),
+                    e
+                );
+                continue;
+            }
+        };
+        match move_file(&file, destination).await {
+            Ok(()) => {
+                unmovable.remove(&file);
+            }
+            Err(e) => {
+                tracing::error!(
+                    "error moving snapshot file {} to {}, skipping it until it changes: {}",
+                    file.display(),
+                    destination.display(),
+                    e
+                );
+                unmovable.insert(file, modified);
+            }
+        }
+    }
+}
+
+async fn import_snapshot_file(
+    path: &Path,
+    context: &SharedContext,
+) -> Result<String, SnapshotFileError> {
+    let tag = path
+        .file_stem()
+        .and_then(|tag| tag.to_str())
+        .filter(|tag| !tag.is_empty())
+        .ok_or(SnapshotFileError::InvalidName)?
+        .to_string();
+
+    let content: serde_json::Value = serde_json::from_slice(&tokio::fs::read(path).await?)?;
+    if content.get(RAW_SNAPSHOT_FIELD).is_some() {
+        let input: RawSnapshotInput = serde_json::from_value(content)?;
+        let (registrations, params) = input.into_parts();
+        update_from_raw_snapshot(tag.clone(), registrations, params, context.clone()).await?;
+    } else {
+        let input: SnapshotInfoInput = serde_json::from_value(content)?;
+        update_from_shanpshot_info(
+            tag.clone(),
+            input.snapshot,
+            input.update_timestamp,
+            context.clone(),
+        )
+        .await?;
+    }
+    Ok(tag)
+}
+
+/// Moves the file into `dir`, prefixed by the current time so files of the same tag are kept
+async fn move_file(file: &Path, dir: &Path) -> std::io::Result<()> {
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a file"))?;
+    let destination = dir.join(format!(
+        "{}-{}",
+        OffsetDateTime::now_utc().unix_timestamp(),
+        file_name.to_string_lossy()
+    ));
+    if tokio::fs::rename(file, &destination).await.is_err() {
+        // renaming fails if the directories are in different file systems
+        tokio::fs::copy(file, &destination).await?;
+        tokio::fs::remove_file(file).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::migrations::initialize_db_with_migration;
+    use crate::db::queries::snapshot::delete_snapshot;
+    use crate::v0::context::test::new_in_memmory_db_test_shared_context;
+    use crate::v0::endpoints::snapshot::get_tags;
+    use std::time::Duration;
+
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut entries: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[tokio::test]
+    async fn import_watched_snapshot_files() {
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let watch_dir = tempfile::tempdir().unwrap();
+        let snapshot_info = serde_json::to_string(&SnapshotInfoInput {
+            snapshot: Vec::new(),
+            update_timestamp: 0,
+        })
+        .unwrap();
+        // files already in the directory are imported on start
+        std::fs::write(watch_dir.path().join("tag_a.json"), &snapshot_info).unwrap();
+        std::fs::write(watch_dir.path().join("malformed.json"), "{").unwrap();
+        std::fs::write(watch_dir.path().join("tag_c.json.part"), &snapshot_info).unwrap();
+
+        let settings = SnapshotWatcherSettings {
+            watch_dir: Some(watch_dir.path().to_path_buf()),
+            archive_dir: None,
+            rejected_dir: None,
+        };
+        let _watcher = SnapshotWatcher::start(&settings, context.clone())
+            .unwrap()
+            .unwrap();
+
+        std::fs::write(watch_dir.path().join("tag_b.json.part"), &snapshot_info).unwrap();
+        std::fs::rename(
+            watch_dir.path().join("tag_b.json.part"),
+            watch_dir.path().join("tag_b.json"),
+        )
+        .unwrap();
+
+        let archive_dir = watch_dir.path().join("archive");
+        for _ in 0..100 {
+            if dir_entries(&archive_dir).len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let mut tags = get_tags(context.clone()).await.unwrap();
+        tags.sort();
+        assert_eq!(tags, vec!["tag_a", "tag_b"]);
+
+        let archived = dir_entries(&archive_dir);
+        assert_eq!(archived.len(), 2);
+        assert!(archived[0].ends_with("-tag_a.json"));
+        assert!(archived[1].ends_with("-tag_b.json"));
+        let rejected = dir_entries(&watch_dir.path().join("rejected"));
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].ends_with("-malformed.json"));
+        assert_eq!(
+            dir_entries(watch_dir.path()),
+            vec!["archive", "rejected", "tag_c.json.part"]
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_failed_imports_and_skip_unmovable_files() {
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+
+        let watch_dir = tempfile::tempdir().unwrap();
+        let dirs = Directories {
+            watch: watch_dir.path().to_path_buf(),
+            // never created, so moving files into it fails
+            archive: watch_dir.path().join("missing").join("archive"),
+            rejected: watch_dir.path().join("rejected"),
+        };
+        std::fs::create_dir(&dirs.rejected).unwrap();
+        let snapshot_info = serde_json::to_string(&SnapshotInfoInput {
+            snapshot: Vec::new(),
+            update_timestamp: 0,
+        })
+        .unwrap();
+        let file = watch_dir.path().join("tag_a.json");
+        std::fs::write(&file, &snapshot_info).unwrap();
+        let mut unmovable = HashMap::new();
+
+        // the database is not initialized yet, the file is kept to be retried
+        import_snapshot_files(&dirs, &context, &mut unmovable).await;
+        assert!(file.exists());
+        assert!(dir_entries(&dirs.rejected).is_empty());
+        assert!(unmovable.is_empty());
+
+        initialize_db_with_migration(db_conn);
+        import_snapshot_files(&dirs, &context, &mut unmovable).await;
+        assert_eq!(get_tags(context.clone()).await.unwrap(), vec!["tag_a"]);
+        assert!(file.exists());
+        assert!(unmovable.contains_key(&file));
+
+        // the file could not be archived, it is not imported again while unchanged
+        delete_snapshot("tag_a", db_conn).unwrap();
+        import_snapshot_files(&dirs, &context, &mut unmovable).await;
+        assert!(get_tags(context.clone()).await.unwrap().is_empty());
+
+        std::fs::create_dir_all(&dirs.archive).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        import_snapshot_files(&dirs, &context, &mut unmovable).await;
+        assert!(unmovable.is_empty());
+    }
+}
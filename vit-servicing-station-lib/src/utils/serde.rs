@@ -1,7 +1,7 @@
 use crate::db::models::vote_options::VoteOptions;
 use crate::utils::datetime::unix_timestamp_to_datetime;
 use serde::de::Visitor;
-use serde::{ser::Error, Deserialize, Deserializer, Serializer};
+use serde::{ser::Error, Deserializer, Serializer};
 use std::fmt;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
@@ -137,9 +137,44 @@ pub fn deserialize_truthy_falsy<'de, D>(deserializer: D) -> Result<bool, D::Erro
 where
     D: Deserializer<'de>,
 {
-    let truthy_value = <&str>::deserialize(deserializer)?;
-    Ok(matches!(
-        truthy_value.to_lowercase().as_ref(),
-        "x" | "1" | "true"
-    ))
+    struct TruthyFalsyDeserializer();
+
+    impl<'de> Visitor<'de> for TruthyFalsyDeserializer {
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("A boolean or a truthy/falsy string is needed")
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<bool, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value)
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<bool, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value == 1)
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<bool, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value == 1)
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<bool, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(matches!(value.to_lowercase().as_ref(), "x" | "1" | "true"))
+        }
+    }
+
+    // csv fields are inferred as booleans or numbers when possible, json ones can be actual booleans
+    deserializer.deserialize_any(TruthyFalsyDeserializer())
 }
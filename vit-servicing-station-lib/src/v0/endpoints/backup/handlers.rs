@@ -0,0 +1,109 @@
+use super::logic;
+use crate::v0::{context::SharedContext, result::HandlerResult};
+use serde::{Deserialize, Serialize};
+use warp::{Rejection, Reply};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BackupRequest {
+    /// Name of the file the backup is written to, inside the configured backup directory
+    pub out: String,
+}
+
+pub async fn backup(
+    request: BackupRequest,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(logic::backup(request, context).await))
+}
+
+#[cfg(all(test, not(feature = "postgres")))]
+mod test {
+    use super::*;
+    use crate::db::{
+        load_db_connection_pool, migrations::initialize_db_with_migration,
+        models::funds::test as funds_testing, queries::funds::query_all_funds,
+    };
+    use crate::testing::filters::ResponseBytesExt;
+    use crate::v0::context::test::new_test_shared_context;
+    use crate::v0::endpoints::backup::logic::BackupInfo;
+    use warp::Filter;
+
+    #[tokio::test]
+    async fn backup_handler() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let db_url = tmp_dir.path().join("db.sqlite3");
+        let out = "backup.sqlite3".to_string();
+
+        let shared_context = new_test_shared_context(db_url.to_str().unwrap(), vec![]);
+        let filter_context = shared_context.clone();
+        let with_context = warp::any().map(move || filter_context.clone());
+
+        let pool = shared_context.read().await.db_connection_pool.clone();
+        initialize_db_with_migration(&pool.get().unwrap());
+        funds_testing::populate_db_with_fund(&funds_testing::get_test_fund(Some(1)), &pool);
+
+        let filter = warp::path!("backup")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_context)
+            .and_then(backup);
+
+        // backups are disabled until a backup directory is configured
+        let result = warp::test::request()
+            .method("POST")
+            .path("/backup")
+            .json(&BackupRequest { out: out.clone() })
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::BAD_REQUEST);
+
+        let backup_dir = tmp_dir.path().join("backups");
+        std::fs::create_dir(&backup_dir).unwrap();
+        shared_context.write().await.backup_dir = Some(backup_dir.clone());
+
+        let result = warp::test::request()
+            .method("POST")
+            .path("/backup")
+            .json(&BackupRequest { out: out.clone() })
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::OK);
+        let info: BackupInfo = result.as_json();
+        let backup_file = backup_dir.join(&out);
+        assert_eq!(info.out, backup_file.to_str().unwrap());
+        assert!(info.size > 0);
+
+        let backup_pool = load_db_connection_pool(&info.out).unwrap();
+        assert_eq!(query_all_funds(&backup_pool).await.unwrap(), vec![1]);
+
+        // existing files are never overwritten
+        let result = warp::test::request()
+            .method("POST")
+            .path("/backup")
+            .json(&BackupRequest { out })
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::BAD_REQUEST);
+
+        // only file names inside the backup directory are accepted
+        for out in [
+            "",
+            "..",
+            "../backup.sqlite3",
+            "nested/backup.sqlite3",
+            "/tmp/backup.sqlite3",
+        ] {
+            let result = warp::test::request()
+                .method("POST")
+                .path("/backup")
+                .json(&BackupRequest {
+                    out: out.to_string(),
+                })
+                .reply(&filter)
+                .await;
+            assert_eq!(result.status(), warp::http::StatusCode::BAD_REQUEST);
+        }
+        assert!(!tmp_dir.path().join("backup.sqlite3").exists());
+    }
+}
@@ -0,0 +1,82 @@
+use super::handlers::BackupRequest;
+use crate::db::backup::{self, BackupError};
+use crate::v0::context::SharedContext;
+use crate::v0::errors::HandleError;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct BackupInfo {
+    pub out: String,
+    pub size: u64,
+}
+
+pub async fn backup(
+    BackupRequest { out }: BackupRequest,
+    context: SharedContext,
+) -> Result<BackupInfo, HandleError> {
+    let context = context.read().await;
+    let backup_dir = context.backup_dir.clone().ok_or_else(|| {
+        HandleError::BadRequest("backups are disabled, no backup_dir is configured".to_string())
+    })?;
+    let out = backup_file_path(&backup_dir, &out)?;
+    let db_conn = context
+        .db_connection_pool
+        .get()
+        .map_err(HandleError::DatabaseError)?;
+    drop(context);
+
+    tokio::task::spawn_blocking(move || {
+        let db_file = backup::database_file(&db_conn).map_err(map_backup_error)?;
+        // claim the file before writing it so an existing one is never overwritten
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&out)
+            .map_err(|e| match e.kind() {
+                ErrorKind::AlreadyExists => {
+                    HandleError::BadRequest(format!("{} already exists", out.display()))
+                }
+                _ => HandleError::InternalError(format!("Error creating {}: {}", out.display(), e)),
+            })?;
+        let out = out.to_string_lossy().into_owned();
+        if let Err(e) = backup::backup_db(&db_file, &out) {
+            let _ = std::fs::remove_file(&out);
+            return Err(map_backup_error(e));
+        }
+        let size = std::fs::metadata(&out)
+            .map_err(|e| HandleError::InternalError(format!("Error reading backup: {}", e)))?
+            .len();
+        Ok(BackupInfo { out, size })
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+/// Path of the backup file `out` in the backup directory. Only plain file names are accepted so
+/// backups cannot be written anywhere else in the server host.
+fn backup_file_path(backup_dir: &Path, out: &str) -> Result<PathBuf, HandleError> {
+    let is_file_name = !out.is_empty()
+        && out != "."
+        && out != ".."
+        && !out.contains(&['/', '\\'][..])
+        && Path::new(out).file_name() == Some(OsStr::new(out));
+    if !is_file_name {
+        return Err(HandleError::BadRequest(format!(
+            "{} is not a valid backup file name",
+            out
+        )));
+    }
+    Ok(backup_dir.join(out))
+}
+
+fn map_backup_error(error: BackupError) -> HandleError {
+    match error {
+        BackupError::InvalidPath(_) | BackupError::Open { .. } => {
+            HandleError::BadRequest(error.to_string())
+        }
+        _ => HandleError::InternalError(error.to_string()),
+    }
+}
@@ -0,0 +1,5 @@
+mod handlers;
+mod logic;
+mod routes;
+
+pub use routes::admin_filter;
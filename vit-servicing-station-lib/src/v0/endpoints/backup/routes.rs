@@ -0,0 +1,15 @@
+use super::handlers::*;
+use crate::v0::context::SharedContext;
+use warp::{Filter, Rejection, Reply};
+
+pub fn admin_filter(
+    context: SharedContext,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let with_context = warp::any().map(move || context.clone());
+
+    warp::path::end()
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context)
+        .and_then(backup)
+}
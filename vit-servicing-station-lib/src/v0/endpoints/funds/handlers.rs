@@ -15,10 +15,32 @@ pub async fn get_all_funds(context: SharedContext) -> Result<impl Reply, Rejecti
     Ok(HandlerResult(logic::get_all_funds(context).await))
 }
 
+pub async fn get_fund_stage(id: i32, context: SharedContext) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(logic::get_fund_stage(id, context).await))
+}
+
+pub async fn get_eligibility(
+    id: i32,
+    voting_key: String,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        logic::get_eligibility(id, voting_key, context).await,
+    ))
+}
+
 pub async fn put_fund(fund: Fund, context: SharedContext) -> Result<impl Reply, Rejection> {
     Ok(HandlerResult(logic::put_fund(fund, context).await))
 }
 
+pub async fn pin_current_fund(id: i32, context: SharedContext) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(logic::pin_current_fund(id, context).await))
+}
+
+pub async fn unpin_current_fund(context: SharedContext) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(logic::unpin_current_fund(context).await))
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
@@ -28,6 +50,7 @@ pub mod test {
         queries::funds::FundWithNext,
     };
     use crate::v0::context::test::new_in_memmory_db_test_shared_context;
+    use time::{Duration, OffsetDateTime};
     use warp::Filter;
 
     #[tokio::test]
@@ -40,8 +63,14 @@ pub mod test {
         // initialize db
         let pool = &shared_context.read().await.db_connection_pool;
         db_testing::initialize_db_with_migration(&pool.get().unwrap());
-        let fund: Fund = funds_testing::get_test_fund(Some(1));
+        let now = OffsetDateTime::now_utc();
+        let mut fund: Fund = funds_testing::get_test_fund(Some(1));
+        fund.fund_start_time = (now - Duration::days(1)).unix_timestamp();
+        fund.fund_end_time = (now + Duration::days(1)).unix_timestamp();
+        fund.next_fund_start_time = (now + Duration::days(10)).unix_timestamp();
         let mut next_fund: Fund = funds_testing::get_test_fund(Some(2));
+        next_fund.fund_start_time = (now + Duration::days(10)).unix_timestamp();
+        next_fund.fund_end_time = (now + Duration::days(20)).unix_timestamp();
 
         next_fund.challenges = Vec::new();
         next_fund.chain_vote_plans = Vec::new();
@@ -65,6 +94,95 @@ pub mod test {
         assert_eq!(next_fund.id, next.id);
         assert_eq!(next_fund.fund_name, next.fund_name);
         assert_eq!(next_fund.stage_dates, next.stage_dates);
+
+        // every stage of the test fund starts now
+        assert_eq!(
+            result_fund.stage,
+            fund.stage_dates.stage_at(fund.stage_dates.tallying_end)
+        );
+    }
+
+    #[tokio::test]
+    async fn select_current_fund() {
+        use crate::v0::endpoints::funds::admin_filter;
+
+        let shared_context = new_in_memmory_db_test_shared_context();
+        let filter_context = shared_context.clone();
+        let with_context = warp::any().map(move || filter_context.clone());
+
+        // cloned so the context is not kept locked while the fallback is turned off
+        let pool = shared_context.read().await.db_connection_pool.clone();
+        let pool = &pool;
+        db_testing::initialize_db_with_migration(&pool.get().unwrap());
+
+        let now = OffsetDateTime::now_utc();
+        let fund_with_dates = |id: i32, start: i64, end: i64, next_start: i64| {
+            let mut fund = funds_testing::get_test_fund(Some(id));
+            fund.challenges = vec![];
+            fund.chain_vote_plans = vec![];
+            fund.goals = vec![];
+            fund.groups = Default::default();
+            fund.fund_start_time = (now + Duration::days(start)).unix_timestamp();
+            fund.fund_end_time = (now + Duration::days(end)).unix_timestamp();
+            fund.next_fund_start_time = (now + Duration::days(next_start)).unix_timestamp();
+            fund
+        };
+        let past = fund_with_dates(1, -30, -20, -10);
+        let future = fund_with_dates(3, 10, 20, 30);
+        funds_testing::populate_db_with_fund(&past, pool);
+        funds_testing::populate_db_with_fund(&future, pool);
+
+        let current_filter = warp::any()
+            .and(warp::get())
+            .and(with_context)
+            .and_then(get_fund);
+        let admin_filter = admin_filter(shared_context.clone());
+        let current_filter = &current_filter;
+        let current = move || async move {
+            let result = warp::test::request()
+                .method("GET")
+                .reply(current_filter)
+                .await;
+            if result.status() == warp::http::StatusCode::NOT_FOUND {
+                return None;
+            }
+            assert_eq!(result.status(), warp::http::StatusCode::OK);
+            let fund: FundWithNext = serde_json::from_slice(result.body()).unwrap();
+            Some((fund.fund.id, fund.next.map(|next| next.id)))
+        };
+
+        // no fund running, the latest past one is used
+        assert_eq!(current().await, Some((past.id, Some(future.id))));
+        shared_context.write().await.current_fund_fallback = false;
+        assert_eq!(current().await, None);
+
+        let running = fund_with_dates(2, -5, 5, 10);
+        funds_testing::populate_db_with_fund(&running, pool);
+        assert_eq!(current().await, Some((running.id, Some(future.id))));
+
+        let result = warp::test::request()
+            .method("PUT")
+            .path(&format!("/current/{}", past.id))
+            .reply(&admin_filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::OK);
+        assert_eq!(current().await, Some((past.id, Some(running.id))));
+
+        let result = warp::test::request()
+            .method("PUT")
+            .path("/current/4")
+            .reply(&admin_filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::NOT_FOUND);
+        assert_eq!(current().await, Some((past.id, Some(running.id))));
+
+        let result = warp::test::request()
+            .method("DELETE")
+            .path("/current")
+            .reply(&admin_filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::OK);
+        assert_eq!(current().await, Some((running.id, Some(future.id))));
     }
 
     #[tokio::test]
@@ -197,6 +315,127 @@ pub mod test {
 
         let result_fund = test_get_fund(fund1.id, shared_context.clone()).await;
         assert_eq!(fund1, result_fund);
+
+        // funds with an inconsistent schedule are rejected
+        let mut invalid_fund = updated_fund.clone();
+        invalid_fund.fund_name = "invalid fund".into();
+        invalid_fund.stage_dates.voting_end = invalid_fund.stage_dates.voting_start - 1;
+        let result = warp::test::request()
+            .method("PUT")
+            .body(serde_json::to_string(&invalid_fund).unwrap())
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::BAD_REQUEST);
+        let message = String::from_utf8(result.body().to_vec()).unwrap();
+        assert!(message.contains("voting_end"), "{}", message);
+
+        let result_fund = test_get_fund(fund2.id, shared_context.clone()).await;
+        assert_eq!(updated_fund, result_fund);
+
+        // the new schedule is checked against the voteplans already stored for the fund
+        let mut moved_fund = fund1.clone();
+        moved_fund.chain_vote_plans = vec![];
+        moved_fund.challenges = vec![];
+        moved_fund.stage_dates.voting_start += 100;
+        moved_fund.stage_dates.voting_end += 100;
+        moved_fund.stage_dates.tallying_end += 100;
+        let result = warp::test::request()
+            .method("PUT")
+            .body(serde_json::to_string(&moved_fund).unwrap())
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::BAD_REQUEST);
+        let message = String::from_utf8(result.body().to_vec()).unwrap();
+        assert!(
+            message.contains(&fund1.chain_vote_plans[0].chain_voteplan_id),
+            "{}",
+            message
+        );
+
+        let result_fund = test_get_fund(fund1.id, shared_context.clone()).await;
+        assert_eq!(fund1, result_fund);
+    }
+
+    #[tokio::test]
+    async fn get_eligibility_handler() {
+        use crate::v0::endpoints::snapshot::update_from_shanpshot_info;
+        use jormungandr_lib::crypto::account::Identifier;
+        use snapshot_lib::{SnapshotInfo, VoterHIR};
+
+        let voting_key = "0000000000000000000000000000000000000000000000000000000000000000";
+        let shared_context = new_in_memmory_db_test_shared_context();
+        let filter_context = shared_context.clone();
+        let with_context = warp::any().map(move || filter_context.clone());
+
+        let pool = &shared_context.read().await.db_connection_pool;
+        db_testing::initialize_db_with_migration(&pool.get().unwrap());
+
+        // threshold of 100, groups `group1` and `group2`
+        let fund = funds_testing::get_test_fund(Some(1));
+        let mut fund_without_snapshot = funds_testing::get_test_fund(Some(2));
+        fund_without_snapshot.challenges = vec![];
+        fund_without_snapshot.chain_vote_plans = vec![];
+        fund_without_snapshot.goals = vec![];
+        fund_without_snapshot.groups = Default::default();
+        fund_without_snapshot.snapshot_tag = None;
+        funds_testing::populate_db_with_fund(&fund, pool);
+        funds_testing::populate_db_with_fund(&fund_without_snapshot, pool);
+
+        let entry = |voting_group: &str, voting_power: u64| SnapshotInfo {
+            contributions: Vec::new(),
+            hir: VoterHIR {
+                voting_key: Identifier::from_hex(voting_key).unwrap(),
+                voting_group: voting_group.to_string(),
+                voting_power: voting_power.into(),
+            },
+        };
+
+        let filter = warp::path!(i32 / "eligibility" / String)
+            .and(warp::get())
+            .and(with_context)
+            .and_then(get_eligibility);
+        let request = |fund_id: i32| {
+            warp::test::request()
+                .method("GET")
+                .path(&format!("/{}/eligibility/{}", fund_id, voting_key))
+        };
+
+        for (entries, eligible_groups, shortfall) in [
+            (
+                vec![entry("group1", 30), entry("group2", 60)],
+                vec![],
+                Some(40),
+            ),
+            (
+                vec![entry("group1", 100), entry("group2", 60)],
+                vec!["group1".to_string()],
+                None,
+            ),
+            // voting groups not in the fund are ignored
+            (vec![entry("other", 1000)], vec![], Some(100)),
+        ] {
+            update_from_shanpshot_info(
+                fund.snapshot_tag.clone().unwrap(),
+                entries,
+                1,
+                shared_context.clone(),
+            )
+            .await
+            .unwrap();
+
+            let result = request(fund.id).reply(&filter).await;
+            assert_eq!(result.status(), warp::http::StatusCode::OK);
+            let eligibility: logic::Eligibility = serde_json::from_slice(result.body()).unwrap();
+            assert_eq!(eligibility.voting_power_threshold, 100);
+            assert_eq!(eligibility.eligible_groups, eligible_groups);
+            assert_eq!(eligibility.shortfall, shortfall);
+            assert_eq!(eligibility.last_updated, 1);
+        }
+
+        let result = request(fund_without_snapshot.id).reply(&filter).await;
+        assert_eq!(result.status(), warp::http::StatusCode::NOT_FOUND);
+        let result = request(3).reply(&filter).await;
+        assert_eq!(result.status(), warp::http::StatusCode::NOT_FOUND);
     }
 
     async fn test_get_fund(id: i32, context: SharedContext) -> Fund {
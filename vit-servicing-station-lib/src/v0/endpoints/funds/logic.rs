@@ -1,7 +1,41 @@
 use crate::db::queries::funds::FundWithNext;
-use crate::db::{models::funds::Fund, queries::funds as funds_queries};
+use crate::db::{
+    models::funds::{Fund, FundStageInfo},
+    queries::{
+        challenges::query_challenges_by_fund_id, funds as funds_queries,
+        voteplans::query_voteplan_by_id,
+    },
+    validation::validate_fund,
+};
 use crate::v0::context::SharedContext;
+use crate::v0::endpoints::snapshot::{get_voters_info, Group};
 use crate::v0::errors::HandleError;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VotingGroupEligibility {
+    pub voting_group: Group,
+    pub voting_power: u64,
+    pub eligible: bool,
+}
+
+/// Voting power of a voting key in the snapshot of a fund compared to its voting power threshold
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eligibility {
+    pub fund_id: i32,
+    pub snapshot_tag: String,
+    pub voting_power_threshold: u64,
+    /// Voting groups of the fund the voting key reaches the threshold in
+    pub eligible_groups: Vec<Group>,
+    /// Voting power missing to reach the threshold, only set if no voting group reaches it
+    pub shortfall: Option<u64>,
+    pub voting_groups: Vec<VotingGroupEligibility>,
+    /// Update time of the snapshot the voting power comes from
+    #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
+    #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
+    pub last_updated: i64,
+}
 
 pub async fn get_fund_by_id(id: i32, context: SharedContext) -> Result<Fund, HandleError> {
     let pool = &context.read().await.db_connection_pool;
@@ -9,8 +43,19 @@ pub async fn get_fund_by_id(id: i32, context: SharedContext) -> Result<Fund, Han
 }
 
 pub async fn get_current_fund(context: SharedContext) -> Result<FundWithNext, HandleError> {
+    let context = context.read().await;
+    funds_queries::query_current_fund(&context.db_connection_pool, context.current_fund_fallback)
+        .await
+}
+
+pub async fn pin_current_fund(id: i32, context: SharedContext) -> Result<(), HandleError> {
+    let pool = &context.read().await.db_connection_pool;
+    funds_queries::pin_current_fund(id, pool).await
+}
+
+pub async fn unpin_current_fund(context: SharedContext) -> Result<(), HandleError> {
     let pool = &context.read().await.db_connection_pool;
-    funds_queries::query_current_fund(pool).await
+    funds_queries::unpin_current_fund(pool).await
 }
 
 pub async fn get_all_funds(context: SharedContext) -> Result<Vec<i32>, HandleError> {
@@ -18,7 +63,86 @@ pub async fn get_all_funds(context: SharedContext) -> Result<Vec<i32>, HandleErr
     funds_queries::query_all_funds(pool).await
 }
 
+pub async fn get_eligibility(
+    id: i32,
+    voting_key: String,
+    context: SharedContext,
+) -> Result<Eligibility, HandleError> {
+    let fund = get_fund_by_id(id, context.clone()).await?;
+    let snapshot_tag = fund
+        .snapshot_tag
+        .clone()
+        .ok_or_else(|| HandleError::NotFound(format!("snapshot tag of fund {}", fund.id)))?;
+    let voters_info = get_voters_info(snapshot_tag.clone(), voting_key, context).await?;
+
+    let voting_power_threshold = fund.voting_power_threshold.max(0) as u64;
+    let voting_groups: Vec<VotingGroupEligibility> = voters_info
+        .voter_info
+        .into_iter()
+        .filter(|voter| fund.has_voting_group(&voter.voting_group))
+        .map(|voter| {
+            let voting_power = u64::from(voter.voting_power);
+            VotingGroupEligibility {
+                voting_group: voter.voting_group,
+                voting_power,
+                eligible: voting_power >= voting_power_threshold,
+            }
+        })
+        .collect();
+
+    let eligible_groups: Vec<Group> = voting_groups
+        .iter()
+        .filter(|group| group.eligible)
+        .map(|group| group.voting_group.clone())
+        .collect();
+    let shortfall = if eligible_groups.is_empty() {
+        let voting_power = voting_groups
+            .iter()
+            .map(|group| group.voting_power)
+            .max()
+            .unwrap_or(0);
+        Some(voting_power_threshold - voting_power)
+    } else {
+        None
+    };
+
+    Ok(Eligibility {
+        fund_id: fund.id,
+        snapshot_tag,
+        voting_power_threshold,
+        eligible_groups,
+        shortfall,
+        voting_groups,
+        last_updated: voters_info.last_updated,
+    })
+}
+
+pub async fn get_fund_stage(id: i32, context: SharedContext) -> Result<FundStageInfo, HandleError> {
+    let fund = get_fund_by_id(id, context).await?;
+    Ok(fund
+        .stage_dates
+        .stage_at(OffsetDateTime::now_utc().unix_timestamp()))
+}
+
+/// Insert or replace the fund, rejecting it with every violation found if its schedule is
+/// inconsistent
+///
+/// Only the fund row is updated, so its new schedule is checked against the voteplans and
+/// challenges already stored for it rather than the ones in the body
 pub async fn put_fund(fund: Fund, context: SharedContext) -> Result<(), HandleError> {
     let pool = &context.read().await.db_connection_pool;
+    let voteplans = query_voteplan_by_id(fund.id, pool).await?;
+    let challenges = query_challenges_by_fund_id(fund.id, pool).await?;
+    let violations = validate_fund(&fund, &voteplans, &challenges);
+    if !violations.is_empty() {
+        return Err(HandleError::BadRequest(
+            violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        ));
+    }
+
     funds_queries::put_fund(fund, pool)
 }
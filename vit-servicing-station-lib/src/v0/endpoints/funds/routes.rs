@@ -19,13 +19,24 @@ pub async fn filter(
         .and(with_context.clone())
         .and_then(get_fund_by_id);
 
+    let stage = warp::path!(i32 / "stage")
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_fund_stage);
+
+    let eligibility = warp::path!(i32 / "eligibility" / String)
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_eligibility);
+
     let all_funds = warp::path::end()
         .and(warp::get())
         .and(with_context)
         .and_then(get_all_funds);
 
     // fund_by_id need to be checked first otherwise requests are swallowed by the fund::any
-    root.and(fund_by_id.or(fund).or(all_funds)).boxed()
+    root.and(fund_by_id.or(stage).or(eligibility).or(fund).or(all_funds))
+        .boxed()
 }
 
 pub fn admin_filter(
@@ -33,9 +44,21 @@ pub fn admin_filter(
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let with_context = warp::any().map(move || context.clone());
 
-    warp::path::end()
+    let put = warp::path::end()
         .and(warp::put())
         .and(warp::body::json())
+        .and(with_context.clone())
+        .and_then(put_fund);
+
+    let pin = warp::path!("current" / i32)
+        .and(warp::put())
+        .and(with_context.clone())
+        .and_then(pin_current_fund);
+
+    let unpin = warp::path!("current")
+        .and(warp::delete())
         .and(with_context)
-        .and_then(put_fund)
+        .and_then(unpin_current_fund);
+
+    put.or(pin).or(unpin)
 }
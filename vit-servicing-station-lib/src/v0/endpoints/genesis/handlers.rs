@@ -58,7 +58,7 @@ fn get_genesis_response(response: Vec<u8>) -> Result<impl Reply, Rejection> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::v0::context::test::new_test_shared_context;
+    use crate::v0::context::test::{new_test_db_url, new_test_shared_context};
     use std::path::PathBuf;
     use std::str::FromStr;
     use warp::Filter;
@@ -67,8 +67,10 @@ mod test {
     async fn get_block0_succeed() {
         // build context
         let block0_path = "../resources/tests/block0.bin";
-        let shared_context =
-            new_test_shared_context("", vec![PathBuf::from_str(block0_path).unwrap()]);
+        let shared_context = new_test_shared_context(
+            &new_test_db_url(),
+            vec![PathBuf::from_str(block0_path).unwrap()],
+        );
         let block0 = std::fs::read(block0_path).unwrap();
 
         let with_context = warp::any().map(move || shared_context.clone());
@@ -1,9 +1,11 @@
 mod advisor_reviews;
+mod backup;
 mod challenges;
 mod funds;
 mod genesis;
 mod health;
 pub mod proposals;
+pub mod rewards;
 pub mod search;
 pub mod service_version;
 pub mod snapshot;
@@ -69,7 +71,17 @@ pub async fn filter(
 
         let fund_filter = warp::path!("fund" / ..).and(funds::admin_filter(context.clone()));
 
-        base.and(snapshot_tx_filter.or(fund_filter))
+        let backup_filter = warp::path!("backup" / ..).and(backup::admin_filter(context.clone()));
+
+        let rewards_filter =
+            warp::path!("rewards" / ..).and(rewards::admin_filter(context.clone()));
+
+        base.and(
+            snapshot_tx_filter
+                .or(fund_filter)
+                .or(backup_filter)
+                .or(rewards_filter),
+        )
     };
 
     let api_token_filter = if enable_api_tokens {
@@ -0,0 +1,255 @@
+use super::logic::{self, Participation, RewardsParams};
+use crate::v0::context::SharedContext;
+use crate::v0::endpoints::snapshot::ExportFormat;
+use serde::{Deserialize, Serialize};
+use warp::{Rejection, Reply};
+
+/// Parameters of the rewards of the vote casters
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RewardsQuery {
+    pub pot: u64,
+    #[serde(default)]
+    pub threshold: u64,
+    #[serde(default)]
+    pub representatives_pot: u64,
+    pub representatives_group: Option<String>,
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RewardsFormatQuery {
+    pub format: Option<ExportFormat>,
+}
+
+/// Parameters of the rewards of an uploaded list of participating voting keys
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RewardsInput {
+    #[serde(flatten)]
+    pub params: RewardsParams,
+    pub voting_keys: Vec<String>,
+}
+
+async fn rewards_reply(
+    tag: String,
+    params: RewardsParams,
+    participation: Participation,
+    format: Option<ExportFormat>,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    let format = format.unwrap_or_default();
+    Ok(
+        match logic::export_rewards(tag, params, participation, format, context).await {
+            Ok(response) => response,
+            Err(e) => e.into_response(),
+        },
+    )
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_rewards(
+    tag: String,
+    query: RewardsQuery,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    let params = RewardsParams {
+        pot: query.pot,
+        threshold: query.threshold,
+        representatives_pot: query.representatives_pot,
+        representatives_group: query.representatives_group,
+    };
+    rewards_reply(tag, params, Participation::Votes, query.format, context).await
+}
+
+#[tracing::instrument(skip(context, input))]
+pub async fn post_rewards(
+    tag: String,
+    query: RewardsFormatQuery,
+    input: RewardsInput,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    let participation = Participation::VotingKeys(input.voting_keys);
+    rewards_reply(tag, input.params, participation, query.format, context).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{admin_filter, RepresentativeReward, Reward, Rewards};
+    use super::*;
+    use crate::db::{
+        migrations as db_testing,
+        models::{funds::test as funds_testing, vote::test as votes_testing},
+    };
+    use crate::v0::context::test::new_in_memmory_db_test_shared_context;
+    use crate::v0::endpoints::snapshot::update_from_shanpshot_info;
+    use jormungandr_lib::crypto::account::Identifier;
+    use snapshot_lib::{KeyContribution, SnapshotInfo, VoterHIR};
+    use warp::hyper::StatusCode;
+    use warp::Filter;
+
+    fn entry(
+        voting_key: &str,
+        voting_group: &str,
+        contributions: &[(&str, &str, u64)],
+    ) -> SnapshotInfo {
+        SnapshotInfo {
+            contributions: contributions
+                .iter()
+                .map(
+                    |(stake_public_key, reward_address, value)| KeyContribution {
+                        stake_public_key: stake_public_key.to_string(),
+                        reward_address: reward_address.to_string(),
+                        value: *value,
+                    },
+                )
+                .collect(),
+            hir: VoterHIR {
+                voting_key: Identifier::from_hex(voting_key).unwrap(),
+                voting_group: voting_group.to_string(),
+                voting_power: contributions
+                    .iter()
+                    .map(|(_, _, value)| value)
+                    .sum::<u64>()
+                    .into(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn rewards_distribution() {
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+            "3333333333333333333333333333333333333333333333333333333333333333",
+        ];
+        let context = new_in_memmory_db_test_shared_context();
+        let pool = context.read().await.db_connection_pool.clone();
+        db_testing::initialize_db_with_migration(&pool.get().unwrap());
+
+        let fund = funds_testing::get_test_fund(Some(1));
+        funds_testing::populate_db_with_fund(&fund, &pool);
+        let tag = fund.snapshot_tag.clone().unwrap();
+        update_from_shanpshot_info(
+            tag.clone(),
+            vec![
+                entry(
+                    keys[0],
+                    "direct",
+                    &[("s1", "addr1", 30), ("s2", "addr2", 10)],
+                ),
+                entry(keys[1], "rep", &[("s3", "addr1", 20), ("s4", "addr3", 1)]),
+                entry(keys[2], "direct", &[("s5", "addr4", 100)]),
+                entry(keys[3], "rep", &[("s6", "addr5", 40)]),
+            ],
+            0,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        // only the votes in the voteplans of the fund count
+        let votes = [
+            (keys[0], fund.chain_vote_plans[0].chain_voteplan_id.clone()),
+            (keys[1], fund.chain_vote_plans[0].chain_voteplan_id.clone()),
+            (keys[2], "other_voteplan".to_string()),
+        ];
+        for (i, (caster, voteplan_id)) in votes.into_iter().enumerate() {
+            let mut vote = votes_testing::get_test_vote();
+            vote.fragment_id = i.to_string();
+            vote.caster = caster.to_string();
+            vote.voteplan_id = voteplan_id;
+            votes_testing::populate_db_with_vote(&vote, &pool);
+        }
+
+        let filter = warp::path!("rewards" / ..).and(admin_filter(context.clone()));
+
+        let result = warp::test::request()
+            .method("GET")
+            .path(&format!(
+                "/rewards/{}?pot=1000&threshold=5&representatives_pot=100",
+                tag
+            ))
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let rewards: Rewards = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(rewards.participants, 2);
+        assert_eq!(rewards.total_stake, 60);
+        assert_eq!(rewards.distributed, 999);
+        assert_eq!(
+            rewards.rewards,
+            vec![
+                Reward {
+                    reward_address: "addr1".to_string(),
+                    stake: 50,
+                    reward: 833,
+                },
+                Reward {
+                    reward_address: "addr2".to_string(),
+                    stake: 10,
+                    reward: 166,
+                },
+            ]
+        );
+        // only the representatives that voted share their pot
+        assert_eq!(rewards.representatives_distributed, 100);
+        assert_eq!(
+            rewards.representative_rewards,
+            vec![RepresentativeReward {
+                voting_key: keys[1].to_string(),
+                voting_power: 21,
+                reward: 100,
+            }]
+        );
+
+        let result = warp::test::request()
+            .method("GET")
+            .path(&format!("/rewards/{}?pot=1000&threshold=5&format=csv", tag))
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        assert_eq!(
+            String::from_utf8(result.body().to_vec()).unwrap(),
+            "reward_address,stake,reward\naddr1,50,833\naddr2,10,166\n"
+        );
+
+        let result = warp::test::request()
+            .method("POST")
+            .path(&format!("/rewards/{}", tag))
+            .json(&RewardsInput {
+                params: RewardsParams {
+                    pot: 1000,
+                    ..Default::default()
+                },
+                voting_keys: vec![keys[2].to_string()],
+            })
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let rewards: Rewards = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(rewards.participants, 1);
+        assert_eq!(rewards.distributed, 1000);
+        assert_eq!(rewards.rewards[0].reward_address, "addr4");
+
+        let result = warp::test::request()
+            .method("POST")
+            .path(&format!("/rewards/{}", tag))
+            .json(&RewardsInput {
+                params: RewardsParams {
+                    pot: 1000,
+                    ..Default::default()
+                },
+                voting_keys: vec!["not a voting key".to_string()],
+            })
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::BAD_REQUEST);
+
+        let result = warp::test::request()
+            .method("GET")
+            .path("/rewards/missing_tag?pot=1000")
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,271 @@
+//! Voter and representative rewards of a snapshot tag. The reward pot is shared between the
+//! reward addresses of the contributions to the participating voting keys, proportionally to their
+//! stake. Reward addresses with less stake than the threshold are left out. The representatives
+//! pot is shared between the participating voting keys of the representatives voting group,
+//! proportionally to their voting power.
+
+use crate::db::{
+    models::snapshot::{Contribution, Voter},
+    queries::{
+        snapshot::{
+            query_contributions_by_snapshot_tag, query_snapshot_by_tag,
+            query_voters_by_snapshot_tag, VotersFilter,
+        },
+        votes::query_vote_casters_by_snapshot_tag,
+    },
+};
+use crate::v0::context::SharedContext;
+use crate::v0::endpoints::snapshot::{ExportFormat, Tag};
+use crate::v0::errors::HandleError;
+use jormungandr_lib::{crypto::account::Identifier, interfaces::Address};
+use serde::{Deserialize, Serialize};
+use snapshot_lib::voting_group::DEFAULT_REPRESENTATIVE_GROUP;
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+use warp::http::{self, header::CONTENT_TYPE};
+use warp::reply::Response;
+use warp::Reply;
+
+const CSV_HEADER: [&str; 3] = ["reward_address", "stake", "reward"];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardsParams {
+    /// Amount shared between the reward addresses
+    pub pot: u64,
+    /// Minimum stake of a reward address to get a reward
+    #[serde(default)]
+    pub threshold: u64,
+    /// Amount shared between the representatives
+    #[serde(default)]
+    pub representatives_pot: u64,
+    /// Voting group of the representatives, `rep` if none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub representatives_group: Option<String>,
+}
+
+/// Source of the voting keys whose contributions are rewarded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Participation {
+    /// Casters of the votes in the voteplans of the funds using the snapshot tag
+    Votes,
+    VotingKeys(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reward {
+    pub reward_address: String,
+    pub stake: u64,
+    pub reward: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepresentativeReward {
+    pub voting_key: String,
+    pub voting_power: u64,
+    pub reward: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rewards {
+    pub tag: Tag,
+    #[serde(flatten)]
+    pub params: RewardsParams,
+    /// Participating voting keys with contributions in the snapshot tag
+    pub participants: u64,
+    /// Stake of the rewarded addresses
+    pub total_stake: u64,
+    /// Sum of the rewards, the rest of the pot is lost to rounding
+    pub distributed: u64,
+    /// Rewards ordered by reward address
+    pub rewards: Vec<Reward>,
+    /// Sum of the representative rewards, the rest of the representatives pot is lost to rounding
+    pub representatives_distributed: u64,
+    /// Rewards of the participating representatives ordered by voting key
+    pub representative_rewards: Vec<RepresentativeReward>,
+}
+
+/// Voting key of a vote caster, either its account address or the hex encoded key itself
+fn caster_voting_key(caster: &str) -> Option<String> {
+    if let Ok(voting_key) = Identifier::from_hex(caster) {
+        return Some(voting_key.to_hex());
+    }
+    let address = Address::from_str(caster).ok()?;
+    let public_key = address.1.public_key()?;
+    Some(Identifier::from(public_key.clone()).to_hex())
+}
+
+async fn participating_voting_keys(
+    tag: Tag,
+    participation: Participation,
+    context: &SharedContext,
+) -> Result<HashSet<String>, HandleError> {
+    match participation {
+        Participation::Votes => {
+            let pool = &context.read().await.db_connection_pool;
+            Ok(query_vote_casters_by_snapshot_tag(tag, pool)
+                .await?
+                .into_iter()
+                .filter_map(|caster| {
+                    let voting_key = caster_voting_key(&caster);
+                    if voting_key.is_none() {
+                        tracing::warn!("ignoring votes of unknown caster {}", caster);
+                    }
+                    voting_key
+                })
+                .collect())
+        }
+        Participation::VotingKeys(voting_keys) => voting_keys
+            .into_iter()
+            .map(|voting_key| {
+                Identifier::from_hex(&voting_key)
+                    .map(|voting_key| voting_key.to_hex())
+                    .map_err(|_| {
+                        HandleError::BadRequest(format!("Invalid voting key {}", voting_key))
+                    })
+            })
+            .collect(),
+    }
+}
+
+/// Share of the pot matching `amount` out of `total`, rounded down
+fn share(pot: u64, amount: u64, total: u64) -> u64 {
+    (pot as u128 * amount as u128)
+        .checked_div(total as u128)
+        .unwrap_or(0) as u64
+}
+
+fn distribute_rewards(
+    tag: Tag,
+    params: RewardsParams,
+    participants: &HashSet<String>,
+    contributions: Vec<Contribution>,
+    representatives: Vec<Voter>,
+) -> Rewards {
+    let mut rewarded_voting_keys = HashSet::new();
+    let mut stakes: BTreeMap<String, u64> = BTreeMap::new();
+    for contribution in contributions {
+        if !participants.contains(&contribution.voting_key) {
+            continue;
+        }
+        *stakes.entry(contribution.reward_address).or_default() += contribution.value as u64;
+        rewarded_voting_keys.insert(contribution.voting_key);
+    }
+    stakes.retain(|_, stake| *stake >= params.threshold);
+
+    let total_stake: u64 = stakes.values().sum();
+    let rewards: Vec<Reward> = stakes
+        .into_iter()
+        .map(|(reward_address, stake)| Reward {
+            reward_address,
+            stake,
+            reward: share(params.pot, stake, total_stake),
+        })
+        .collect();
+
+    let voting_powers: BTreeMap<String, u64> = representatives
+        .into_iter()
+        .filter(|representative| participants.contains(&representative.voting_key))
+        .map(|representative| {
+            (
+                representative.voting_key,
+                representative.voting_power as u64,
+            )
+        })
+        .collect();
+    let total_voting_power: u64 = voting_powers.values().sum();
+    let representative_rewards: Vec<RepresentativeReward> = voting_powers
+        .into_iter()
+        .map(|(voting_key, voting_power)| RepresentativeReward {
+            voting_key,
+            voting_power,
+            reward: share(params.representatives_pot, voting_power, total_voting_power),
+        })
+        .collect();
+
+    Rewards {
+        tag,
+        participants: rewarded_voting_keys.len() as u64,
+        total_stake,
+        distributed: rewards.iter().map(|reward| reward.reward).sum(),
+        params,
+        rewards,
+        representatives_distributed: representative_rewards
+            .iter()
+            .map(|reward| reward.reward)
+            .sum(),
+        representative_rewards,
+    }
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_rewards(
+    tag: Tag,
+    params: RewardsParams,
+    participation: Participation,
+    context: SharedContext,
+) -> Result<Rewards, HandleError> {
+    let participants = participating_voting_keys(tag.clone(), participation, &context).await?;
+
+    let pool = &context.read().await.db_connection_pool;
+    query_snapshot_by_tag(tag.clone(), pool).await?;
+    let contributions = query_contributions_by_snapshot_tag(tag.clone(), pool).await?;
+    let representatives_group = params
+        .representatives_group
+        .clone()
+        .unwrap_or_else(|| DEFAULT_REPRESENTATIVE_GROUP.to_string());
+    let representatives = query_voters_by_snapshot_tag(
+        tag.clone(),
+        VotersFilter {
+            voting_group: Some(representatives_group),
+            min_voting_power: None,
+            descending: false,
+            after: None,
+            limit: i64::MAX,
+        },
+        pool,
+    )
+    .await?;
+
+    Ok(distribute_rewards(
+        tag,
+        params,
+        &participants,
+        contributions,
+        representatives,
+    ))
+}
+
+fn csv_error(e: impl std::fmt::Display) -> HandleError {
+    HandleError::InternalError(format!("Error serializing rewards: {}", e))
+}
+
+fn rewards_csv(rewards: &[Reward]) -> Result<Vec<u8>, HandleError> {
+    // the header is written even without rows
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer.write_record(CSV_HEADER).map_err(csv_error)?;
+    for reward in rewards {
+        writer.serialize(reward).map_err(csv_error)?;
+    }
+    writer.into_inner().map_err(|e| csv_error(e.into_error()))
+}
+
+/// Rewards in the requested format, the CSV export has a row per reward address and leaves out
+/// the representative rewards
+pub async fn export_rewards(
+    tag: Tag,
+    params: RewardsParams,
+    participation: Participation,
+    format: ExportFormat,
+    context: SharedContext,
+) -> Result<Response, HandleError> {
+    let rewards = get_rewards(tag, params, participation, context).await?;
+    match format {
+        ExportFormat::Json => Ok(warp::reply::json(&rewards).into_response()),
+        ExportFormat::Csv => http::Response::builder()
+            .header(CONTENT_TYPE, "text/csv")
+            .body(rewards_csv(&rewards.rewards)?.into())
+            .map_err(|e| HandleError::InternalError(format!("Error building response: {}", e))),
+    }
+}
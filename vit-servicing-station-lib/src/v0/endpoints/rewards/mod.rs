@@ -0,0 +1,7 @@
+mod handlers;
+mod logic;
+mod routes;
+
+pub use handlers::{RewardsInput, RewardsQuery};
+pub use logic::{get_rewards, Participation, RepresentativeReward, Reward, Rewards, RewardsParams};
+pub use routes::admin_filter;
@@ -0,0 +1,24 @@
+use super::handlers::{get_rewards, post_rewards, RewardsFormatQuery, RewardsQuery};
+use crate::v0::context::SharedContext;
+use warp::{Filter, Rejection, Reply};
+
+pub fn admin_filter(
+    context: SharedContext,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let with_context = warp::any().map(move || context.clone());
+
+    let from_votes = warp::path!(String)
+        .and(warp::get())
+        .and(warp::query::<RewardsQuery>())
+        .and(with_context.clone())
+        .and_then(get_rewards);
+
+    let from_voting_keys = warp::path!(String)
+        .and(warp::post())
+        .and(warp::query::<RewardsFormatQuery>())
+        .and(warp::body::json())
+        .and(with_context)
+        .and_then(post_rewards);
+
+    from_votes.or(from_voting_keys)
+}
@@ -241,6 +241,24 @@ mod test {
             .as_json();
 
         assert_eq!(count, 3);
+
+        // a search without any word to match is rejected instead of matching everything
+        let body = serde_json::to_string(&SearchCountQuery {
+            order_by: vec![],
+            table: Table::Challenges,
+            filter: vec![Constraint {
+                column: Column::Title,
+                search: "!!!".to_string(),
+            }],
+        })
+        .unwrap();
+        let result = warp::test::request()
+            .method("POST")
+            .path("/search_count")
+            .body(body)
+            .reply(&filter_search_count)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
@@ -308,4 +326,130 @@ mod test {
         let output = vec![challenge_2, challenge_3, challenge_4, challenge_5];
         assert_eq!(challenges, output);
     }
+
+    #[tokio::test]
+    async fn full_text_search() {
+        use crate::db::models::challenges::test::{
+            get_test_challenge_with_fund_id, populate_db_with_challenge_conn,
+        };
+        use crate::db::models::proposals::{
+            community_choice,
+            test::{get_test_proposal, populate_db_with_proposal_conn},
+            ProposalChallengeInfo,
+        };
+
+        let (with_context, conn) = test_context().await;
+
+        let mut proposal_1 = get_test_proposal("group1");
+        proposal_1.proposal.proposal_title = "Cardano education".to_string();
+        populate_db_with_proposal_conn(&proposal_1, &conn);
+
+        let mut proposal_2 = get_test_proposal("group2");
+        proposal_2.proposal.internal_id = 2;
+        proposal_2.proposal.proposal_id = "2".to_string();
+        proposal_2.proposal.proposal_title = "Education education".to_string();
+        proposal_2.voteplan.chain_voteplan_id = "voteplan_2".to_string();
+        proposal_2.challenge_info =
+            ProposalChallengeInfo::CommunityChoice(community_choice::ChallengeInfo {
+                proposal_brief: "brief".to_string(),
+                proposal_importance: "importance".to_string(),
+                proposal_goal: "goal".to_string(),
+                proposal_metrics: "metrics".to_string(),
+            });
+        populate_db_with_proposal_conn(&proposal_2, &conn);
+
+        populate_db_with_challenge_conn(
+            &get_test_challenge_with_fund_id(proposal_1.proposal.fund_id),
+            &conn,
+        );
+
+        let filter = warp::path!("search")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_context)
+            .and_then(search);
+
+        // challenge specific information is searchable through `any`
+        let body = serde_json::to_string(&SearchQuery {
+            query: SearchCountQuery {
+                table: Table::Proposals,
+                filter: vec![Constraint {
+                    search: "young".to_string(),
+                    column: Column::Any,
+                }],
+                order_by: vec![],
+            },
+            limit: None,
+            offset: None,
+        })
+        .unwrap();
+
+        let proposals: Vec<serde_json::Value> = warp::test::request()
+            .method("POST")
+            .path("/search")
+            .body(body)
+            .reply(&filter)
+            .await
+            .as_json();
+
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0]["internal_id"], 1);
+        assert!(proposals[0]["snippet"]
+            .as_str()
+            .unwrap()
+            .contains("<mark>young</mark>"));
+
+        // the most relevant match comes first when ordering by descending relevance
+        let body = serde_json::to_string(&SearchQuery {
+            query: SearchCountQuery {
+                table: Table::Proposals,
+                filter: vec![Constraint {
+                    search: "education".to_string(),
+                    column: Column::Title,
+                }],
+                order_by: vec![OrderBy {
+                    column: Column::Relevance,
+                    descending: true,
+                }],
+            },
+            limit: None,
+            offset: None,
+        })
+        .unwrap();
+
+        let proposals: Vec<serde_json::Value> = warp::test::request()
+            .method("POST")
+            .path("/search")
+            .body(body)
+            .reply(&filter)
+            .await
+            .as_json();
+
+        assert_eq!(proposals.len(), 2);
+        assert_eq!(proposals[0]["internal_id"], 2);
+        assert_eq!(proposals[1]["internal_id"], 1);
+
+        // relevance is meaningless without a text to search
+        let body = serde_json::to_string(&SearchQuery {
+            query: SearchCountQuery {
+                table: Table::Proposals,
+                filter: vec![],
+                order_by: vec![OrderBy {
+                    column: Column::Relevance,
+                    descending: true,
+                }],
+            },
+            limit: None,
+            offset: None,
+        })
+        .unwrap();
+
+        let result = warp::test::request()
+            .method("POST")
+            .path("/search")
+            .body(body)
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
 }
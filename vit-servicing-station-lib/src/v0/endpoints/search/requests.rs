@@ -51,13 +51,26 @@ pub enum Column {
     Desc,
     Author,
     Funds,
+    /// Every text column of the table, only valid as search constraint
+    Any,
+    /// How well rows match the search constraints, only valid for ordering
+    Relevance,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult<T> {
+    #[serde(flatten)]
+    pub item: T,
+    /// Fragment of the matched text with the search terms highlighted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)] // should serialize as if it is either a `Vec<Challenge>` or `Vec<FullProposalInfo>`
 pub enum SearchResponse {
-    Challenge(Vec<Challenge>),
-    Proposal(Vec<FullProposalInfo>),
+    Challenge(Vec<SearchResult<Challenge>>),
+    Proposal(Vec<SearchResult<FullProposalInfo>>),
 }
 
 #[cfg(test)]
@@ -70,7 +83,10 @@ mod tests {
 
     #[test]
     fn response_serializes_as_vec() {
-        let response = SearchResponse::Proposal(vec![get_test_proposal("asdf")]);
+        let response = SearchResponse::Proposal(vec![SearchResult {
+            item: get_test_proposal("asdf"),
+            snippet: None,
+        }]);
         let s = to_string(&response).unwrap();
         assert!(s.starts_with('['));
         assert!(s.ends_with(']'));
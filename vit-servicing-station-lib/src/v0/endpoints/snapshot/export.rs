@@ -0,0 +1,384 @@
+//! Snapshot content export. Entries are read one page at a time ordered by voting key and voting
+//! group, with their contributions ordered by stake key, so the JSON export of the same content is
+//! always the same and is identified by its SHA-256 hash.
+
+use super::Tag;
+use crate::{
+    db::{
+        models::snapshot::{Contribution, Voter},
+        queries::snapshot::{
+            load_contributions_by_voting_keys, load_voters_page, put_snapshot_content_hash,
+            query_snapshot_by_tag,
+        },
+        DbConnection, DbConnectionPool,
+    },
+    v0::{context::SharedContext, errors::HandleError},
+};
+use futures_util::StreamExt;
+use itertools::Itertools;
+use jormungandr_lib::crypto::account::Identifier;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snapshot_lib::{KeyContribution, SnapshotInfo, VoterHIR};
+use warp::http::{self, header::CONTENT_TYPE};
+use warp::hyper::Body;
+use warp::reply::Response;
+
+/// Response header holding the hash of the JSON export, whatever the requested format is
+pub const CONTENT_HASH_HEADER: &str = "x-snapshot-content-hash";
+
+// the voting keys of a page are bound in a single query, keep them below the sqlite limit of 999
+// bound variables
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+const CSV_HEADER: [&str; 6] = [
+    "voting_key",
+    "voting_group",
+    "voting_power",
+    "stake_public_key",
+    "reward_address",
+    "value",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl Default for ExportFormat {
+    fn default() -> Self {
+        ExportFormat::Json
+    }
+}
+
+/// A contribution per row, voters without contributions have a single row without them
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    voting_key: &'a str,
+    voting_group: &'a str,
+    voting_power: u64,
+    stake_public_key: Option<&'a str>,
+    reward_address: Option<&'a str>,
+    value: Option<u64>,
+}
+
+fn db_error(e: diesel::result::Error) -> HandleError {
+    HandleError::InternalError(format!("Error exporting snapshot: {}", e))
+}
+
+fn snapshot_info(
+    voter: Voter,
+    contributions: Vec<Contribution>,
+) -> Result<SnapshotInfo, HandleError> {
+    let voting_key = Identifier::from_hex(&voter.voting_key).map_err(|_| {
+        HandleError::InternalError(format!("Invalid voting key {}", voter.voting_key))
+    })?;
+    Ok(SnapshotInfo {
+        contributions: contributions
+            .into_iter()
+            .map(|contribution| KeyContribution {
+                stake_public_key: contribution.stake_public_key,
+                reward_address: contribution.reward_address,
+                value: contribution.value as u64,
+            })
+            .collect(),
+        hir: VoterHIR {
+            voting_key,
+            voting_group: voter.voting_group,
+            voting_power: (voter.voting_power as u64).into(),
+        },
+    })
+}
+
+/// A page of entries along with the position of its last voter
+fn load_entries_page(
+    tag: &str,
+    after: Option<(String, String)>,
+    db_conn: &DbConnection,
+) -> Result<(Vec<SnapshotInfo>, Option<(String, String)>), HandleError> {
+    let voters = load_voters_page(tag, after, EXPORT_PAGE_SIZE, db_conn).map_err(db_error)?;
+    let last = voters
+        .last()
+        .map(|voter| (voter.voting_key.clone(), voter.voting_group.clone()));
+
+    let voting_keys = voters
+        .iter()
+        .map(|voter| voter.voting_key.clone())
+        .dedup()
+        .collect();
+    let mut contributions = load_contributions_by_voting_keys(tag, voting_keys, db_conn)
+        .map_err(db_error)?
+        .into_iter()
+        .map(|contribution| {
+            (
+                (
+                    contribution.voting_key.clone(),
+                    contribution.voting_group.clone(),
+                ),
+                contribution,
+            )
+        })
+        .into_group_map();
+
+    let entries = voters
+        .into_iter()
+        .map(|voter| {
+            let voter_contributions = contributions
+                .remove(&(voter.voting_key.clone(), voter.voting_group.clone()))
+                .unwrap_or_default();
+            snapshot_info(voter, voter_contributions)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((entries, last))
+}
+
+/// Every entry of a snapshot tag, one page at a time
+fn entries_pages<'a>(
+    tag: &'a str,
+    db_conn: &'a DbConnection,
+) -> impl Iterator<Item = Result<Vec<SnapshotInfo>, HandleError>> + 'a {
+    let mut after = None;
+    let mut finished = false;
+    std::iter::from_fn(move || {
+        if finished {
+            return None;
+        }
+        match load_entries_page(tag, after.take(), db_conn) {
+            Ok((entries, last)) => {
+                finished = (entries.len() as i64) < EXPORT_PAGE_SIZE;
+                after = last;
+                if entries.is_empty() {
+                    None
+                } else {
+                    Some(Ok(entries))
+                }
+            }
+            Err(e) => {
+                finished = true;
+                Some(Err(e))
+            }
+        }
+    })
+}
+
+/// Serializes the export in the requested format while hashing its JSON serialization
+struct Export {
+    format: ExportFormat,
+    hasher: Sha256,
+    first_entry: bool,
+}
+
+impl Export {
+    fn new(format: ExportFormat) -> Self {
+        Self {
+            format,
+            hasher: Sha256::new(),
+            first_entry: true,
+        }
+    }
+
+    fn start(&mut self) -> Result<Vec<u8>, HandleError> {
+        self.hasher.update(b"[");
+        match self.format {
+            ExportFormat::Json => Ok(b"[".to_vec()),
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                writer.write_record(CSV_HEADER).map_err(csv_error)?;
+                writer.into_inner().map_err(|e| csv_error(e.into_error()))
+            }
+        }
+    }
+
+    fn page(&mut self, entries: Vec<SnapshotInfo>) -> Result<Vec<u8>, HandleError> {
+        let mut json = Vec::new();
+        for entry in &entries {
+            if !self.first_entry {
+                json.push(b',');
+            }
+            self.first_entry = false;
+            serde_json::to_writer(&mut json, entry).map_err(|e| {
+                HandleError::InternalError(format!("Error serializing snapshot: {}", e))
+            })?;
+        }
+        self.hasher.update(&json);
+
+        match self.format {
+            ExportFormat::Json => Ok(json),
+            ExportFormat::Csv => csv_rows(&entries),
+        }
+    }
+
+    /// Last chunk of the export along with the content hash
+    fn finish(mut self) -> (Vec<u8>, String) {
+        self.hasher.update(b"]");
+        let content_hash = format!("{:x}", self.hasher.finalize());
+        match self.format {
+            ExportFormat::Json => (b"]".to_vec(), content_hash),
+            ExportFormat::Csv => (Vec::new(), content_hash),
+        }
+    }
+}
+
+fn csv_error(e: impl std::fmt::Display) -> HandleError {
+    HandleError::InternalError(format!("Error serializing snapshot: {}", e))
+}
+
+fn csv_rows(entries: &[SnapshotInfo]) -> Result<Vec<u8>, HandleError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    for entry in entries {
+        let voting_key = entry.hir.voting_key.to_hex();
+        let row = |contribution: Option<&KeyContribution>| CsvRow {
+            voting_key: &voting_key,
+            voting_group: &entry.hir.voting_group,
+            voting_power: entry.hir.voting_power.into(),
+            stake_public_key: contribution.map(|c| c.stake_public_key.as_str()),
+            reward_address: contribution.map(|c| c.reward_address.as_str()),
+            value: contribution.map(|c| c.value),
+        };
+        if entry.contributions.is_empty() {
+            writer.serialize(row(None)).map_err(csv_error)?;
+        }
+        for contribution in &entry.contributions {
+            writer
+                .serialize(row(Some(contribution)))
+                .map_err(csv_error)?;
+        }
+    }
+    writer.into_inner().map_err(|e| csv_error(e.into_error()))
+}
+
+/// Hash of the JSON export of the current content of a snapshot tag
+pub fn content_hash(tag: &str, db_conn: &DbConnection) -> Result<String, HandleError> {
+    let mut export = Export::new(ExportFormat::Json);
+    export.start()?;
+    for entries in entries_pages(tag, db_conn) {
+        export.page(entries?)?;
+    }
+    Ok(export.finish().1)
+}
+
+/// Stores the hash of the current content of a snapshot tag in its `snapshots` row
+pub fn update_content_hash(tag: &str, db_conn: &DbConnection) -> Result<String, HandleError> {
+    let content_hash = content_hash(tag, db_conn)?;
+    put_snapshot_content_hash(tag, &content_hash, db_conn).map_err(db_error)?;
+    Ok(content_hash)
+}
+
+/// Reads a page of entries with its own pooled connection, released once the page is read
+async fn load_export_page(
+    tag: Tag,
+    after: Option<(String, String)>,
+    pool: &DbConnectionPool,
+) -> Result<(Vec<SnapshotInfo>, Option<(String, String)>), HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || load_entries_page(&tag, after, &db_conn))
+        .await
+        .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
+
+/// Next chunk to produce, entries are read after the given voter
+enum ExportStep {
+    Start,
+    Page(Option<(String, String)>),
+    Finish,
+}
+
+struct ExportState {
+    tag: Tag,
+    expected_hash: String,
+    pool: DbConnectionPool,
+    export: Export,
+    step: ExportStep,
+}
+
+impl ExportState {
+    /// Following chunk of the export, `None` once the export is complete or failed
+    async fn next_chunk(
+        state: Option<Self>,
+    ) -> Option<(Result<Vec<u8>, HandleError>, Option<Self>)> {
+        let mut state = state?;
+        let chunk = match std::mem::replace(&mut state.step, ExportStep::Finish) {
+            ExportStep::Start => {
+                state.step = ExportStep::Page(None);
+                state.export.start()
+            }
+            ExportStep::Page(after) => {
+                match load_export_page(state.tag.clone(), after, &state.pool).await {
+                    Ok((entries, last)) if !entries.is_empty() => {
+                        if entries.len() as i64 == EXPORT_PAGE_SIZE {
+                            state.step = ExportStep::Page(last);
+                        }
+                        state.export.page(entries)
+                    }
+                    Ok(_) => return Some((state.finish(), None)),
+                    Err(e) => Err(e),
+                }
+            }
+            ExportStep::Finish => return Some((state.finish(), None)),
+        };
+        let next = chunk.is_ok().then(|| state);
+        Some((chunk, next))
+    }
+
+    /// Pages are read in separate queries, so the export fails instead of completing if the
+    /// content does not match `expected_hash` anymore.
+    fn finish(self) -> Result<Vec<u8>, HandleError> {
+        let (end, content_hash) = self.export.finish();
+        if content_hash != self.expected_hash {
+            return Err(HandleError::InternalError(format!(
+                "Snapshot {} was updated during the export",
+                self.tag
+            )));
+        }
+        Ok(end)
+    }
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn export_snapshot(
+    tag: Tag,
+    format: ExportFormat,
+    context: SharedContext,
+) -> Result<Response, HandleError> {
+    let pool = context.read().await.db_connection_pool.clone();
+    let snapshot = query_snapshot_by_tag(tag.clone(), &pool).await?;
+
+    let content_hash = match snapshot.content_hash {
+        Some(content_hash) => content_hash,
+        // snapshots imported before content hashes were introduced get it on their first export
+        None => {
+            let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+            let tag = tag.clone();
+            tokio::task::spawn_blocking(move || update_content_hash(&tag, &db_conn))
+                .await
+                .map_err(|e| {
+                    HandleError::InternalError(format!("Error executing request: {}", e))
+                })??
+        }
+    };
+
+    // chunks are produced as the client reads them, so no connection is held while waiting on it
+    let state = ExportState {
+        tag,
+        expected_hash: content_hash.clone(),
+        pool,
+        export: Export::new(format),
+        step: ExportStep::Start,
+    };
+    let body = futures_util::stream::unfold(Some(state), ExportState::next_chunk).map(|chunk| {
+        chunk.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+    });
+    let content_type = match format {
+        ExportFormat::Json => "application/json",
+        ExportFormat::Csv => "text/csv",
+    };
+    http::Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .header(CONTENT_HASH_HEADER, content_hash)
+        .body(Body::wrap_stream(body))
+        .map_err(|e| HandleError::InternalError(format!("Error building response: {}", e)))
+}
@@ -1,9 +1,11 @@
+use super::{ExportFormat, JobId};
 use crate::v0::context::SharedContext;
 use crate::v0::result::HandlerResult;
+use futures_util::Stream;
 use jormungandr_lib::interfaces::Value;
 use serde::{Deserialize, Serialize};
-use snapshot_lib::{Fraction, RawSnapshot, SnapshotInfo};
-use warp::{Rejection, Reply};
+use snapshot_lib::{registration::VotingRegistration, Fraction, SnapshotInfo};
+use warp::{Buf, Rejection, Reply};
 
 #[tracing::instrument(skip(context))]
 pub async fn get_voters_info(
@@ -16,6 +18,16 @@ pub async fn get_voters_info(
     ))
 }
 
+#[tracing::instrument(skip(context))]
+pub async fn get_current_voters_info(
+    voting_key: String,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        super::get_current_voters_info(voting_key, context).await,
+    ))
+}
+
 #[tracing::instrument(skip(context))]
 pub async fn get_delegator_info(
     tag: String,
@@ -27,11 +39,127 @@ pub async fn get_delegator_info(
     ))
 }
 
+#[tracing::instrument(skip(context))]
+pub async fn get_voters_info_batch(
+    tag: String,
+    voting_keys: Vec<String>,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        super::get_voters_info_batch(tag, voting_keys, context).await,
+    ))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_delegators_info_batch(
+    tag: String,
+    stake_public_keys: Vec<String>,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        super::get_delegators_info_batch(tag, stake_public_keys, context).await,
+    ))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_voter_history(
+    voting_key: String,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        super::get_voter_history(voting_key, context).await,
+    ))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_delegator_history(
+    stake_public_key: String,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        super::get_delegator_history(stake_public_key, context).await,
+    ))
+}
+
 #[tracing::instrument(skip(context))]
 pub async fn get_tags(context: SharedContext) -> Result<impl Reply, Rejection> {
     Ok(HandlerResult(super::get_tags(context).await))
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerOrder {
+    Asc,
+    Desc,
+}
+
+/// Filtering and pagination of the voters of a snapshot tag
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct VotersQuery {
+    pub voting_group: Option<String>,
+    pub min_voting_power: Option<u64>,
+    /// Order by voting power, descending by default
+    pub order: Option<PowerOrder>,
+    pub limit: Option<u64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_voters(
+    tag: String,
+    query: VotersQuery,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(super::get_voters(tag, query, context).await))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_snapshot_stats(
+    tag: String,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(super::get_snapshot_stats(tag, context).await))
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SnapshotExportQuery {
+    pub format: Option<ExportFormat>,
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_snapshot_export(
+    tag: String,
+    query: SnapshotExportQuery,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(
+        match super::export::export_snapshot(tag, query.format.unwrap_or_default(), context).await {
+            Ok(response) => response,
+            Err(e) => e.into_response(),
+        },
+    )
+}
+
+/// Pagination of the changes listed by the snapshot diff
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SnapshotDiffQuery {
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_snapshot_diff(
+    from_tag: String,
+    to_tag: String,
+    query: SnapshotDiffQuery,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        super::get_snapshot_diff(from_tag, to_tag, query.limit, query.offset, context).await,
+    ))
+}
+
 /// Snapshot information update with timestamp.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct SnapshotInfoInput {
@@ -44,7 +172,7 @@ pub struct SnapshotInfoInput {
 /// Raw Snapshot information update with timestamp.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RawSnapshotInput {
-    pub snapshot: RawSnapshot,
+    pub snapshot: Vec<VotingRegistration>,
     #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
     #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
     pub update_timestamp: i64,
@@ -54,24 +182,87 @@ pub struct RawSnapshotInput {
     pub representatives_group: Option<String>,
 }
 
+impl RawSnapshotInput {
+    pub fn into_parts(self) -> (Vec<VotingRegistration>, RawSnapshotParams) {
+        (
+            self.snapshot,
+            RawSnapshotParams {
+                update_timestamp: self.update_timestamp,
+                min_stake_threshold: self.min_stake_threshold,
+                voting_power_cap: self.voting_power_cap,
+                direct_voters_group: self.direct_voters_group,
+                representatives_group: self.representatives_group,
+            },
+        )
+    }
+}
+
+/// Processing parameters of a raw snapshot, first line of a streamed raw snapshot upload.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RawSnapshotParams {
+    #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
+    #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
+    pub update_timestamp: i64,
+    pub min_stake_threshold: Value,
+    pub voting_power_cap: Fraction,
+    pub direct_voters_group: Option<String>,
+    pub representatives_group: Option<String>,
+}
+
+/// Processing parameters of a raw snapshot reprocessing along with the tag it is imported into.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RawSnapshotReprocessInput {
+    pub new_tag: String,
+    #[serde(flatten)]
+    pub params: RawSnapshotParams,
+}
+
+/// First line of a streamed snapshot information upload.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SnapshotInfoStreamHeader {
+    #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
+    #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
+    pub update_timestamp: i64,
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_raw_snapshots(context: SharedContext) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(super::get_raw_snapshots(context).await))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_raw_snapshot(
+    tag: String,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(super::get_raw_snapshot(tag, context).await))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn reprocess_raw_snapshot(
+    tag: String,
+    input: RawSnapshotReprocessInput,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(
+        super::reprocess_raw_snapshot(tag, input.new_tag, input.params, context).await,
+    ))
+}
+
+#[tracing::instrument(skip(input))]
+pub async fn preview_raw_snapshot(input: RawSnapshotInput) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(super::preview_raw_snapshot(input).await))
+}
+
 #[tracing::instrument(skip(context))]
 pub async fn put_raw_snapshot(
     tag: String,
     input: RawSnapshotInput,
     context: SharedContext,
 ) -> Result<impl Reply, Rejection> {
+    let (registrations, params) = input.into_parts();
     Ok(HandlerResult(
-        super::update_from_raw_snapshot(
-            tag,
-            input.snapshot,
-            input.update_timestamp,
-            input.min_stake_threshold,
-            input.voting_power_cap,
-            input.direct_voters_group,
-            input.representatives_group,
-            context,
-        )
-        .await,
+        super::update_from_raw_snapshot(tag, registrations, params, context).await,
     ))
 }
 
@@ -86,3 +277,43 @@ pub async fn put_snapshot_info(
             .await,
     ))
 }
+
+#[tracing::instrument(skip(context))]
+pub async fn delete_tag(tag: String, context: SharedContext) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(super::delete_tag(tag, context).await))
+}
+
+#[tracing::instrument(skip(body, context))]
+pub async fn put_raw_snapshot_stream<S, B>(
+    tag: String,
+    body: S,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection>
+where
+    S: Stream<Item = Result<B, warp::Error>>,
+    B: Buf,
+{
+    Ok(HandlerResult(
+        super::stream::start_raw_snapshot_upload(tag, body, context).await,
+    ))
+}
+
+#[tracing::instrument(skip(body, context))]
+pub async fn put_snapshot_info_stream<S, B>(
+    tag: String,
+    body: S,
+    context: SharedContext,
+) -> Result<impl Reply, Rejection>
+where
+    S: Stream<Item = Result<B, warp::Error>>,
+    B: Buf,
+{
+    Ok(HandlerResult(
+        super::stream::start_snapshot_info_upload(tag, body, context).await,
+    ))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_snapshot_job(id: JobId, context: SharedContext) -> Result<impl Reply, Rejection> {
+    Ok(HandlerResult(super::get_snapshot_job(id, context).await))
+}
@@ -0,0 +1,83 @@
+use super::Tag;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+pub type JobId = u64;
+
+/// Number of finished jobs kept around so their status can still be queried
+const MAX_FINISHED_JOBS: usize = 100;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Finished,
+    Failed { error: String },
+}
+
+/// Progress of a streamed snapshot upload
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotJob {
+    pub id: JobId,
+    pub tag: Tag,
+    #[serde(flatten)]
+    pub state: JobState,
+    /// Registrations or snapshot entries read from the upload so far
+    pub entries_read: u64,
+    /// Voters being written to the database, they are visible once the job is finished
+    pub voters_processed: u64,
+    /// Contributions being written to the database, they are visible once the job is finished
+    pub contributions_processed: u64,
+}
+
+impl SnapshotJob {
+    pub fn is_running(&self) -> bool {
+        self.state == JobState::Running
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SnapshotJobs {
+    jobs: Mutex<BTreeMap<JobId, SnapshotJob>>,
+}
+
+impl SnapshotJobs {
+    pub fn start(&self, tag: Tag) -> SnapshotJob {
+        let mut jobs = self.jobs.lock().unwrap();
+
+        let finished = jobs.values().filter(|job| !job.is_running()).count();
+        if finished >= MAX_FINISHED_JOBS {
+            // ids are increasing, the first finished job is the oldest one
+            if let Some(id) = jobs
+                .values()
+                .find(|job| !job.is_running())
+                .map(|job| job.id)
+            {
+                jobs.remove(&id);
+            }
+        }
+
+        let id = jobs.keys().next_back().map_or(1, |id| id + 1);
+        let job = SnapshotJob {
+            id,
+            tag,
+            state: JobState::Running,
+            entries_read: 0,
+            voters_processed: 0,
+            contributions_processed: 0,
+        };
+        jobs.insert(id, job.clone());
+        job
+    }
+
+    pub fn get(&self, id: JobId) -> Option<SnapshotJob> {
+        self.jobs.lock().unwrap().get(&id).cloned()
+    }
+
+    pub fn update(&self, id: JobId, f: impl FnOnce(&mut SnapshotJob)) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(&id) {
+            f(job)
+        }
+    }
+}
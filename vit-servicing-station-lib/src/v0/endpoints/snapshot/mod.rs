@@ -1,5 +1,11 @@
+mod export;
 mod handlers;
+mod jobs;
+mod preview;
+mod raw;
 mod routes;
+mod stats;
+mod stream;
 
 use crate::{
     db::{
@@ -7,25 +13,49 @@ use crate::{
             self,
             snapshot::{Contribution, Voter},
         },
+        queries::funds::{query_current_fund, query_funds_snapshot_tags},
         queries::snapshot::{
-            batch_put_contributions, batch_put_voters, put_snapshot, query_all_snapshots,
+            delete_snapshot, query_all_snapshots, query_changed_stake_public_keys,
+            query_changed_voting_keys, query_contributions_by_stake_public_key,
             query_contributions_by_stake_public_key_and_snapshot_tag,
-            query_contributions_by_voting_key_and_voter_group_and_snapshot_tag,
-            query_snapshot_by_tag, query_total_voting_power_by_voting_group_and_snapshot_tag,
-            query_voters_by_voting_key_and_snapshot_tag,
+            query_contributions_by_stake_public_keys_and_snapshot_tags,
+            query_contributions_by_voting_keys_and_snapshot_tag, query_snapshot_by_tag,
+            query_voter_aggregates_by_voting_key,
+            query_voter_aggregates_by_voting_key_and_snapshot_tag,
+            query_voter_aggregates_by_voting_keys_and_snapshot_tag, query_voters_by_snapshot_tag,
+            query_voters_by_voting_keys_and_snapshot_tags,
+            query_voting_groups_stats_by_snapshot_tag, query_voting_groups_summary_by_snapshot_tag,
+            replace_snapshot, RawSnapshotContent, SnapshotChunk, VoterAggregates, VotersCursor,
+            VotersFilter, VotingGroupSummary,
         },
     },
+    server::settings::SnapshotRetention,
     v0::{context::SharedContext, errors::HandleError},
 };
-pub use handlers::{RawSnapshotInput, SnapshotInfoInput};
+use export::content_hash;
+pub use export::{ExportFormat, CONTENT_HASH_HEADER};
+pub use handlers::{
+    PowerOrder, RawSnapshotInput, RawSnapshotParams, RawSnapshotReprocessInput, SnapshotDiffQuery,
+    SnapshotExportQuery, SnapshotInfoInput, SnapshotInfoStreamHeader, VotersQuery,
+};
 use itertools::Itertools;
+pub use jobs::{JobId, JobState, SnapshotJob, SnapshotJobs};
 use jormungandr_lib::interfaces::Value;
+pub use preview::{preview_raw_snapshot, PreviewVoter, PreviewVotingGroup, SnapshotPreview};
+pub use raw::{get_raw_snapshot, get_raw_snapshots, reprocess_raw_snapshot, StoredRawSnapshot};
 pub use routes::{filter, update_filter};
 use serde::{Deserialize, Serialize};
 use snapshot_lib::{
+    registration::VotingRegistration,
     voting_group::{RepsVotersAssigner, DEFAULT_DIRECT_VOTER_GROUP, DEFAULT_REPRESENTATIVE_GROUP},
     Fraction, RawSnapshot, Snapshot, SnapshotInfo,
 };
+pub use stats::{
+    ContentVersion, SnapshotStats, SnapshotStatsCache, TopVotersShare, VotingGroupStats,
+    VotingPowerPercentiles,
+};
+use std::collections::{BTreeMap, HashMap};
+use time::OffsetDateTime;
 
 pub type Tag = String;
 pub type Group = String;
@@ -39,6 +69,22 @@ pub struct VoterInfo {
     pub voting_power_saturation: f64,
 }
 
+impl From<VoterAggregates> for VoterInfo {
+    fn from(voter: VoterAggregates) -> Self {
+        Self {
+            voting_power: Value::from(voter.voting_power as u64),
+            delegations_count: voter.delegations_count as u64,
+            delegations_power: voter.delegations_power as u64,
+            voting_power_saturation: if voter.total_voting_power != 0 {
+                voter.voting_power as f64 / voter.total_voting_power as f64
+            } else {
+                0_f64
+            },
+            voting_group: voter.voting_group,
+        }
+    }
+}
+
 /// Voter information in the current snapshot
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct VotersInfo {
@@ -59,45 +105,211 @@ pub async fn get_voters_info(
     let pool = &context.read().await.db_connection_pool;
 
     let snapshot = query_snapshot_by_tag(tag.clone(), pool).await?;
-    let mut voter_info = Vec::new();
-    let voters =
-        query_voters_by_voting_key_and_snapshot_tag(voting_key.clone(), tag.clone(), pool).await?;
+    let voter_info = query_voter_aggregates_by_voting_key_and_snapshot_tag(voting_key, tag, pool)
+        .await?
+        .into_iter()
+        .map(VoterInfo::from)
+        .collect();
 
-    for voter in voters {
-        let contributors = query_contributions_by_voting_key_and_voter_group_and_snapshot_tag(
-            voting_key.clone(),
-            voter.voting_group.clone(),
-            tag.clone(),
-            pool,
-        )
-        .await?;
+    Ok(VotersInfo {
+        voter_info,
+        last_updated: snapshot.last_updated,
+    })
+}
 
-        let total_voting_power_per_group =
-            query_total_voting_power_by_voting_group_and_snapshot_tag(
-                voter.voting_group.clone(),
-                tag.clone(),
-                pool,
-            )
-            .await? as f64;
+/// Voter information in the snapshot tag of the current fund. Only the voting groups of the fund
+/// are listed, unless it has none.
+#[tracing::instrument(skip(context))]
+pub async fn get_current_voters_info(
+    voting_key: String,
+    context: SharedContext,
+) -> Result<VotersInfo, HandleError> {
+    let fund = {
+        let context = context.read().await;
+        query_current_fund(&context.db_connection_pool, context.current_fund_fallback)
+            .await?
+            .fund
+    };
+    let tag = fund
+        .snapshot_tag
+        .ok_or_else(|| HandleError::NotFound(format!("snapshot tag of fund {}", fund.id)))?;
+
+    let mut voters_info = get_voters_info(tag, voting_key, context).await?;
+    voters_info
+        .voter_info
+        .retain(|voter| fund.has_voting_group(&voter.voting_group));
+    Ok(voters_info)
+}
 
-        voter_info.push(VoterInfo {
-            voting_power: Value::from(voter.voting_power as u64),
-            delegations_count: contributors.len() as u64,
-            delegations_power: contributors
-                .iter()
-                .map(|contributor| contributor.value as u64)
-                .sum(),
-            voting_group: voter.voting_group,
-            voting_power_saturation: if total_voting_power_per_group != 0_f64 {
-                voter.voting_power as f64 / total_voting_power_per_group
-            } else {
-                0_f64
-            },
-        })
+#[tracing::instrument(skip(context))]
+pub async fn get_snapshot_stats(
+    tag: String,
+    context: SharedContext,
+) -> Result<SnapshotStats, HandleError> {
+    let context = context.read().await;
+    let pool = &context.db_connection_pool;
+    // the row is read before the stats, so they are never older than the version they are cached
+    // with
+    let snapshot = query_snapshot_by_tag(tag.clone(), pool).await?;
+    let version = ContentVersion::from(&snapshot);
+    if let Some(stats) = context.snapshot_stats.get(&tag, &version) {
+        return Ok(stats);
     }
 
-    Ok(VotersInfo {
-        voter_info,
+    let stats = SnapshotStats {
+        voting_groups: query_voting_groups_stats_by_snapshot_tag(tag.clone(), pool)
+            .await?
+            .into_iter()
+            .map(VotingGroupStats::from)
+            .collect(),
+        tag,
+        last_updated: snapshot.last_updated,
+    };
+    context.snapshot_stats.insert(version, stats.clone());
+    Ok(stats)
+}
+
+/// Voter information of a voting key in one of its voting groups
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VoterEntry {
+    pub voting_key: String,
+    #[serde(flatten)]
+    pub info: VoterInfo,
+}
+
+/// A page of the voters of a snapshot tag
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VotersPage {
+    pub voters: Vec<VoterEntry>,
+    /// Cursor of the following page, `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Timestamp for the latest update in voter info in the snapshot
+    #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
+    #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
+    pub last_updated: i64,
+}
+
+const DEFAULT_VOTERS_PAGE_SIZE: u64 = 100;
+const MAX_VOTERS_PAGE_SIZE: u64 = 1000;
+
+fn encode_voters_cursor(voter: &Voter) -> String {
+    // the voting group goes last as it is the only field that may contain the separator
+    base64::encode_config(
+        format!(
+            "{}:{}:{}",
+            voter.voting_power, voter.voting_key, voter.voting_group
+        ),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+fn decode_voters_cursor(cursor: &str) -> Result<VotersCursor, HandleError> {
+    let invalid_cursor = || HandleError::BadRequest(format!("invalid cursor {}", cursor));
+    let decoded = base64::decode_config(cursor, base64::URL_SAFE_NO_PAD)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(invalid_cursor)?;
+    let mut fields = decoded.splitn(3, ':');
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some(voting_power), Some(voting_key), Some(voting_group)) => Ok(VotersCursor {
+            voting_power: voting_power.parse().map_err(|_| invalid_cursor())?,
+            voting_key: voting_key.to_string(),
+            voting_group: voting_group.to_string(),
+        }),
+        _ => Err(invalid_cursor()),
+    }
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_voters(
+    tag: String,
+    query: VotersQuery,
+    context: SharedContext,
+) -> Result<VotersPage, HandleError> {
+    let pool = &context.read().await.db_connection_pool;
+
+    let snapshot = query_snapshot_by_tag(tag.clone(), pool).await?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_VOTERS_PAGE_SIZE)
+        .min(MAX_VOTERS_PAGE_SIZE) as usize;
+    let filter = VotersFilter {
+        voting_group: query.voting_group,
+        min_voting_power: query
+            .min_voting_power
+            .map(|power| power.min(i64::MAX as u64) as i64),
+        descending: query.order.unwrap_or(PowerOrder::Desc) == PowerOrder::Desc,
+        after: query
+            .cursor
+            .as_deref()
+            .map(decode_voters_cursor)
+            .transpose()?,
+        // one more voter tells whether there is a following page
+        limit: limit as i64 + 1,
+    };
+    let mut voters = query_voters_by_snapshot_tag(tag.clone(), filter, pool).await?;
+    let next_cursor = if voters.len() > limit {
+        voters.truncate(limit);
+        voters.last().map(encode_voters_cursor)
+    } else {
+        None
+    };
+
+    let groups_power: HashMap<Group, i64> =
+        query_voting_groups_summary_by_snapshot_tag(tag.clone(), pool)
+            .await?
+            .into_iter()
+            .map(|summary| (summary.voting_group, summary.total_voting_power))
+            .collect();
+
+    let voting_keys = voters
+        .iter()
+        .map(|voter| voter.voting_key.clone())
+        .unique()
+        .collect();
+    let mut delegations: HashMap<(String, Group), (u64, u64)> = HashMap::new();
+    for contribution in
+        query_contributions_by_voting_keys_and_snapshot_tag(voting_keys, tag, pool).await?
+    {
+        let (count, power) = delegations
+            .entry((contribution.voting_key, contribution.voting_group))
+            .or_default();
+        *count += 1;
+        *power += contribution.value as u64;
+    }
+
+    let voters = voters
+        .into_iter()
+        .map(|voter| {
+            let (delegations_count, delegations_power) = delegations
+                .get(&(voter.voting_key.clone(), voter.voting_group.clone()))
+                .copied()
+                .unwrap_or_default();
+            let total_voting_power = groups_power
+                .get(&voter.voting_group)
+                .copied()
+                .unwrap_or_default() as f64;
+            VoterEntry {
+                voting_key: voter.voting_key,
+                info: VoterInfo {
+                    voting_power: Value::from(voter.voting_power as u64),
+                    delegations_count,
+                    delegations_power,
+                    voting_power_saturation: if total_voting_power != 0_f64 {
+                        voter.voting_power as f64 / total_voting_power
+                    } else {
+                        0_f64
+                    },
+                    voting_group: voter.voting_group,
+                },
+            }
+        })
+        .collect();
+
+    Ok(VotersPage {
+        voters,
+        next_cursor,
         last_updated: snapshot.last_updated,
     })
 }
@@ -126,7 +338,11 @@ pub async fn get_delegator_info(
         query_contributions_by_stake_public_key_and_snapshot_tag(stake_public_key, tag, pool)
             .await?;
 
-    Ok(DelegatorInfo {
+    Ok(delegator_info(&contributions, snapshot.last_updated))
+}
+
+fn delegator_info(contributions: &[Contribution], last_updated: i64) -> DelegatorInfo {
+    DelegatorInfo {
         dreps: contributions
             .iter()
             .map(|contribution| contribution.voting_key.clone())
@@ -137,8 +353,187 @@ pub async fn get_delegator_info(
             .map(|contribution| contribution.voting_group.clone())
             .unique()
             .collect(),
-        last_updated: snapshot.last_updated,
-    })
+        last_updated,
+    }
+}
+
+/// Default maximum number of keys of a batch lookup
+pub const DEFAULT_SNAPSHOT_MAX_BATCH_SIZE: usize = 500;
+
+/// Requested keys without duplicates, in their original order
+fn batch_keys(keys: Vec<String>, max_batch_size: usize) -> Result<Vec<String>, HandleError> {
+    let keys: Vec<String> = keys.into_iter().unique().collect();
+    if keys.len() > max_batch_size {
+        return Err(HandleError::BadRequest(format!(
+            "Too many keys, at most {} keys can be requested at once, got {}",
+            max_batch_size,
+            keys.len()
+        )));
+    }
+    Ok(keys)
+}
+
+/// Voter information of each requested voting key, keys not in the snapshot have no entries
+#[tracing::instrument(skip(context))]
+pub async fn get_voters_info_batch(
+    tag: String,
+    voting_keys: Vec<String>,
+    context: SharedContext,
+) -> Result<BTreeMap<String, VotersInfo>, HandleError> {
+    let context = context.read().await;
+    let voting_keys = batch_keys(voting_keys, context.snapshot_max_batch_size)?;
+    let pool = &context.db_connection_pool;
+
+    let snapshot = query_snapshot_by_tag(tag.clone(), pool).await?;
+    let mut voters =
+        query_voter_aggregates_by_voting_keys_and_snapshot_tag(voting_keys.clone(), tag, pool)
+            .await?
+            .into_iter()
+            .map(|voter| (voter.voting_key.clone(), VoterInfo::from(voter)))
+            .into_group_map();
+
+    Ok(voting_keys
+        .into_iter()
+        .map(|voting_key| {
+            let voter_info = voters.remove(&voting_key).unwrap_or_default();
+            (
+                voting_key,
+                VotersInfo {
+                    voter_info,
+                    last_updated: snapshot.last_updated,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Delegations of each requested stake key, keys not in the snapshot have no entries
+#[tracing::instrument(skip(context))]
+pub async fn get_delegators_info_batch(
+    tag: String,
+    stake_public_keys: Vec<String>,
+    context: SharedContext,
+) -> Result<BTreeMap<String, DelegatorInfo>, HandleError> {
+    let context = context.read().await;
+    let stake_public_keys = batch_keys(stake_public_keys, context.snapshot_max_batch_size)?;
+    let pool = &context.db_connection_pool;
+
+    let snapshot = query_snapshot_by_tag(tag.clone(), pool).await?;
+    let mut contributions = query_contributions_by_stake_public_keys_and_snapshot_tags(
+        stake_public_keys.clone(),
+        vec![tag],
+        pool,
+    )
+    .await?
+    .into_iter()
+    .map(|contribution| (contribution.stake_public_key.clone(), contribution))
+    .into_group_map();
+
+    Ok(stake_public_keys
+        .into_iter()
+        .map(|stake_public_key| {
+            let contributions = contributions.remove(&stake_public_key).unwrap_or_default();
+            let info = delegator_info(&contributions, snapshot.last_updated);
+            (stake_public_key, info)
+        })
+        .collect())
+}
+
+/// Voter information of a voting key in a snapshot tag
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VoterHistoryEntry {
+    pub tag: Tag,
+    #[serde(flatten)]
+    pub info: VotersInfo,
+}
+
+/// Delegations of a stake key in a snapshot tag
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegatorHistoryEntry {
+    pub tag: Tag,
+    #[serde(flatten)]
+    pub info: DelegatorInfo,
+}
+
+/// Every snapshot tag ordered by update time along with the entries of the key in each of them,
+/// tags the key is not part of are listed without entries
+fn history<T, E>(
+    snapshots: Vec<models::snapshot::Snapshot>,
+    mut entries: HashMap<Tag, Vec<T>>,
+    entry: impl Fn(Tag, i64, Vec<T>) -> E,
+) -> Vec<E> {
+    snapshots
+        .into_iter()
+        .map(|snapshot| {
+            let tag_entries = entries.remove(&snapshot.tag).unwrap_or_default();
+            entry(snapshot.tag, snapshot.last_updated, tag_entries)
+        })
+        .collect()
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_voter_history(
+    voting_key: String,
+    context: SharedContext,
+) -> Result<Vec<VoterHistoryEntry>, HandleError> {
+    let pool = &context.read().await.db_connection_pool;
+
+    let snapshots = query_all_snapshots(pool).await?;
+    let mut voters: HashMap<Tag, Vec<VoterInfo>> = HashMap::new();
+    for voter in query_voter_aggregates_by_voting_key(voting_key, pool).await? {
+        voters
+            .entry(voter.snapshot_tag.clone())
+            .or_default()
+            .push(VoterInfo::from(voter));
+    }
+
+    Ok(history(
+        snapshots,
+        voters,
+        |tag, last_updated, voter_info| VoterHistoryEntry {
+            tag,
+            info: VotersInfo {
+                voter_info,
+                last_updated,
+            },
+        },
+    ))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_delegator_history(
+    stake_public_key: String,
+    context: SharedContext,
+) -> Result<Vec<DelegatorHistoryEntry>, HandleError> {
+    let pool = &context.read().await.db_connection_pool;
+
+    let snapshots = query_all_snapshots(pool).await?;
+    let contributions = query_contributions_by_stake_public_key(stake_public_key, pool)
+        .await?
+        .into_iter()
+        .map(|contribution| (contribution.snapshot_tag.clone(), contribution))
+        .into_group_map();
+
+    Ok(history(
+        snapshots,
+        contributions,
+        |tag, last_updated, contributions| DelegatorHistoryEntry {
+            tag,
+            info: DelegatorInfo {
+                dreps: contributions
+                    .iter()
+                    .map(|contribution| contribution.voting_key.clone())
+                    .unique()
+                    .collect(),
+                voting_groups: contributions
+                    .iter()
+                    .map(|contribution| contribution.voting_group.clone())
+                    .unique()
+                    .collect(),
+                last_updated,
+            },
+        },
+    ))
 }
 
 pub async fn get_tags(context: SharedContext) -> Result<Vec<Tag>, HandleError> {
@@ -151,99 +546,487 @@ pub async fn get_tags(context: SharedContext) -> Result<Vec<Tag>, HandleError> {
         .collect())
 }
 
-#[allow(clippy::too_many_arguments)]
-#[tracing::instrument(skip(snapshot, context))]
-pub async fn update_from_raw_snapshot(
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// Only present in the newer tag
+    Added,
+    /// Only present in the older tag
+    Removed,
+    /// Present in both tags with different values
+    Changed,
+}
+
+impl ChangeKind {
+    fn new<T>(from: &[T], to: &[T]) -> Self {
+        match (from.is_empty(), to.is_empty()) {
+            (true, _) => ChangeKind::Added,
+            (_, true) => ChangeKind::Removed,
+            _ => ChangeKind::Changed,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VotingGroupPower {
+    pub voting_group: Group,
+    pub voting_power: Value,
+}
+
+/// Voting power per group of a voting key in each tag
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoterChange {
+    pub voting_key: String,
+    pub kind: ChangeKind,
+    pub from: Vec<VotingGroupPower>,
+    pub to: Vec<VotingGroupPower>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delegation {
+    pub voting_key: String,
+    pub voting_group: Group,
+    pub reward_address: String,
+    pub value: u64,
+}
+
+/// Contributions of a stake key in each tag
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributionChange {
+    pub stake_public_key: String,
+    pub kind: ChangeKind,
+    pub from: Vec<Delegation>,
+    pub to: Vec<Delegation>,
+}
+
+/// A page of changes, `total` counts every change regardless of the pagination
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangesPage<T> {
+    pub total: u64,
+    pub changes: Vec<T>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagSummary {
+    pub tag: Tag,
+    #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
+    #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
+    pub last_updated: i64,
+    pub voting_groups: Vec<VotingGroupSummary>,
+}
+
+/// Changes between two snapshot tags
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub from: TagSummary,
+    pub to: TagSummary,
+    pub voters: ChangesPage<VoterChange>,
+    pub contributions: ChangesPage<ContributionChange>,
+}
+
+/// Entries of the same key in the compared tags
+struct TagsPair<T> {
+    from: Vec<T>,
+    to: Vec<T>,
+}
+
+impl<T> Default for TagsPair<T> {
+    fn default() -> Self {
+        Self {
+            from: Vec::new(),
+            to: Vec::new(),
+        }
+    }
+}
+
+impl<T> TagsPair<T> {
+    fn tag_entries(&mut self, tag: &str, from_tag: &str) -> &mut Vec<T> {
+        if tag == from_tag {
+            &mut self.from
+        } else {
+            &mut self.to
+        }
+    }
+}
+
+const DEFAULT_DIFF_PAGE_SIZE: u64 = 100;
+// the keys of a page are bound in a single query, keep them below the sqlite limit of 999
+// bound variables
+const MAX_DIFF_PAGE_SIZE: u64 = 500;
+
+async fn get_tag_summary(
     tag: String,
+    pool: &crate::db::DbConnectionPool,
+) -> Result<TagSummary, HandleError> {
+    let snapshot = query_snapshot_by_tag(tag.clone(), pool).await?;
+    Ok(TagSummary {
+        voting_groups: query_voting_groups_summary_by_snapshot_tag(tag.clone(), pool).await?,
+        tag,
+        last_updated: snapshot.last_updated,
+    })
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn get_snapshot_diff(
+    from_tag: String,
+    to_tag: String,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    context: SharedContext,
+) -> Result<SnapshotDiff, HandleError> {
+    let pool = &context.read().await.db_connection_pool;
+
+    let from = get_tag_summary(from_tag.clone(), pool).await?;
+    let to = get_tag_summary(to_tag.clone(), pool).await?;
+
+    let limit = limit
+        .unwrap_or(DEFAULT_DIFF_PAGE_SIZE)
+        .min(MAX_DIFF_PAGE_SIZE) as i64;
+    let offset = offset.unwrap_or(0).min(i64::MAX as u64) as i64;
+    let tags = vec![from_tag.clone(), to_tag.clone()];
+
+    let (voters_total, voting_keys) =
+        query_changed_voting_keys(from_tag.clone(), to_tag.clone(), limit, offset, pool).await?;
+    let voters =
+        query_voters_by_voting_keys_and_snapshot_tags(voting_keys.clone(), tags.clone(), pool)
+            .await?;
+    let mut voters_powers: HashMap<String, TagsPair<VotingGroupPower>> = HashMap::new();
+    for voter in voters {
+        let powers = voters_powers.entry(voter.voting_key).or_default();
+        powers
+            .tag_entries(&voter.snapshot_tag, &from_tag)
+            .push(VotingGroupPower {
+                voting_group: voter.voting_group,
+                voting_power: Value::from(voter.voting_power as u64),
+            });
+    }
+    let voter_changes = voting_keys
+        .into_iter()
+        .map(|voting_key| {
+            let TagsPair { from, to } = voters_powers.remove(&voting_key).unwrap_or_default();
+            VoterChange {
+                kind: ChangeKind::new(&from, &to),
+                voting_key,
+                from,
+                to,
+            }
+        })
+        .collect();
+
+    let (contributions_total, stake_public_keys) =
+        query_changed_stake_public_keys(from_tag.clone(), to_tag.clone(), limit, offset, pool)
+            .await?;
+    let contributions = query_contributions_by_stake_public_keys_and_snapshot_tags(
+        stake_public_keys.clone(),
+        tags,
+        pool,
+    )
+    .await?;
+    let mut delegations: HashMap<String, TagsPair<Delegation>> = HashMap::new();
+    for contribution in contributions {
+        let entries = delegations
+            .entry(contribution.stake_public_key)
+            .or_default();
+        entries
+            .tag_entries(&contribution.snapshot_tag, &from_tag)
+            .push(Delegation {
+                voting_key: contribution.voting_key,
+                voting_group: contribution.voting_group,
+                reward_address: contribution.reward_address,
+                value: contribution.value as u64,
+            });
+    }
+    let contribution_changes = stake_public_keys
+        .into_iter()
+        .map(|stake_public_key| {
+            let TagsPair { from, to } = delegations.remove(&stake_public_key).unwrap_or_default();
+            ContributionChange {
+                kind: ChangeKind::new(&from, &to),
+                stake_public_key,
+                from,
+                to,
+            }
+        })
+        .collect();
+
+    Ok(SnapshotDiff {
+        from,
+        to,
+        voters: ChangesPage {
+            total: voters_total as u64,
+            changes: voter_changes,
+        },
+        contributions: ChangesPage {
+            total: contributions_total as u64,
+            changes: contribution_changes,
+        },
+    })
+}
+
+/// Number of snapshot entries written to the database at once
+const SNAPSHOT_CHUNK_SIZE: usize = 1000;
+
+fn process_raw_snapshot(
     snapshot: RawSnapshot,
-    update_timestamp: i64,
     min_stake_threshold: Value,
     voting_power_cap: Fraction,
     direct_voters_group: Option<String>,
     representatives_group: Option<String>,
-    context: SharedContext,
-) -> Result<(), HandleError> {
+) -> Result<Vec<SnapshotInfo>, HandleError> {
     let direct_voter = direct_voters_group.unwrap_or_else(|| DEFAULT_DIRECT_VOTER_GROUP.into());
     let representative =
         representatives_group.unwrap_or_else(|| DEFAULT_REPRESENTATIVE_GROUP.into());
     let assigner = RepsVotersAssigner::new(direct_voter, representative);
-    let snapshot =
+    Ok(
         Snapshot::from_raw_snapshot(snapshot, min_stake_threshold, voting_power_cap, &assigner)
             .map_err(|e| HandleError::InternalError(e.to_string()))?
-            .to_full_snapshot_info();
-
-    update_from_shanpshot_info(tag, snapshot, update_timestamp, context).await
+            .to_full_snapshot_info(),
+    )
 }
 
-#[tracing::instrument(skip(snapshot, context))]
-pub async fn update_from_shanpshot_info(
+/// Processes the registrations and imports the result, the registrations are stored along with
+/// the processing parameters so the tag can be reprocessed later on
+#[tracing::instrument(skip(registrations, context))]
+pub async fn update_from_raw_snapshot(
     tag: String,
-    snapshot: impl IntoIterator<Item = SnapshotInfo>,
-    update_timestamp: i64,
+    registrations: Vec<VotingRegistration>,
+    params: RawSnapshotParams,
     context: SharedContext,
 ) -> Result<(), HandleError> {
-    let pool = &context.read().await.db_connection_pool;
-
-    put_snapshot(
-        models::snapshot::Snapshot {
-            tag: tag.clone(),
-            last_updated: update_timestamp,
-        },
-        pool,
+    let raw_snapshot = raw::raw_snapshot_content(&tag, &params, &registrations)?;
+    let snapshot = process_raw_snapshot(
+        RawSnapshot::from(registrations),
+        params.min_stake_threshold,
+        params.voting_power_cap,
+        params.direct_voters_group,
+        params.representatives_group,
     )?;
 
-    let mut contributions = Vec::new();
-    let mut voters = Vec::new();
-    for entry in snapshot.into_iter() {
-        contributions.extend(entry.contributions.into_iter().map(|contribution| {
-            Contribution {
-                stake_public_key: contribution.stake_public_key,
-                reward_address: contribution.reward_address,
-                value: contribution
-                    .value
-                    .try_into()
-                    .expect("value should not exceed i64 limit"),
-                voting_key: entry.hir.voting_key.to_hex(),
-                voting_group: entry.hir.voting_group.clone(),
-                snapshot_tag: tag.clone(),
-            }
-        }));
+    import_snapshot(
+        tag,
+        snapshot,
+        params.update_timestamp,
+        Some(raw_snapshot),
+        context,
+    )
+    .await
+}
+
+fn to_i64(value: u64) -> Result<i64, HandleError> {
+    value
+        .try_into()
+        .map_err(|_| HandleError::BadRequest(format!("value {} exceeds i64 limit", value)))
+}
 
-        voters.push(Voter {
-            voting_key: entry.hir.voting_key.to_hex(),
+fn push_snapshot_entry(
+    chunk: &mut SnapshotChunk,
+    tag: &str,
+    entry: SnapshotInfo,
+) -> Result<(), HandleError> {
+    let voting_key = entry.hir.voting_key.to_hex();
+    for contribution in entry.contributions {
+        chunk.contributions.push(Contribution {
+            stake_public_key: contribution.stake_public_key,
+            reward_address: contribution.reward_address,
+            value: to_i64(contribution.value)?,
+            voting_key: voting_key.clone(),
             voting_group: entry.hir.voting_group.clone(),
-            voting_power: Into::<u64>::into(entry.hir.voting_power)
-                .try_into()
-                .expect("value should not exceed i64 limit"),
-            snapshot_tag: tag.clone(),
+            snapshot_tag: tag.to_string(),
         });
     }
-    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
-    batch_put_voters(&voters, &db_conn)?;
-    batch_put_contributions(&contributions, &db_conn)?;
+    chunk.voters.push(Voter {
+        voting_key,
+        voting_group: entry.hir.voting_group,
+        voting_power: to_i64(entry.hir.voting_power.into())?,
+        snapshot_tag: tag.to_string(),
+    });
     Ok(())
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::db::migrations::initialize_db_with_migration;
-    use crate::v0::context::test::new_in_memmory_db_test_shared_context;
-    use jormungandr_lib::crypto::account::Identifier;
-    use snapshot_lib::registration::{Delegations, VotingRegistration};
-    use snapshot_lib::{KeyContribution, SnapshotInfo, VoterHIR};
-    use tracing::Level;
-    use warp::hyper::StatusCode;
-    use warp::{Filter, Reply};
-
-    #[tokio::test]
-    pub async fn test_snapshot() {
-        let context = new_in_memmory_db_test_shared_context();
-        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
-        initialize_db_with_migration(db_conn);
+/// Groups the snapshot entries in chunks of rows to be written to the database
+fn snapshot_chunks(
+    tag: Tag,
+    entries: impl Iterator<Item = Result<SnapshotInfo, HandleError>>,
+) -> impl Iterator<Item = Result<SnapshotChunk, HandleError>> {
+    let mut entries = entries.peekable();
+    std::iter::from_fn(move || {
+        entries.peek()?;
+        let mut chunk = SnapshotChunk::default();
+        for entry in entries.by_ref().take(SNAPSHOT_CHUNK_SIZE) {
+            if let Err(e) = entry.and_then(|entry| push_snapshot_entry(&mut chunk, &tag, entry)) {
+                return Some(Err(e));
+            }
+        }
+        Some(Ok(chunk))
+    })
+}
 
-        let keys = [
-            Identifier::from_hex(
+#[tracing::instrument(skip(snapshot, context))]
+pub async fn update_from_shanpshot_info(
+    tag: String,
+    snapshot: impl IntoIterator<Item = SnapshotInfo>,
+    update_timestamp: i64,
+    context: SharedContext,
+) -> Result<(), HandleError> {
+    import_snapshot(tag, snapshot, update_timestamp, None, context).await
+}
+
+async fn import_snapshot(
+    tag: String,
+    snapshot: impl IntoIterator<Item = SnapshotInfo>,
+    update_timestamp: i64,
+    raw_snapshot: Option<RawSnapshotContent>,
+    context: SharedContext,
+) -> Result<(), HandleError> {
+    let context = context.read().await;
+    let pool = &context.db_connection_pool;
+
+    let entries: Vec<SnapshotInfo> = snapshot.into_iter().collect();
+    let snapshot = models::snapshot::Snapshot {
+        tag: tag.clone(),
+        last_updated: update_timestamp,
+        content_hash: None,
+    };
+    let chunks_tag = tag.clone();
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    tokio::task::spawn_blocking(move || {
+        let chunks = snapshot_chunks(chunks_tag, entries.into_iter().map(Ok));
+        replace_snapshot(snapshot, raw_snapshot, chunks, content_hash, &db_conn)
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))??;
+
+    context.snapshot_stats.invalidate(&tag);
+
+    enforce_snapshot_retention(
+        tag,
+        &context.snapshot_retention,
+        &context.snapshot_stats,
+        pool,
+    )
+    .await
+}
+
+pub async fn get_snapshot_job(
+    id: JobId,
+    context: SharedContext,
+) -> Result<SnapshotJob, HandleError> {
+    context
+        .read()
+        .await
+        .snapshot_jobs
+        .get(id)
+        .ok_or_else(|| HandleError::NotFound(format!("snapshot job {}", id)))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn delete_tag(tag: String, context: SharedContext) -> Result<(), HandleError> {
+    let context = context.read().await;
+    let db_conn = context
+        .db_connection_pool
+        .get()
+        .map_err(HandleError::DatabaseError)?;
+    let deleted_tag = tag.clone();
+    let deleted = tokio::task::spawn_blocking(move || delete_snapshot(&deleted_tag, &db_conn))
+        .await
+        .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))??;
+    context.snapshot_stats.invalidate(&tag);
+    if !deleted {
+        return Err(HandleError::NotFound("Snapshot tag not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Tags to be deleted according to the retention policy, `snapshots` must be sorted by
+/// ascending update time. Tags used by a fund are never deleted and do not count towards
+/// `keep_last`.
+fn expired_tags(
+    snapshots: Vec<models::snapshot::Snapshot>,
+    retention: &SnapshotRetention,
+    imported_tag: &str,
+    fund_tags: &[Tag],
+    now: i64,
+) -> Vec<Tag> {
+    // the imported tag is always kept and counts as the most recent one
+    let mut tags: Vec<Tag> = snapshots
+        .into_iter()
+        .rev()
+        .filter(|snapshot| snapshot.tag != imported_tag && !fund_tags.contains(&snapshot.tag))
+        .enumerate()
+        .filter(|(index, snapshot)| {
+            let too_many = retention
+                .keep_last
+                .map_or(false, |keep_last| *index as u64 + 2 > keep_last);
+            let too_old = retention.max_age_secs.map_or(false, |max_age_secs| {
+                now.saturating_sub(snapshot.last_updated) > max_age_secs.min(i64::MAX as u64) as i64
+            });
+            too_many || too_old
+        })
+        .map(|(_, snapshot)| snapshot.tag)
+        .collect();
+    tags.reverse();
+    tags
+}
+
+async fn enforce_snapshot_retention(
+    imported_tag: String,
+    retention: &SnapshotRetention,
+    stats: &SnapshotStatsCache,
+    pool: &crate::db::DbConnectionPool,
+) -> Result<(), HandleError> {
+    if retention.keep_last.is_none() && retention.max_age_secs.is_none() {
+        return Ok(());
+    }
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let tags = expired_tags(
+        query_all_snapshots(pool).await?,
+        retention,
+        &imported_tag,
+        &query_funds_snapshot_tags(pool).await?,
+        now,
+    );
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    let deleted_tags = tags.clone();
+    tokio::task::spawn_blocking(move || {
+        for tag in deleted_tags {
+            tracing::info!(
+                "deleting snapshot tag {} according to the retention policy",
+                tag
+            );
+            delete_snapshot(&tag, &db_conn)?;
+        }
+        Ok::<_, HandleError>(())
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))??;
+
+    for tag in tags {
+        stats.invalidate(&tag);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::migrations::initialize_db_with_migration;
+    use crate::v0::context::test::new_in_memmory_db_test_shared_context;
+    use jormungandr_lib::crypto::account::Identifier;
+    use snapshot_lib::registration::{Delegations, VotingRegistration};
+    use snapshot_lib::{KeyContribution, SnapshotInfo, VoterHIR};
+    use tracing::Level;
+    use warp::hyper::StatusCode;
+    use warp::{Filter, Reply};
+
+    #[tokio::test]
+    pub async fn test_snapshot() {
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let keys = [
+            Identifier::from_hex(
                 "0000000000000000000000000000000000000000000000000000000000000000",
             )
             .unwrap(),
@@ -556,88 +1339,1253 @@ mod test {
         .await
         .unwrap();
         update_from_shanpshot_info(
-            TAG2.to_string(),
-            inputs.clone(),
-            UPDATE_TIME1,
+            TAG2.to_string(),
+            inputs.clone(),
+            UPDATE_TIME1,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            super::get_voters_info(TAG1.to_string(), voting_key.to_hex(), context.clone())
+                .await
+                .unwrap()
+                .voter_info,
+            inputs
+                .iter()
+                .cloned()
+                .map(|snapshot| VoterInfo {
+                    voting_group: snapshot.hir.voting_group,
+                    voting_power: snapshot.hir.voting_power,
+                    delegations_power: snapshot
+                        .contributions
+                        .iter()
+                        .map(|KeyContribution { value, .. }| value)
+                        .sum(),
+                    delegations_count: snapshot.contributions.len() as u64,
+                    voting_power_saturation: 1_f64,
+                })
+                .collect::<Vec<_>>()
+        );
+
+        super::update_from_shanpshot_info(
+            TAG1.to_string(),
+            inputs[0..1].to_vec(),
+            UPDATE_TIME1,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            super::get_voters_info(TAG1.to_string(), voting_key.to_hex(), context.clone())
+                .await
+                .unwrap()
+                .voter_info,
+            inputs[0..1]
+                .iter()
+                .cloned()
+                .map(|snapshot| VoterInfo {
+                    voting_group: snapshot.hir.voting_group,
+                    voting_power: snapshot.hir.voting_power,
+                    delegations_power: snapshot
+                        .contributions
+                        .iter()
+                        .map(|KeyContribution { value, .. }| value)
+                        .sum(),
+                    delegations_count: snapshot.contributions.len() as u64,
+                    voting_power_saturation: 1_f64,
+                })
+                .collect::<Vec<_>>()
+        );
+
+        // asserting that TAG2 is untouched, just in case
+        assert_eq!(
+            super::get_voters_info(TAG2.to_string(), voting_key.to_hex(), context.clone())
+                .await
+                .unwrap()
+                .voter_info,
+            inputs
+                .iter()
+                .cloned()
+                .map(|snapshot| VoterInfo {
+                    voting_group: snapshot.hir.voting_group,
+                    voting_power: snapshot.hir.voting_power,
+                    delegations_power: snapshot
+                        .contributions
+                        .iter()
+                        .map(|KeyContribution { value, .. }| value)
+                        .sum(),
+                    delegations_count: snapshot.contributions.len() as u64,
+                    voting_power_saturation: 1_f64,
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_diff() {
+        const TAG1: &str = "tag1";
+        const TAG2: &str = "tag2";
+
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+        ];
+        let entry = |key: &str, group: &str, power: u64, stake_keys: &[(&str, u64)]| SnapshotInfo {
+            contributions: stake_keys
+                .iter()
+                .map(|(stake_public_key, value)| KeyContribution {
+                    reward_address: format!("address_{}", stake_public_key),
+                    stake_public_key: stake_public_key.to_string(),
+                    value: *value,
+                })
+                .collect(),
+            hir: VoterHIR {
+                voting_key: Identifier::from_hex(key).unwrap(),
+                voting_group: group.to_string(),
+                voting_power: power.into(),
+            },
+        };
+
+        update_from_shanpshot_info(
+            TAG1.to_string(),
+            vec![
+                entry(keys[0], "group1", 1, &[("stake_1", 1)]),
+                entry(keys[0], "group2", 2, &[("stake_2", 2)]),
+                entry(keys[1], "group1", 2, &[("stake_3", 2)]),
+            ],
+            0,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+        update_from_shanpshot_info(
+            TAG2.to_string(),
+            vec![
+                entry(keys[0], "group1", 1, &[("stake_1", 1)]),
+                entry(keys[0], "group2", 3, &[("stake_2", 3)]),
+                entry(keys[2], "group1", 2, &[("stake_3", 2)]),
+            ],
+            1,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        let diff = get_snapshot_diff(
+            TAG1.to_string(),
+            TAG2.to_string(),
+            None,
+            None,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        let power = |group: &str, power: u64| VotingGroupPower {
+            voting_group: group.to_string(),
+            voting_power: Value::from(power),
+        };
+        assert_eq!(diff.voters.total, 3);
+        assert_eq!(
+            diff.voters.changes,
+            vec![
+                VoterChange {
+                    voting_key: keys[0].to_string(),
+                    kind: ChangeKind::Changed,
+                    from: vec![power("group1", 1), power("group2", 2)],
+                    to: vec![power("group1", 1), power("group2", 3)],
+                },
+                VoterChange {
+                    voting_key: keys[1].to_string(),
+                    kind: ChangeKind::Removed,
+                    from: vec![power("group1", 2)],
+                    to: vec![],
+                },
+                VoterChange {
+                    voting_key: keys[2].to_string(),
+                    kind: ChangeKind::Added,
+                    from: vec![],
+                    to: vec![power("group1", 2)],
+                },
+            ]
+        );
+
+        // stake_1 is untouched, stake_2 value changed and stake_3 moved to another voting key
+        assert_eq!(diff.contributions.total, 2);
+        let changed_stake_keys = diff
+            .contributions
+            .changes
+            .iter()
+            .map(|change| (change.stake_public_key.as_str(), change.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changed_stake_keys,
+            vec![
+                ("stake_2", ChangeKind::Changed),
+                ("stake_3", ChangeKind::Changed)
+            ]
+        );
+        assert_eq!(diff.contributions.changes[1].from[0].voting_key, keys[1]);
+        assert_eq!(diff.contributions.changes[1].to[0].voting_key, keys[2]);
+
+        assert_eq!(
+            diff.from.voting_groups,
+            vec![
+                VotingGroupSummary {
+                    voting_group: "group1".to_string(),
+                    total_voting_power: 3,
+                    voters_count: 2,
+                },
+                VotingGroupSummary {
+                    voting_group: "group2".to_string(),
+                    total_voting_power: 2,
+                    voters_count: 1,
+                },
+            ]
+        );
+        assert_eq!(diff.to.last_updated, 1);
+        assert_eq!(diff.to.voting_groups[1].total_voting_power, 3);
+
+        // pagination applies to both voters and contributions
+        let diff = get_snapshot_diff(
+            TAG1.to_string(),
+            TAG2.to_string(),
+            Some(1),
+            Some(1),
+            context.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(diff.voters.total, 3);
+        assert_eq!(diff.voters.changes.len(), 1);
+        assert_eq!(diff.voters.changes[0].voting_key, keys[1]);
+        assert_eq!(diff.contributions.changes.len(), 1);
+        assert_eq!(diff.contributions.changes[0].stake_public_key, "stake_3");
+
+        // both tags need to exist
+        let snapshot_root = warp::path!("snapshot" / ..).boxed();
+        let filter = filter(snapshot_root, context.clone());
+        assert_eq!(
+            warp::test::request()
+                .path("/snapshot/diff/tag1/tag3")
+                .reply(&filter)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+        let result = warp::test::request()
+            .path("/snapshot/diff/tag2/tag1?limit=2")
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let diff: SnapshotDiff = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(diff.from.tag, TAG2);
+        assert_eq!(diff.voters.changes.len(), 2);
+        assert_eq!(diff.voters.changes[1].kind, ChangeKind::Added);
+
+        // pages never exceed the maximum page size
+        let large_tag_entries = (0..=MAX_DIFF_PAGE_SIZE)
+            .map(|i| snapshot_entry(&format!("{:064x}", i + 0xff), &format!("large_stake_{}", i)))
+            .collect();
+        update_from_shanpshot_info("large".to_string(), large_tag_entries, 2, context.clone())
+            .await
+            .unwrap();
+        let added = MAX_DIFF_PAGE_SIZE + 1;
+        for limit in [None, Some(MAX_DIFF_PAGE_SIZE + 1), Some(u64::MAX)] {
+            let diff = get_snapshot_diff(
+                TAG1.to_string(),
+                "large".to_string(),
+                limit,
+                None,
+                context.clone(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(diff.voters.total, added + 2);
+            assert_eq!(
+                diff.voters.changes.len() as u64,
+                limit.map_or(DEFAULT_DIFF_PAGE_SIZE, |_| MAX_DIFF_PAGE_SIZE)
+            );
+            assert_eq!(diff.contributions.total, added + 3);
+            assert!(diff.contributions.changes.len() as u64 <= MAX_DIFF_PAGE_SIZE);
+        }
+    }
+
+    fn snapshot_entry(voting_key: &str, stake_public_key: &str) -> SnapshotInfo {
+        SnapshotInfo {
+            contributions: vec![KeyContribution {
+                reward_address: "address".to_string(),
+                stake_public_key: stake_public_key.to_string(),
+                value: 1,
+            }],
+            hir: VoterHIR {
+                voting_key: Identifier::from_hex(voting_key).unwrap(),
+                voting_group: "group".to_string(),
+                voting_power: 1.into(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_voters() {
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let entry =
+            |key: char, voting_group: &str, voting_power: u64, delegations: usize| SnapshotInfo {
+                contributions: (0..delegations)
+                    .map(|i| KeyContribution {
+                        reward_address: "address".to_string(),
+                        stake_public_key: format!("stake_public_key_{}_{}", key, i),
+                        value: 1,
+                    })
+                    .collect(),
+                hir: VoterHIR {
+                    voting_key: Identifier::from_hex(&key.to_string().repeat(64)).unwrap(),
+                    voting_group: voting_group.to_string(),
+                    voting_power: voting_power.into(),
+                },
+            };
+        update_from_shanpshot_info(
+            "tag".to_string(),
+            vec![
+                entry('0', "direct", 1, 1),
+                entry('1', "direct", 5, 1),
+                entry('2', "direct", 3, 1),
+                entry('3', "direct", 3, 1),
+                entry('1', "rep", 6, 3),
+            ],
+            0,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        let filter = filter(warp::path!("snapshot" / ..).boxed(), context.clone());
+        let get_voters = |query: &str| {
+            let filter = filter.clone();
+            let path = format!("/snapshot/tag/voters?{}", query);
+            async move {
+                let result = warp::test::request().path(&path).reply(&filter).await;
+                assert_eq!(result.status(), StatusCode::OK, "{:?}", result.body());
+                serde_json::from_slice::<VotersPage>(result.body()).unwrap()
+            }
+        };
+        let powers = |page: &VotersPage| {
+            page.voters
+                .iter()
+                .map(|voter| {
+                    (
+                        voter.voting_key.chars().next().unwrap(),
+                        u64::from(voter.info.voting_power),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let page = get_voters("").await;
+        assert_eq!(
+            powers(&page),
+            vec![('1', 6), ('1', 5), ('2', 3), ('3', 3), ('0', 1)]
+        );
+        assert!(page.next_cursor.is_none());
+        assert_eq!(page.voters[0].info.voting_group, "rep");
+        assert_eq!(page.voters[0].info.delegations_count, 3);
+        assert_eq!(page.voters[0].info.delegations_power, 3);
+        assert_eq!(page.voters[0].info.voting_power_saturation, 1_f64);
+        assert_eq!(page.voters[1].info.delegations_count, 1);
+        assert_eq!(page.voters[1].info.voting_power_saturation, 5_f64 / 12_f64);
+
+        // ties on the voting power do not skip or repeat voters across pages
+        let mut listed = Vec::new();
+        let mut query = "voting_group=direct&order=asc&limit=2".to_string();
+        loop {
+            let page = get_voters(&query).await;
+            assert!(page.voters.len() <= 2);
+            listed.extend(powers(&page));
+            match page.next_cursor {
+                Some(cursor) => {
+                    query = format!("voting_group=direct&order=asc&limit=2&cursor={}", cursor)
+                }
+                None => break,
+            }
+        }
+        assert_eq!(listed, vec![('0', 1), ('2', 3), ('3', 3), ('1', 5)]);
+
+        let page = get_voters("voting_group=direct&min_voting_power=3&limit=2").await;
+        assert_eq!(powers(&page), vec![('1', 5), ('2', 3)]);
+        let page = get_voters(&format!(
+            "voting_group=direct&min_voting_power=3&limit=2&cursor={}",
+            page.next_cursor.unwrap()
+        ))
+        .await;
+        assert_eq!(powers(&page), vec![('3', 3)]);
+        assert!(page.next_cursor.is_none());
+
+        for (path, status) in [
+            (
+                "/snapshot/tag/voters?cursor=invalid",
+                StatusCode::BAD_REQUEST,
+            ),
+            ("/snapshot/tag/voters?order=power", StatusCode::BAD_REQUEST),
+            ("/snapshot/missing/voters", StatusCode::NOT_FOUND),
+        ] {
+            assert_eq!(
+                warp::test::request()
+                    .path(path)
+                    .reply(&filter)
+                    .await
+                    .status(),
+                status
+            );
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_stats() {
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let entry = |key: char, voting_group: &str, voting_power: u64| {
+            let mut entry = snapshot_entry(&key.to_string().repeat(64), "stake_public_key");
+            entry.hir.voting_group = voting_group.to_string();
+            entry.hir.voting_power = voting_power.into();
+            entry
+        };
+        update_from_shanpshot_info(
+            "tag".to_string(),
+            vec![
+                entry('0', "direct", 4),
+                entry('1', "direct", 1),
+                entry('2', "direct", 3),
+                entry('3', "direct", 2),
+                entry('0', "rep", 5),
+            ],
+            0,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        let filter = filter(warp::path!("snapshot" / ..).boxed(), context.clone());
+        let get_stats = || async {
+            let result = warp::test::request()
+                .path("/snapshot/tag/stats")
+                .reply(&filter)
+                .await;
+            assert_eq!(result.status(), StatusCode::OK);
+            serde_json::from_slice::<SnapshotStats>(result.body()).unwrap()
+        };
+
+        let stats = get_stats().await;
+        assert_eq!(stats.voting_groups.len(), 2);
+        let direct = &stats.voting_groups[0];
+        assert_eq!(direct.voting_group, "direct");
+        assert_eq!(direct.voters_count, 4);
+        assert_eq!(direct.total_voting_power, 10);
+        // every voter is delegated to by the same stake key
+        assert_eq!(direct.delegators_count, 1);
+        assert_eq!(direct.median_voting_power, 2);
+        assert_eq!(
+            direct.percentiles,
+            VotingPowerPercentiles {
+                p10: 1,
+                p25: 1,
+                p50: 2,
+                p75: 3,
+                p90: 4,
+                p99: 4,
+            }
+        );
+        assert_eq!(direct.top_voters[0].voters, 10);
+        assert_eq!(direct.top_voters[0].voting_power, 10);
+        assert_eq!(direct.top_voters[0].share, 1_f64);
+        assert!((direct.gini_coefficient - 0.25).abs() < 1e-9);
+
+        let rep = &stats.voting_groups[1];
+        assert_eq!(rep.voting_group, "rep");
+        assert_eq!(rep.voters_count, 1);
+        assert_eq!(rep.median_voting_power, 5);
+        assert_eq!(rep.gini_coefficient, 0_f64);
+
+        // cached as long as the content of the tag stays the same
+        let version = ContentVersion::from(
+            &query_snapshot_by_tag("tag".to_string(), &context.read().await.db_connection_pool)
+                .await
+                .unwrap(),
+        );
+        assert_eq!(
+            context.read().await.snapshot_stats.get("tag", &version),
+            Some(stats)
+        );
+        update_from_shanpshot_info(
+            "tag".to_string(),
+            vec![entry('0', "direct", 7), entry('1', "direct", 7)],
+            1,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+        let stats = get_stats().await;
+        assert_eq!(stats.last_updated, 1);
+        assert_eq!(stats.voting_groups.len(), 1);
+        assert_eq!(stats.voting_groups[0].total_voting_power, 14);
+        assert_eq!(stats.voting_groups[0].gini_coefficient, 0_f64);
+
+        // deleted by another process without going through this server
+        delete_snapshot("tag", db_conn).unwrap();
+        assert_eq!(
+            warp::test::request()
+                .path("/snapshot/tag/stats")
+                .reply(&filter)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+
+        assert_eq!(
+            warp::test::request()
+                .path("/snapshot/missing/stats")
+                .reply(&filter)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_history() {
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+        ];
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        // imported out of order, the history follows the update time
+        for (tag, entries, update_timestamp) in [
+            (
+                "tag_c",
+                vec![snapshot_entry(keys[1], "stake_public_key")],
+                2,
+            ),
+            (
+                "tag_a",
+                vec![snapshot_entry(keys[0], "stake_public_key")],
+                0,
+            ),
+            (
+                "tag_b",
+                vec![
+                    snapshot_entry(keys[0], "stake_public_key"),
+                    snapshot_entry(keys[1], "other_stake_public_key"),
+                ],
+                1,
+            ),
+        ] {
+            update_from_shanpshot_info(tag.to_string(), entries, update_timestamp, context.clone())
+                .await
+                .unwrap();
+        }
+
+        let filter = filter(warp::path!("snapshot" / ..).boxed(), context.clone());
+
+        let result = warp::test::request()
+            .path(&format!("/snapshot/history/voter/{}", keys[0]))
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let history: Vec<VoterHistoryEntry> = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|entry| (entry.tag.as_str(), entry.info.last_updated))
+                .collect::<Vec<_>>(),
+            vec![("tag_a", 0), ("tag_b", 1), ("tag_c", 2)]
+        );
+        assert_eq!(history[0].info.voter_info[0].voting_power_saturation, 1_f64);
+        assert_eq!(history[1].info.voter_info[0].voting_power_saturation, 0.5);
+        assert_eq!(history[1].info.voter_info[0].delegations_count, 1);
+        assert!(history[2].info.voter_info.is_empty());
+
+        let result = warp::test::request()
+            .path("/snapshot/history/delegator/stake_public_key")
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let history: Vec<DelegatorHistoryEntry> = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|entry| (entry.tag.as_str(), entry.info.dreps.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("tag_a", vec![keys[0].to_string()]),
+                ("tag_b", vec![keys[0].to_string()]),
+                ("tag_c", vec![keys[1].to_string()]),
+            ]
+        );
+        assert_eq!(history[2].info.voting_groups, vec!["group".to_string()]);
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_batch_lookup() {
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+        ];
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+        context.write().await.snapshot_max_batch_size = 3;
+
+        update_from_shanpshot_info(
+            "tag".to_string(),
+            vec![
+                snapshot_entry(keys[0], "stake_public_key"),
+                snapshot_entry(keys[1], "stake_public_key"),
+            ],
+            0,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        let filter = filter(warp::path!("snapshot" / ..).boxed(), context.clone());
+
+        let result = warp::test::request()
+            .method("POST")
+            .path("/snapshot/tag/voters")
+            .json(&vec![keys[0], keys[2], keys[0]])
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let voters: BTreeMap<String, VotersInfo> = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(voters.len(), 2);
+        assert_eq!(voters[keys[0]].voter_info[0].voting_power_saturation, 0.5);
+        assert_eq!(voters[keys[0]].voter_info[0].delegations_count, 1);
+        assert!(voters[keys[2]].voter_info.is_empty());
+
+        let result = warp::test::request()
+            .method("POST")
+            .path("/snapshot/tag/delegators")
+            .json(&vec!["stake_public_key", "other_stake_public_key"])
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let delegators: BTreeMap<String, DelegatorInfo> =
+            serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(
+            delegators["stake_public_key"].dreps,
+            vec![keys[0].to_string(), keys[1].to_string()]
+        );
+        assert!(delegators["other_stake_public_key"].dreps.is_empty());
+
+        let result = warp::test::request()
+            .method("POST")
+            .path("/snapshot/tag/voters")
+            .json(&vec![keys[0], keys[1], keys[2], "other"])
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::BAD_REQUEST);
+
+        // batches above the limit of bind parameters of sqlite are looked up in several queries
+        context.write().await.snapshot_max_batch_size = 2000;
+        let mut many_keys: Vec<String> = (0..1500).map(|i| format!("{:064x}", i)).collect();
+        many_keys.push(keys[1].to_string());
+        let result = warp::test::request()
+            .method("POST")
+            .path("/snapshot/tag/voters")
+            .json(&many_keys)
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let voters: BTreeMap<String, VotersInfo> = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(voters.len(), 1501);
+        assert_eq!(voters[keys[0]].voter_info.len(), 1);
+        assert_eq!(voters[keys[1]].voter_info.len(), 1);
+        let mut many_stake_keys: Vec<String> = (0..1500).map(|i| format!("stake_{}", i)).collect();
+        many_stake_keys.push("stake_public_key".to_string());
+        let result = warp::test::request()
+            .method("POST")
+            .path("/snapshot/tag/delegators")
+            .json(&many_stake_keys)
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let delegators: BTreeMap<String, DelegatorInfo> =
+            serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(delegators["stake_public_key"].dreps.len(), 2);
+
+        let result = warp::test::request()
+            .method("POST")
+            .path("/snapshot/missing_tag/delegators")
+            .json(&vec!["stake_public_key"])
+            .reply(&filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_current_fund_voter() {
+        use crate::db::models::funds::test::{get_test_fund, populate_db_with_fund};
+
+        let key = "0000000000000000000000000000000000000000000000000000000000000000";
+        let context = new_in_memmory_db_test_shared_context();
+        let pool = context.read().await.db_connection_pool.clone();
+        initialize_db_with_migration(&pool.get().unwrap());
+
+        let fund = get_test_fund(Some(1));
+        let fund_group = fund.groups.iter().next().unwrap().group_id.clone();
+        let mut fund_entry = snapshot_entry(key, "stake_public_key");
+        fund_entry.hir.voting_group = fund_group.clone();
+        update_from_shanpshot_info(
+            fund.snapshot_tag.clone().unwrap(),
+            vec![fund_entry, snapshot_entry(key, "other_stake_public_key")],
+            0,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+
+        let filter = filter(warp::path!("snapshot" / ..).boxed(), context.clone());
+        let request = || {
+            warp::test::request()
+                .method("GET")
+                .path(&format!("/snapshot/voter/current/{}", key))
+        };
+
+        // no current fund yet
+        let result = request().reply(&filter).await;
+        assert_eq!(result.status(), StatusCode::NOT_FOUND);
+
+        populate_db_with_fund(&fund, &pool);
+        let result = request().reply(&filter).await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let voters: VotersInfo = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(voters.voter_info.len(), 1);
+        assert_eq!(voters.voter_info[0].voting_group, fund_group);
+        assert_eq!(voters.voter_info[0].delegations_count, 1);
+
+        // the tag can still be looked up directly, with every voting group
+        let voters = get_voters_info(fund.snapshot_tag.unwrap(), key.to_string(), context)
+            .await
+            .unwrap();
+        assert_eq!(voters.voter_info.len(), 2);
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_export() {
+        use sha2::{Digest, Sha256};
+
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+        ];
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let mut without_contributions = snapshot_entry(keys[2], "stake_public_key_2");
+        without_contributions.contributions.clear();
+        let entries = vec![
+            snapshot_entry(keys[1], "stake_public_key_1"),
+            without_contributions,
+            snapshot_entry(keys[0], "stake_public_key_0"),
+        ];
+        update_from_shanpshot_info("tag".to_string(), entries.clone(), 0, context.clone())
+            .await
+            .unwrap();
+
+        let filter = filter(warp::path!("snapshot" / ..).boxed(), context.clone());
+        let export = |format: &str| {
+            let filter = filter.clone();
+            let path = format!("/snapshot/tag/export?format={}", format);
+            async move {
+                let result = warp::test::request().path(&path).reply(&filter).await;
+                assert_eq!(result.status(), StatusCode::OK);
+                let content_hash = result.headers()[CONTENT_HASH_HEADER]
+                    .to_str()
+                    .unwrap()
+                    .to_string();
+                (content_hash, result.body().to_vec())
+            }
+        };
+
+        let (content_hash, body) = export("json").await;
+        assert_eq!(content_hash, format!("{:x}", Sha256::digest(&body)));
+        let exported: Vec<SnapshotInfo> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            exported,
+            vec![entries[2].clone(), entries[0].clone(), entries[1].clone()]
+        );
+        let pool = &context.read().await.db_connection_pool;
+        assert_eq!(
+            query_snapshot_by_tag("tag".to_string(), pool)
+                .await
+                .unwrap()
+                .content_hash,
+            Some(content_hash.clone())
+        );
+
+        // the export only takes a connection while reading a page, not while the client is
+        // yet to read the body
+        let in_use = |pool: &crate::db::DbConnectionPool| {
+            let state = pool.state();
+            state.connections - state.idle_connections
+        };
+        let connections = in_use(pool);
+        let response =
+            super::export::export_snapshot("tag".to_string(), ExportFormat::Json, context.clone())
+                .await
+                .unwrap();
+        tokio::task::yield_now().await;
+        assert_eq!(in_use(pool), connections);
+        drop(response);
+
+        let (csv_hash, body) = export("csv").await;
+        assert_eq!(csv_hash, content_hash);
+        let lines: Vec<String> = String::from_utf8(body)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "voting_key,voting_group,voting_power,stake_public_key,reward_address,value"
+                    .to_string(),
+                format!("{},group,1,stake_public_key_0,address,1", keys[0]),
+                format!("{},group,1,stake_public_key_1,address,1", keys[1]),
+                format!("{},group,1,,,", keys[2]),
+            ]
+        );
+
+        // the hash only depends on the content
+        update_from_shanpshot_info(
+            "tag".to_string(),
+            entries.into_iter().rev(),
+            1,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(export("json").await.0, content_hash);
+
+        assert_eq!(
+            warp::test::request()
+                .path("/snapshot/missing/export")
+                .reply(&filter)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_delete() {
+        let key = "0000000000000000000000000000000000000000000000000000000000000000";
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        for tag in ["tag_a", "tag_b"] {
+            update_from_shanpshot_info(
+                tag.to_string(),
+                vec![snapshot_entry(key, "stake_public_key")],
+                0,
+                context.clone(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let snapshot_root = warp::path!("snapshot" / ..).boxed();
+        let filter = filter(snapshot_root.clone(), context.clone());
+        let delete_filter = snapshot_root.and(update_filter(context.clone()));
+
+        assert_eq!(
+            warp::test::request()
+                .path("/snapshot/tag_a")
+                .method("DELETE")
+                .reply(&delete_filter)
+                .await
+                .status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            warp::test::request()
+                .path("/snapshot/tag_a")
+                .method("DELETE")
+                .reply(&delete_filter)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+
+        assert!(get_voters_info("tag_a", key, &filter).await.is_err());
+        assert_eq!(
+            get_voters_info("tag_b", key, &filter).await.unwrap(),
+            vec![(1u64, 1u64, 1u64, "group".to_string())]
+        );
+        assert_eq!(get_tags(context.clone()).await.unwrap(), vec!["tag_b"]);
+
+        // entries of the deleted tag are gone as well
+        let pool = &context.read().await.db_connection_pool;
+        let contributions = query_contributions_by_stake_public_keys_and_snapshot_tags(
+            vec!["stake_public_key".to_string()],
+            vec!["tag_a".to_string(), "tag_b".to_string()],
+            pool,
+        )
+        .await
+        .unwrap();
+        assert_eq!(contributions.len(), 1);
+        assert_eq!(contributions[0].snapshot_tag, "tag_b");
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_replacement_drops_stale_entries() {
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+        ];
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        update_from_shanpshot_info(
+            "tag".to_string(),
+            vec![
+                snapshot_entry(keys[0], "stake_public_key_0"),
+                snapshot_entry(keys[1], "stake_public_key_1"),
+            ],
+            0,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+        update_from_shanpshot_info(
+            "tag".to_string(),
+            vec![snapshot_entry(keys[0], "stake_public_key_0")],
+            1,
             context.clone(),
         )
         .await
         .unwrap();
 
-        assert_eq!(
-            super::get_voters_info(TAG1.to_string(), voting_key.to_hex(), context.clone())
+        let voters_info =
+            super::get_voters_info("tag".to_string(), keys[0].to_string(), context.clone())
                 .await
-                .unwrap()
-                .voter_info,
-            inputs
-                .iter()
-                .cloned()
-                .map(|snapshot| VoterInfo {
-                    voting_group: snapshot.hir.voting_group,
-                    voting_power: snapshot.hir.voting_power,
-                    delegations_power: snapshot
-                        .contributions
-                        .iter()
-                        .map(|KeyContribution { value, .. }| value)
-                        .sum(),
-                    delegations_count: snapshot.contributions.len() as u64,
-                    voting_power_saturation: 1_f64,
-                })
-                .collect::<Vec<_>>()
+                .unwrap();
+        assert_eq!(voters_info.last_updated, 1);
+        assert_eq!(voters_info.voter_info.len(), 1);
+        assert_eq!(voters_info.voter_info[0].voting_power_saturation, 1_f64);
+
+        let voters_info =
+            super::get_voters_info("tag".to_string(), keys[1].to_string(), context.clone())
+                .await
+                .unwrap();
+        assert!(voters_info.voter_info.is_empty());
+
+        let delegator_info = get_delegator_info(
+            "tag".to_string(),
+            "stake_public_key_1".to_string(),
+            context.clone(),
+        )
+        .await
+        .unwrap();
+        assert!(delegator_info.dreps.is_empty());
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_retention() {
+        use crate::db::models::funds::{
+            test::{get_test_fund, populate_db_with_fund},
+            Fund,
+        };
+
+        let key = "0000000000000000000000000000000000000000000000000000000000000000";
+        let context = new_in_memmory_db_test_shared_context();
+        let pool = context.read().await.db_connection_pool.clone();
+        initialize_db_with_migration(&pool.get().unwrap());
+        context.write().await.snapshot_retention = SnapshotRetention {
+            keep_last: Some(2),
+            max_age_secs: None,
+        };
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        for (tag, last_updated) in [("tag_a", now - 2), ("tag_b", now - 1), ("tag_c", now)] {
+            update_from_shanpshot_info(
+                tag.to_string(),
+                vec![snapshot_entry(key, "stake_public_key")],
+                last_updated,
+                context.clone(),
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            get_tags(context.clone()).await.unwrap(),
+            vec!["tag_b", "tag_c"]
         );
 
-        super::update_from_shanpshot_info(
-            TAG1.to_string(),
-            inputs[0..1].to_vec(),
-            UPDATE_TIME1,
+        // the imported tag is kept even if it is the oldest one
+        update_from_shanpshot_info(
+            "tag_d".to_string(),
+            vec![snapshot_entry(key, "stake_public_key")],
+            now - 10,
             context.clone(),
         )
         .await
         .unwrap();
+        assert_eq!(
+            get_tags(context.clone()).await.unwrap(),
+            vec!["tag_d", "tag_c"]
+        );
 
+        // tags used by a fund are never deleted
+        populate_db_with_fund(
+            &Fund {
+                snapshot_tag: Some("tag_c".to_string()),
+                ..get_test_fund(Some(1))
+            },
+            &pool,
+        );
+        for (tag, last_updated) in [("tag_e", now + 1), ("tag_f", now + 2)] {
+            update_from_shanpshot_info(
+                tag.to_string(),
+                vec![snapshot_entry(key, "stake_public_key")],
+                last_updated,
+                context.clone(),
+            )
+            .await
+            .unwrap();
+        }
         assert_eq!(
-            super::get_voters_info(TAG1.to_string(), voting_key.to_hex(), context.clone())
-                .await
-                .unwrap()
-                .voter_info,
-            inputs[0..1]
-                .iter()
-                .cloned()
-                .map(|snapshot| VoterInfo {
-                    voting_group: snapshot.hir.voting_group,
-                    voting_power: snapshot.hir.voting_power,
-                    delegations_power: snapshot
-                        .contributions
-                        .iter()
-                        .map(|KeyContribution { value, .. }| value)
-                        .sum(),
-                    delegations_count: snapshot.contributions.len() as u64,
-                    voting_power_saturation: 1_f64,
+            get_tags(context.clone()).await.unwrap(),
+            vec!["tag_c", "tag_e", "tag_f"]
+        );
+    }
+
+    #[test]
+    fn snapshot_retention_expired_tags() {
+        let snapshots = || {
+            [("a", 10), ("b", 20), ("c", 30), ("d", 40)]
+                .into_iter()
+                .map(|(tag, last_updated)| models::snapshot::Snapshot {
+                    tag: tag.to_string(),
+                    last_updated,
+                    content_hash: None,
                 })
                 .collect::<Vec<_>>()
+        };
+        let retention = |keep_last, max_age_secs| SnapshotRetention {
+            keep_last,
+            max_age_secs,
+        };
+
+        assert!(expired_tags(snapshots(), &retention(None, None), "d", &[], 40).is_empty());
+        assert_eq!(
+            expired_tags(snapshots(), &retention(Some(2), None), "d", &[], 40),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            expired_tags(snapshots(), &retention(Some(2), None), "a", &[], 40),
+            vec!["b", "c"]
+        );
+        assert_eq!(
+            expired_tags(snapshots(), &retention(None, Some(15)), "d", &[], 40),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            expired_tags(snapshots(), &retention(Some(3), Some(25)), "d", &[], 40),
+            vec!["a"]
+        );
+        assert_eq!(
+            expired_tags(snapshots(), &retention(Some(1), Some(25)), "d", &[], 40),
+            vec!["a", "b", "c"]
+        );
+        // tags used by a fund are kept and leave room for the other ones
+        assert!(expired_tags(
+            snapshots(),
+            &retention(Some(2), None),
+            "d",
+            &["a".to_string(), "c".to_string()],
+            40
+        )
+        .is_empty());
+        assert_eq!(
+            expired_tags(
+                snapshots(),
+                &retention(Some(1), Some(25)),
+                "d",
+                &["b".to_string()],
+                40
+            ),
+            vec!["a", "c"]
         );
+    }
 
-        // asserting that TAG2 is untouched, just in case
+    async fn upload_and_wait<F>(path: &str, body: Vec<u8>, filter: &F) -> SnapshotJob
+    where
+        F: Filter + 'static,
+        F::Extract: Reply + Send,
+    {
+        let result = warp::test::request()
+            .method("PUT")
+            .path(path)
+            .body(body)
+            .reply(filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let mut job: SnapshotJob = serde_json::from_slice(result.body()).unwrap();
+        while job.is_running() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            let result = warp::test::request()
+                .path(&format!("/snapshot/jobs/{}", job.id))
+                .reply(filter)
+                .await;
+            assert_eq!(result.status(), StatusCode::OK);
+            job = serde_json::from_slice(result.body()).unwrap();
+        }
+        job
+    }
+
+    #[tokio::test]
+    pub async fn test_snapshot_stream_upload() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+        ];
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let snapshot_root = warp::path!("snapshot" / ..).boxed();
+        let filter = filter(snapshot_root.clone(), context.clone());
+        let put_filter = snapshot_root.and(update_filter(context));
+
+        // gzip compressed snapshot information
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let header = SnapshotInfoStreamHeader {
+            update_timestamp: 1,
+        };
+        writeln!(encoder, "{}", serde_json::to_string(&header).unwrap()).unwrap();
+        for (key, stake_public_key) in keys.iter().zip(["stake_key_0", "stake_key_1"]) {
+            let entry = snapshot_entry(key, stake_public_key);
+            writeln!(encoder, "{}", serde_json::to_string(&entry).unwrap()).unwrap();
+        }
+        let job = upload_and_wait(
+            "/snapshot/snapshot_info_stream/tag_a",
+            encoder.finish().unwrap(),
+            &put_filter,
+        )
+        .await;
+        assert_eq!(job.state, JobState::Finished);
+        assert_eq!(job.tag, "tag_a");
+        assert_eq!(job.entries_read, 2);
+        assert_eq!(job.voters_processed, 2);
+        assert_eq!(job.contributions_processed, 2);
+        for key in keys {
+            assert_eq!(
+                get_voters_info("tag_a", key, &filter).await.unwrap(),
+                vec![(1u64, 1u64, 1u64, "group".to_string())]
+            );
+        }
+
+        // plain raw snapshot, with registrations split by empty lines
+        let params = RawSnapshotParams {
+            update_timestamp: 2,
+            min_stake_threshold: 0.into(),
+            voting_power_cap: 100.into(),
+            direct_voters_group: None,
+            representatives_group: None,
+        };
+        let registrations = [
+            (
+                "0xa6a3c0447aeb9cc54cf6422ba32b294e5e1c3ef6d782f2acff4a70694c4d1663",
+                2u64,
+            ),
+            (
+                "0x00588e8e1d18cba576a4d35758069fe94e53f638b6faf7c07b8abd2bc5c5cdee",
+                1u64,
+            ),
+        ]
+        .iter()
+        .map(|(stake_public_key, voting_power)| {
+            serde_json::to_string(&VotingRegistration {
+                stake_public_key: stake_public_key.to_string(),
+                voting_power: (*voting_power).into(),
+                reward_address: stake_public_key.to_string(),
+                delegations: Delegations::Legacy(Identifier::from_hex(keys[0]).unwrap()),
+                voting_purpose: 0,
+            })
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+        let body = format!(
+            "{}\n{}\n\n{}\n",
+            serde_json::to_string(&params).unwrap(),
+            registrations[0],
+            registrations[1]
+        );
+        let job = upload_and_wait(
+            "/snapshot/raw_snapshot_stream/tag_b",
+            body.into_bytes(),
+            &put_filter,
+        )
+        .await;
+        assert_eq!(job.state, JobState::Finished);
+        assert_eq!(job.entries_read, 2);
         assert_eq!(
-            super::get_voters_info(TAG2.to_string(), voting_key.to_hex(), context.clone())
+            get_voters_info("tag_b", keys[0], &filter).await.unwrap(),
+            vec![(3u64, 2u64, 3u64, "direct".to_string())]
+        );
+
+        // malformed entries fail the job without touching the previous content
+        let body = format!(
+            "{}\n{}\nnot a registration\n",
+            serde_json::to_string(&params).unwrap(),
+            registrations[0]
+        );
+        let job = upload_and_wait(
+            "/snapshot/raw_snapshot_stream/tag_b",
+            body.into_bytes(),
+            &put_filter,
+        )
+        .await;
+        match job.state {
+            JobState::Failed { error } => assert!(error.contains("line 3"), "{}", error),
+            state => panic!("unexpected job state {:?}", state),
+        }
+        assert_eq!(
+            get_voters_info("tag_b", keys[0], &filter).await.unwrap(),
+            vec![(3u64, 2u64, 3u64, "direct".to_string())]
+        );
+
+        assert_eq!(
+            warp::test::request()
+                .path("/snapshot/jobs/100")
+                .reply(&put_filter)
                 .await
-                .unwrap()
-                .voter_info,
-            inputs
-                .iter()
-                .cloned()
-                .map(|snapshot| VoterInfo {
-                    voting_group: snapshot.hir.voting_group,
-                    voting_power: snapshot.hir.voting_power,
-                    delegations_power: snapshot
-                        .contributions
-                        .iter()
-                        .map(|KeyContribution { value, .. }| value)
-                        .sum(),
-                    delegations_count: snapshot.contributions.len() as u64,
-                    voting_power_saturation: 1_f64,
-                })
-                .collect::<Vec<_>>()
+                .status(),
+            StatusCode::NOT_FOUND
         );
     }
 
@@ -938,4 +2886,217 @@ mod test {
 
         assert_eq!(tags, vec!["tag_a", "tag_b"]);
     }
+
+    #[tokio::test]
+    async fn test_snapshot_preview() {
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+            "3333333333333333333333333333333333333333333333333333333333333333",
+        ];
+        let registration =
+            |stake_public_key: &str, voting_power: u64, key: &str| VotingRegistration {
+                stake_public_key: stake_public_key.to_string(),
+                voting_power: voting_power.into(),
+                reward_address: stake_public_key.to_string(),
+                delegations: Delegations::Legacy(Identifier::from_hex(key).unwrap()),
+                voting_purpose: 0,
+            };
+        let input = RawSnapshotInput {
+            snapshot: vec![
+                registration("stake_a", 10, keys[0]),
+                registration("stake_b", 3, keys[1]),
+                registration("stake_c", 3, keys[2]),
+                registration("stake_d", 1, keys[3]),
+            ],
+            update_timestamp: 0,
+            min_stake_threshold: 2.into(),
+            voting_power_cap: Fraction::from(1) / Fraction::from(2),
+            direct_voters_group: None,
+            representatives_group: None,
+        };
+
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let snapshot_root = warp::path!("snapshot" / ..).boxed();
+        let filter = filter(snapshot_root.clone(), context.clone());
+        let put_filter = snapshot_root.and(update_filter(context));
+
+        let result = warp::test::request()
+            .path("/snapshot/preview")
+            .method("POST")
+            .json(&input)
+            .reply(&put_filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let preview: SnapshotPreview = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(preview.registrations, 4);
+        assert_eq!(preview.filtered_registrations, 1);
+        assert_eq!(preview.filtered_voters, 1);
+        assert_eq!(preview.capped_voters, 1);
+        assert_eq!(preview.voting_groups.len(), 1);
+        assert_eq!(preview.voting_groups[0].voting_group, "direct");
+        assert_eq!(preview.voting_groups[0].voters_count, 3);
+        assert_eq!(
+            preview
+                .largest_voters
+                .iter()
+                .map(|voter| voter.voting_key.as_str())
+                .collect::<Vec<_>>(),
+            vec![keys[0], keys[1], keys[2]]
+        );
+        assert_eq!(preview.largest_voters[0].uncapped_voting_power, 10);
+        assert!(preview.largest_voters[0].voting_power < 10);
+        assert_eq!(
+            preview.voting_groups[0].total_voting_power,
+            preview
+                .largest_voters
+                .iter()
+                .map(|voter| voter.voting_power)
+                .sum::<u64>()
+        );
+
+        // nothing is written
+        let result = warp::test::request().path("/snapshot").reply(&filter).await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let tags: Vec<String> = serde_json::from_slice(result.body()).unwrap();
+        assert!(tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_reprocess() {
+        let keys = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+        ];
+        let registration =
+            |stake_public_key: &str, voting_power: u64, key: &str| VotingRegistration {
+                stake_public_key: stake_public_key.to_string(),
+                voting_power: voting_power.into(),
+                reward_address: stake_public_key.to_string(),
+                delegations: Delegations::Legacy(Identifier::from_hex(key).unwrap()),
+                voting_purpose: 0,
+            };
+        let input = RawSnapshotInput {
+            snapshot: vec![
+                registration("stake_a", 10, keys[0]),
+                registration("stake_b", 3, keys[1]),
+            ],
+            update_timestamp: 0,
+            min_stake_threshold: 0.into(),
+            voting_power_cap: 100.into(),
+            direct_voters_group: None,
+            representatives_group: None,
+        };
+
+        let context = new_in_memmory_db_test_shared_context();
+        let db_conn = &context.read().await.db_connection_pool.get().unwrap();
+        initialize_db_with_migration(db_conn);
+
+        let snapshot_root = warp::path!("snapshot" / ..).boxed();
+        let filter = filter(snapshot_root.clone(), context.clone());
+        let put_filter = snapshot_root.and(update_filter(context.clone()));
+
+        let result = warp::test::request()
+            .path("/snapshot/raw_snapshot/tag_a")
+            .method("PUT")
+            .json(&input)
+            .reply(&put_filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+
+        let result = warp::test::request()
+            .path("/snapshot/raw_snapshot")
+            .reply(&put_filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let raw_snapshots: Vec<StoredRawSnapshot> = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(raw_snapshots.len(), 1);
+        assert_eq!(raw_snapshots[0].tag, "tag_a");
+        assert_eq!(raw_snapshots[0].registrations_count, 2);
+
+        let result = warp::test::request()
+            .path("/snapshot/raw_snapshot/tag_a")
+            .reply(&put_filter)
+            .await;
+        assert_eq!(result.status(), StatusCode::OK);
+        let stored: RawSnapshotInput = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&stored).unwrap(),
+            serde_json::to_value(&input).unwrap()
+        );
+
+        let reprocess = |tag: &str, new_tag: &str| {
+            let params = RawSnapshotParams {
+                update_timestamp: 1,
+                min_stake_threshold: 5.into(),
+                voting_power_cap: 100.into(),
+                direct_voters_group: Some("reprocessed".to_string()),
+                representatives_group: None,
+            };
+            warp::test::request()
+                .path(&format!("/snapshot/{}/reprocess", tag))
+                .method("POST")
+                .json(&RawSnapshotReprocessInput {
+                    new_tag: new_tag.to_string(),
+                    params,
+                })
+        };
+
+        let result = reprocess("tag_a", "tag_b").reply(&put_filter).await;
+        assert_eq!(result.status(), StatusCode::OK);
+        assert_eq!(
+            get_voters_info("tag_b", keys[0], &filter).await.unwrap(),
+            vec![(10u64, 1u64, 10u64, "reprocessed".to_string())]
+        );
+        assert!(get_voters_info("tag_b", keys[1], &filter)
+            .await
+            .unwrap()
+            .is_empty());
+        // the original tag is left untouched
+        assert_eq!(
+            get_voters_info("tag_a", keys[1], &filter).await.unwrap(),
+            vec![(3u64, 1u64, 3u64, "direct".to_string())]
+        );
+
+        assert_eq!(
+            reprocess("tag_a", "tag_a")
+                .reply(&put_filter)
+                .await
+                .status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            reprocess("tag_c", "tag_d")
+                .reply(&put_filter)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+
+        // importing processed entries drops the stored raw snapshot
+        update_from_shanpshot_info(
+            "tag_a".to_string(),
+            vec![snapshot_entry(keys[0], "stake_a")],
+            2,
+            context.clone(),
+        )
+        .await
+        .unwrap();
+        let result = warp::test::request()
+            .path("/snapshot/raw_snapshot")
+            .reply(&put_filter)
+            .await;
+        let raw_snapshots: Vec<StoredRawSnapshot> = serde_json::from_slice(result.body()).unwrap();
+        assert_eq!(
+            raw_snapshots
+                .iter()
+                .map(|raw_snapshot| raw_snapshot.tag.as_str())
+                .collect::<Vec<_>>(),
+            vec!["tag_b"]
+        );
+    }
 }
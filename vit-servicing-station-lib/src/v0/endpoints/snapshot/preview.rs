@@ -0,0 +1,161 @@
+//! Dry run of the raw snapshot processing. Registrations are processed with the requested
+//! parameters and summarized without writing anything, so that the minimum stake threshold and
+//! the voting power cap can be evaluated before the snapshot is imported.
+
+use super::{process_raw_snapshot, Group, RawSnapshotInput};
+use crate::v0::errors::HandleError;
+use serde::{Deserialize, Serialize};
+use snapshot_lib::registration::{Delegations, VotingRegistration};
+use snapshot_lib::{RawSnapshot, SnapshotInfo};
+use std::collections::{BTreeMap, HashSet};
+
+/// Number of voters with the most voting power listed in a preview
+const PREVIEW_LARGEST_VOTERS: usize = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewVotingGroup {
+    pub voting_group: Group,
+    pub voters_count: u64,
+    pub total_voting_power: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewVoter {
+    pub voting_key: String,
+    pub voting_group: Group,
+    pub voting_power: u64,
+    /// Voting power before applying the voting power cap
+    pub uncapped_voting_power: u64,
+    pub delegations_count: u64,
+}
+
+/// Outcome of processing a raw snapshot with a given set of parameters
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPreview {
+    pub registrations: u64,
+    /// Registrations dropped by the minimum stake threshold
+    pub filtered_registrations: u64,
+    /// Voting keys left without voting power once the minimum stake threshold is applied
+    pub filtered_voters: u64,
+    /// Voting keys whose voting power is reduced by the voting power cap
+    pub capped_voters: u64,
+    pub voting_groups: Vec<PreviewVotingGroup>,
+    pub largest_voters: Vec<PreviewVoter>,
+}
+
+fn delegated_voting_keys(registration: &VotingRegistration) -> Vec<String> {
+    match &registration.delegations {
+        Delegations::Legacy(voting_key) => vec![voting_key.to_hex()],
+        Delegations::New(delegations) => delegations
+            .iter()
+            .map(|(voting_key, _)| voting_key.to_hex())
+            .collect(),
+    }
+}
+
+fn preview_voter(entry: &SnapshotInfo) -> PreviewVoter {
+    PreviewVoter {
+        voting_key: entry.hir.voting_key.to_hex(),
+        voting_group: entry.hir.voting_group.clone(),
+        voting_power: entry.hir.voting_power.into(),
+        uncapped_voting_power: entry
+            .contributions
+            .iter()
+            .map(|contribution| contribution.value)
+            .sum(),
+        delegations_count: entry.contributions.len() as u64,
+    }
+}
+
+/// Keys of the registrations, kept aside as processing consumes the registrations
+struct RegisteredKeys {
+    stake_public_keys: Vec<String>,
+    voting_keys: HashSet<String>,
+}
+
+impl RegisteredKeys {
+    fn new(registrations: &[VotingRegistration]) -> Self {
+        Self {
+            stake_public_keys: registrations
+                .iter()
+                .map(|registration| registration.stake_public_key.clone())
+                .collect(),
+            voting_keys: registrations
+                .iter()
+                .flat_map(delegated_voting_keys)
+                .collect(),
+        }
+    }
+}
+
+fn snapshot_preview(registered: RegisteredKeys, entries: &[SnapshotInfo]) -> SnapshotPreview {
+    let contributing_stake_keys: HashSet<&str> = entries
+        .iter()
+        .flat_map(|entry| &entry.contributions)
+        .map(|contribution| contribution.stake_public_key.as_str())
+        .collect();
+    let voting_keys: HashSet<String> = entries
+        .iter()
+        .map(|entry| entry.hir.voting_key.to_hex())
+        .collect();
+
+    let mut voters: Vec<PreviewVoter> = entries.iter().map(preview_voter).collect();
+
+    let mut voting_groups: BTreeMap<&str, PreviewVotingGroup> = BTreeMap::new();
+    for voter in &voters {
+        let group =
+            voting_groups
+                .entry(&voter.voting_group)
+                .or_insert_with(|| PreviewVotingGroup {
+                    voting_group: voter.voting_group.clone(),
+                    voters_count: 0,
+                    total_voting_power: 0,
+                });
+        group.voters_count += 1;
+        group.total_voting_power += voter.voting_power;
+    }
+    let voting_groups = voting_groups.into_values().collect();
+
+    let capped_voters = voters
+        .iter()
+        .filter(|voter| voter.voting_power < voter.uncapped_voting_power)
+        .count() as u64;
+
+    voters.sort_by(|a, b| {
+        b.voting_power
+            .cmp(&a.voting_power)
+            .then_with(|| a.voting_key.cmp(&b.voting_key))
+            .then_with(|| a.voting_group.cmp(&b.voting_group))
+    });
+    voters.truncate(PREVIEW_LARGEST_VOTERS);
+
+    SnapshotPreview {
+        registrations: registered.stake_public_keys.len() as u64,
+        filtered_registrations: registered
+            .stake_public_keys
+            .iter()
+            .filter(|stake_public_key| !contributing_stake_keys.contains(stake_public_key.as_str()))
+            .count() as u64,
+        filtered_voters: registered.voting_keys.difference(&voting_keys).count() as u64,
+        capped_voters,
+        voting_groups,
+        largest_voters: voters,
+    }
+}
+
+#[tracing::instrument(skip(input))]
+pub async fn preview_raw_snapshot(input: RawSnapshotInput) -> Result<SnapshotPreview, HandleError> {
+    tokio::task::spawn_blocking(move || {
+        let registered = RegisteredKeys::new(&input.snapshot);
+        let entries = process_raw_snapshot(
+            RawSnapshot::from(input.snapshot),
+            input.min_stake_threshold,
+            input.voting_power_cap,
+            input.direct_voters_group,
+            input.representatives_group,
+        )?;
+        Ok(snapshot_preview(registered, &entries))
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))?
+}
@@ -0,0 +1,168 @@
+//! Raw snapshots kept along with the snapshot tags imported from them. Registrations and
+//! processing parameters are stored per tag, so a tag can be processed again with different
+//! parameters without fetching the registrations again.
+
+use super::{to_i64, update_from_raw_snapshot, RawSnapshotInput, RawSnapshotParams, Tag};
+use crate::{
+    db::{
+        models::snapshot::{RawSnapshot, RawSnapshotRegistration},
+        queries::snapshot::{
+            query_all_snapshots, query_raw_snapshot_by_tag,
+            query_raw_snapshot_registrations_by_tag, query_raw_snapshots, query_snapshot_by_tag,
+            RawSnapshotContent,
+        },
+    },
+    v0::{context::SharedContext, errors::HandleError},
+};
+use jormungandr_lib::interfaces::Value;
+use serde::{Deserialize, Serialize};
+use snapshot_lib::registration::VotingRegistration;
+use std::collections::HashMap;
+
+/// Processing parameters of a snapshot tag imported from a raw snapshot
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredRawSnapshot {
+    pub tag: Tag,
+    #[serde(flatten)]
+    pub params: RawSnapshotParams,
+    pub registrations_count: u64,
+}
+
+fn serialization_error(e: serde_json::Error) -> HandleError {
+    HandleError::InternalError(format!("Error serializing raw snapshot: {}", e))
+}
+
+fn deserialization_error(e: serde_json::Error) -> HandleError {
+    HandleError::InternalError(format!("Error loading raw snapshot: {}", e))
+}
+
+/// Rows storing the registrations and the processing parameters of a raw snapshot import
+pub(super) fn raw_snapshot_content(
+    tag: &str,
+    params: &RawSnapshotParams,
+    registrations: &[VotingRegistration],
+) -> Result<RawSnapshotContent, HandleError> {
+    Ok(RawSnapshotContent {
+        raw_snapshot: RawSnapshot {
+            tag: tag.to_string(),
+            min_stake_threshold: to_i64(params.min_stake_threshold.into())?,
+            voting_power_cap: serde_json::to_string(&params.voting_power_cap)
+                .map_err(serialization_error)?,
+            direct_voters_group: params.direct_voters_group.clone(),
+            representatives_group: params.representatives_group.clone(),
+            registrations_count: registrations.len() as i64,
+        },
+        registrations: registrations
+            .iter()
+            .enumerate()
+            .map(|(index, registration)| {
+                Ok(RawSnapshotRegistration {
+                    tag: tag.to_string(),
+                    registration_index: index as i64,
+                    registration: serde_json::to_string(registration)
+                        .map_err(serialization_error)?,
+                })
+            })
+            .collect::<Result<_, HandleError>>()?,
+    })
+}
+
+fn stored_params(
+    raw_snapshot: &RawSnapshot,
+    update_timestamp: i64,
+) -> Result<RawSnapshotParams, HandleError> {
+    Ok(RawSnapshotParams {
+        update_timestamp,
+        min_stake_threshold: Value::from(raw_snapshot.min_stake_threshold as u64),
+        voting_power_cap: serde_json::from_str(&raw_snapshot.voting_power_cap)
+            .map_err(deserialization_error)?,
+        direct_voters_group: raw_snapshot.direct_voters_group.clone(),
+        representatives_group: raw_snapshot.representatives_group.clone(),
+    })
+}
+
+/// Snapshot tags with a stored raw snapshot, ordered by update time
+#[tracing::instrument(skip(context))]
+pub async fn get_raw_snapshots(
+    context: SharedContext,
+) -> Result<Vec<StoredRawSnapshot>, HandleError> {
+    let pool = &context.read().await.db_connection_pool;
+
+    let mut raw_snapshots: HashMap<Tag, RawSnapshot> = query_raw_snapshots(pool)
+        .await?
+        .into_iter()
+        .map(|raw_snapshot| (raw_snapshot.tag.clone(), raw_snapshot))
+        .collect();
+
+    query_all_snapshots(pool)
+        .await?
+        .into_iter()
+        .filter_map(|snapshot| {
+            let raw_snapshot = raw_snapshots.remove(&snapshot.tag)?;
+            Some(
+                stored_params(&raw_snapshot, snapshot.last_updated).map(|params| {
+                    StoredRawSnapshot {
+                        tag: snapshot.tag,
+                        params,
+                        registrations_count: raw_snapshot.registrations_count as u64,
+                    }
+                }),
+            )
+        })
+        .collect()
+}
+
+async fn load_raw_snapshot(
+    tag: Tag,
+    context: &SharedContext,
+) -> Result<RawSnapshotInput, HandleError> {
+    let pool = &context.read().await.db_connection_pool;
+
+    let snapshot = query_snapshot_by_tag(tag.clone(), pool).await?;
+    let raw_snapshot = query_raw_snapshot_by_tag(tag.clone(), pool).await?;
+    let params = stored_params(&raw_snapshot, snapshot.last_updated)?;
+    let registrations = query_raw_snapshot_registrations_by_tag(tag, pool)
+        .await?
+        .into_iter()
+        .map(|registration| {
+            serde_json::from_str(&registration.registration).map_err(deserialization_error)
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(RawSnapshotInput {
+        snapshot: registrations,
+        update_timestamp: params.update_timestamp,
+        min_stake_threshold: params.min_stake_threshold,
+        voting_power_cap: params.voting_power_cap,
+        direct_voters_group: params.direct_voters_group,
+        representatives_group: params.representatives_group,
+    })
+}
+
+/// Registrations and processing parameters the snapshot tag was imported from
+#[tracing::instrument(skip(context))]
+pub async fn get_raw_snapshot(
+    tag: Tag,
+    context: SharedContext,
+) -> Result<RawSnapshotInput, HandleError> {
+    load_raw_snapshot(tag, &context).await
+}
+
+/// Processes the raw snapshot of a tag again with the given parameters and imports the result
+/// into `new_tag`, the original tag is left untouched
+#[tracing::instrument(skip(context))]
+pub async fn reprocess_raw_snapshot(
+    tag: Tag,
+    new_tag: Tag,
+    params: RawSnapshotParams,
+    context: SharedContext,
+) -> Result<(), HandleError> {
+    if new_tag == tag {
+        return Err(HandleError::BadRequest(format!(
+            "Snapshot {} cannot be reprocessed into itself",
+            tag
+        )));
+    }
+    let raw_snapshot = load_raw_snapshot(tag, &context).await?;
+    update_from_raw_snapshot(new_tag, raw_snapshot.snapshot, params, context).await
+}
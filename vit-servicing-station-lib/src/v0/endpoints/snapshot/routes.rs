@@ -1,7 +1,12 @@
 use crate::v0::context::SharedContext;
 
 use super::handlers::{
-    get_delegator_info, get_tags, get_voters_info, put_raw_snapshot, put_snapshot_info,
+    delete_tag, get_current_voters_info, get_delegator_history, get_delegator_info,
+    get_delegators_info_batch, get_raw_snapshot, get_raw_snapshots, get_snapshot_diff,
+    get_snapshot_export, get_snapshot_job, get_snapshot_stats, get_tags, get_voter_history,
+    get_voters, get_voters_info, get_voters_info_batch, preview_raw_snapshot, put_raw_snapshot,
+    put_raw_snapshot_stream, put_snapshot_info, put_snapshot_info_stream, reprocess_raw_snapshot,
+    SnapshotDiffQuery, SnapshotExportQuery, VotersQuery,
 };
 use warp::filters::BoxedFilter;
 use warp::{Filter, Rejection, Reply};
@@ -12,6 +17,11 @@ pub fn filter(
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let with_context = warp::any().map(move || context.clone());
 
+    let get_current_voters_info = warp::path!("voter" / "current" / String)
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_current_voters_info);
+
     let get_voters_info = warp::path!("voter" / String / String)
         .and(warp::get())
         .and(with_context.clone())
@@ -22,12 +32,71 @@ pub fn filter(
         .and(with_context.clone())
         .and_then(get_delegator_info);
 
+    let get_voter_history = warp::path!("history" / "voter" / String)
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_voter_history);
+
+    let get_delegator_history = warp::path!("history" / "delegator" / String)
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_delegator_history);
+
+    let get_snapshot_diff = warp::path!("diff" / String / String)
+        .and(warp::get())
+        .and(warp::query::<SnapshotDiffQuery>())
+        .and(with_context.clone())
+        .and_then(get_snapshot_diff);
+
+    let get_voters = warp::path!(String / "voters")
+        .and(warp::get())
+        .and(warp::query::<VotersQuery>())
+        .and(with_context.clone())
+        .and_then(get_voters);
+
+    let get_voters_info_batch = warp::path!(String / "voters")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context.clone())
+        .and_then(get_voters_info_batch);
+
+    let get_delegators_info_batch = warp::path!(String / "delegators")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context.clone())
+        .and_then(get_delegators_info_batch);
+
+    let get_snapshot_stats = warp::path!(String / "stats")
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_snapshot_stats);
+
+    let get_snapshot_export = warp::path!(String / "export")
+        .and(warp::get())
+        .and(warp::query::<SnapshotExportQuery>())
+        .and(with_context.clone())
+        .and_then(get_snapshot_export);
+
     let get_tags = warp::path::end()
         .and(warp::get())
         .and(with_context)
         .and_then(get_tags);
 
-    root.and(get_voters_info.or(get_delegator_info).or(get_tags))
+    // the current fund voter info is checked first, it would be taken for a `current` tag otherwise
+    root.and(
+        get_current_voters_info
+            .or(get_voters_info)
+            .or(get_delegator_info)
+            .or(get_voter_history)
+            .or(get_delegator_history)
+            .or(get_snapshot_diff)
+            .or(get_voters)
+            .or(get_voters_info_batch)
+            .or(get_delegators_info_batch)
+            .or(get_snapshot_stats)
+            .or(get_snapshot_export)
+            .or(get_tags),
+    )
 }
 
 pub fn update_filter(
@@ -44,8 +113,60 @@ pub fn update_filter(
     let raw_snapshot = warp::path!("raw_snapshot" / String)
         .and(warp::put())
         .and(warp::body::json())
-        .and(with_context)
+        .and(with_context.clone())
         .and_then(put_raw_snapshot);
 
-    snapshot_info.or(raw_snapshot)
+    let raw_snapshots = warp::path!("raw_snapshot")
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_raw_snapshots);
+
+    let get_raw_snapshot = warp::path!("raw_snapshot" / String)
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_raw_snapshot);
+
+    let reprocess = warp::path!(String / "reprocess")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context.clone())
+        .and_then(reprocess_raw_snapshot);
+
+    let preview = warp::path!("preview")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and_then(preview_raw_snapshot);
+
+    let snapshot_info_stream = warp::path!("snapshot_info_stream" / String)
+        .and(warp::put())
+        .and(warp::body::stream())
+        .and(with_context.clone())
+        .and_then(put_snapshot_info_stream);
+
+    let raw_snapshot_stream = warp::path!("raw_snapshot_stream" / String)
+        .and(warp::put())
+        .and(warp::body::stream())
+        .and(with_context.clone())
+        .and_then(put_raw_snapshot_stream);
+
+    let job = warp::path!("jobs" / u64)
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(get_snapshot_job);
+
+    let delete_tag = warp::path!(String)
+        .and(warp::delete())
+        .and(with_context)
+        .and_then(delete_tag);
+
+    snapshot_info
+        .or(raw_snapshot)
+        .or(raw_snapshots)
+        .or(get_raw_snapshot)
+        .or(reprocess)
+        .or(preview)
+        .or(snapshot_info_stream)
+        .or(raw_snapshot_stream)
+        .or(job)
+        .or(delete_tag)
 }
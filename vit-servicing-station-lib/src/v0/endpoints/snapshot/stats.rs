@@ -0,0 +1,160 @@
+use super::{Group, Tag};
+use crate::db::{models::snapshot::Snapshot, queries::snapshot::VotingGroupStatsRow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Voting power of the voter ranked at each percentile of a voting group
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VotingPowerPercentiles {
+    pub p10: u64,
+    pub p25: u64,
+    pub p50: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+/// Voting power held by the voters with the most voting power of a voting group
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopVotersShare {
+    pub voters: u64,
+    pub voting_power: u64,
+    /// Share of the total voting power of the voting group
+    pub share: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VotingGroupStats {
+    pub voting_group: Group,
+    pub voters_count: u64,
+    pub total_voting_power: u64,
+    /// Distinct stake keys contributing to the voters of the voting group
+    pub delegators_count: u64,
+    pub median_voting_power: u64,
+    pub percentiles: VotingPowerPercentiles,
+    pub top_voters: Vec<TopVotersShare>,
+    /// Inequality of the voting power distribution, 0 when every voter has the same voting power
+    pub gini_coefficient: f64,
+}
+
+impl From<VotingGroupStatsRow> for VotingGroupStats {
+    fn from(row: VotingGroupStatsRow) -> Self {
+        let total_voting_power = row.total_voting_power as u64;
+        let top_voters = |voters: u64, voting_power: i64| TopVotersShare {
+            voters,
+            voting_power: voting_power as u64,
+            share: if total_voting_power != 0 {
+                voting_power as f64 / total_voting_power as f64
+            } else {
+                0_f64
+            },
+        };
+        Self {
+            voting_group: row.voting_group,
+            voters_count: row.voters_count as u64,
+            total_voting_power,
+            delegators_count: row.delegators_count as u64,
+            median_voting_power: row.p50 as u64,
+            percentiles: VotingPowerPercentiles {
+                p10: row.p10 as u64,
+                p25: row.p25 as u64,
+                p50: row.p50 as u64,
+                p75: row.p75 as u64,
+                p90: row.p90 as u64,
+                p99: row.p99 as u64,
+            },
+            top_voters: vec![
+                top_voters(10, row.top_10_voting_power),
+                top_voters(100, row.top_100_voting_power),
+            ],
+            gini_coefficient: row.gini_coefficient,
+        }
+    }
+}
+
+/// Voting power distribution of a snapshot tag
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotStats {
+    pub tag: Tag,
+    #[serde(deserialize_with = "crate::utils::serde::deserialize_unix_timestamp_from_rfc3339")]
+    #[serde(serialize_with = "crate::utils::serde::serialize_unix_timestamp_as_rfc3339")]
+    pub last_updated: i64,
+    pub voting_groups: Vec<VotingGroupStats>,
+}
+
+/// Version of the content of a snapshot tag, as stored in its `snapshots` row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentVersion {
+    pub last_updated: i64,
+    pub content_hash: Option<String>,
+}
+
+impl From<&Snapshot> for ContentVersion {
+    fn from(snapshot: &Snapshot) -> Self {
+        Self {
+            last_updated: snapshot.last_updated,
+            content_hash: snapshot.content_hash.clone(),
+        }
+    }
+}
+
+/// Statistics of the snapshot tags already computed along with the version of the content they
+/// were computed from. They are only served for that same version, so a tag updated by another
+/// process or server sharing the database is never served stale stats.
+#[derive(Debug, Default)]
+pub struct SnapshotStatsCache {
+    stats: Mutex<HashMap<Tag, (ContentVersion, SnapshotStats)>>,
+}
+
+impl SnapshotStatsCache {
+    pub fn get(&self, tag: &str, version: &ContentVersion) -> Option<SnapshotStats> {
+        self.stats
+            .lock()
+            .unwrap()
+            .get(tag)
+            .filter(|(cached_version, _)| cached_version == version)
+            .map(|(_, stats)| stats.clone())
+    }
+
+    /// Cache stats computed from the content of the tag at `version`, which must be read before
+    /// the stats are
+    pub fn insert(&self, version: ContentVersion, stats: SnapshotStats) {
+        self.stats
+            .lock()
+            .unwrap()
+            .insert(stats.tag.clone(), (version, stats));
+    }
+
+    pub fn invalidate(&self, tag: &str) {
+        self.stats.lock().unwrap().remove(tag);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats_are_only_served_for_their_version() {
+        let cache = SnapshotStatsCache::default();
+        let version = |last_updated, content_hash: &str| ContentVersion {
+            last_updated,
+            content_hash: Some(content_hash.to_string()),
+        };
+        let stats = SnapshotStats {
+            tag: "tag".to_string(),
+            last_updated: 0,
+            voting_groups: Vec::new(),
+        };
+
+        cache.insert(version(0, "hash"), stats.clone());
+        assert_eq!(cache.get("tag", &version(0, "hash")), Some(stats));
+        // the tag was updated since the stats were computed
+        assert_eq!(cache.get("tag", &version(1, "hash")), None);
+        assert_eq!(cache.get("tag", &version(0, "other_hash")), None);
+
+        cache.invalidate("tag");
+        assert_eq!(cache.get("tag", &version(0, "hash")), None);
+    }
+}
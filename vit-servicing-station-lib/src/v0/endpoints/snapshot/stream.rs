@@ -0,0 +1,270 @@
+//! Streamed snapshot uploads. The request body, optionally gzip compressed, is spooled to a
+//! temporary file and processed in the background one line at a time: the first line holds the
+//! processing parameters and each following line a single registration or snapshot entry.
+
+use super::{
+    enforce_snapshot_retention, export::content_hash, process_raw_snapshot,
+    raw::raw_snapshot_content, snapshot_chunks, JobId, JobState, RawSnapshotParams,
+    SnapshotInfoStreamHeader, SnapshotJob, SnapshotJobs, SnapshotStatsCache, Tag,
+};
+use crate::{
+    db::{
+        models,
+        queries::snapshot::{replace_snapshot, RawSnapshotContent},
+        DbConnectionPool,
+    },
+    server::settings::SnapshotRetention,
+    v0::{context::SharedContext, errors::HandleError},
+};
+use flate2::read::GzDecoder;
+use futures_util::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use snapshot_lib::{registration::VotingRegistration, RawSnapshot, SnapshotInfo};
+use std::io::{BufRead, BufReader, Lines};
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
+use warp::Buf;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Clone, Copy, Debug)]
+enum UploadKind {
+    RawSnapshot,
+    SnapshotInfo,
+}
+
+fn io_error(e: std::io::Error) -> HandleError {
+    HandleError::InternalError(format!("Error storing upload: {}", e))
+}
+
+async fn spool<S, B>(body: S) -> Result<NamedTempFile, HandleError>
+where
+    S: Stream<Item = Result<B, warp::Error>>,
+    B: Buf,
+{
+    let file = NamedTempFile::new().map_err(io_error)?;
+    let mut writer = tokio::fs::File::from_std(file.reopen().map_err(io_error)?);
+    tokio::pin!(body);
+    while let Some(buf) = body.next().await {
+        let mut buf =
+            buf.map_err(|e| HandleError::BadRequest(format!("Error reading body: {}", e)))?;
+        while buf.has_remaining() {
+            let chunk = buf.chunk();
+            writer.write_all(chunk).await.map_err(io_error)?;
+            let len = chunk.len();
+            buf.advance(len);
+        }
+    }
+    writer.flush().await.map_err(io_error)?;
+    Ok(file)
+}
+
+/// Newline delimited JSON content of a spooled upload
+struct Upload {
+    lines: Lines<Box<dyn BufRead + Send>>,
+    line_number: usize,
+}
+
+impl Upload {
+    fn open(file: &NamedTempFile) -> Result<Self, HandleError> {
+        let mut reader = BufReader::new(file.reopen().map_err(io_error)?);
+        let compressed = reader
+            .fill_buf()
+            .map_err(io_error)?
+            .starts_with(&GZIP_MAGIC);
+        let reader: Box<dyn BufRead + Send> = if compressed {
+            Box::new(BufReader::new(GzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+        Ok(Self {
+            lines: reader.lines(),
+            line_number: 0,
+        })
+    }
+
+    /// Next non empty line
+    fn next_entry<T: DeserializeOwned>(&mut self) -> Option<Result<T, HandleError>> {
+        loop {
+            let line = self.lines.next()?;
+            self.line_number += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    return Some(Err(HandleError::BadRequest(format!(
+                        "Error reading line {}: {}",
+                        self.line_number, e
+                    ))))
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line).map_err(|e| {
+                HandleError::BadRequest(format!(
+                    "Invalid entry at line {}: {}",
+                    self.line_number, e
+                ))
+            }));
+        }
+    }
+
+    fn header<T: DeserializeOwned>(&mut self) -> Result<T, HandleError> {
+        self.next_entry()
+            .unwrap_or_else(|| Err(HandleError::BadRequest("Empty upload".to_string())))
+    }
+
+    fn entries<T: DeserializeOwned>(mut self) -> impl Iterator<Item = Result<T, HandleError>> {
+        std::iter::from_fn(move || self.next_entry())
+    }
+}
+
+pub async fn start_raw_snapshot_upload<S, B>(
+    tag: Tag,
+    body: S,
+    context: SharedContext,
+) -> Result<SnapshotJob, HandleError>
+where
+    S: Stream<Item = Result<B, warp::Error>>,
+    B: Buf,
+{
+    start_upload(tag, body, UploadKind::RawSnapshot, context).await
+}
+
+pub async fn start_snapshot_info_upload<S, B>(
+    tag: Tag,
+    body: S,
+    context: SharedContext,
+) -> Result<SnapshotJob, HandleError>
+where
+    S: Stream<Item = Result<B, warp::Error>>,
+    B: Buf,
+{
+    start_upload(tag, body, UploadKind::SnapshotInfo, context).await
+}
+
+/// Stores the upload and processes it in the background, the returned job tracks its progress
+async fn start_upload<S, B>(
+    tag: Tag,
+    body: S,
+    kind: UploadKind,
+    context: SharedContext,
+) -> Result<SnapshotJob, HandleError>
+where
+    S: Stream<Item = Result<B, warp::Error>>,
+    B: Buf,
+{
+    let file = spool(body).await?;
+
+    let (pool, retention, jobs, stats) = {
+        let context = context.read().await;
+        (
+            context.db_connection_pool.clone(),
+            context.snapshot_retention.clone(),
+            context.snapshot_jobs.clone(),
+            context.snapshot_stats.clone(),
+        )
+    };
+    let job = jobs.start(tag.clone());
+    let id = job.id;
+
+    tokio::spawn(async move {
+        let state =
+            match process_upload(id, tag, file, kind, &pool, &retention, &jobs, &stats).await {
+                Ok(()) => JobState::Finished,
+                Err(e) => {
+                    tracing::error!("snapshot upload job {} failed: {}", id, e);
+                    JobState::Failed {
+                        error: e.to_string(),
+                    }
+                }
+            };
+        jobs.update(id, |job| job.state = state);
+    });
+
+    Ok(job)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_upload(
+    id: JobId,
+    tag: Tag,
+    file: NamedTempFile,
+    kind: UploadKind,
+    pool: &DbConnectionPool,
+    retention: &SnapshotRetention,
+    jobs: &Arc<SnapshotJobs>,
+    stats: &SnapshotStatsCache,
+) -> Result<(), HandleError> {
+    let db_conn = pool.get().map_err(HandleError::DatabaseError)?;
+    let chunks_tag = tag.clone();
+    let jobs = jobs.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut upload = Upload::open(&file)?;
+        let entry_read = move |jobs: &SnapshotJobs| jobs.update(id, |job| job.entries_read += 1);
+
+        let (update_timestamp, raw_snapshot, entries): (
+            i64,
+            Option<RawSnapshotContent>,
+            Box<dyn Iterator<Item = Result<SnapshotInfo, HandleError>>>,
+        ) = match kind {
+            UploadKind::RawSnapshot => {
+                let params: RawSnapshotParams = upload.header()?;
+                // the whole set of registrations is needed to compute the voting power caps
+                let registrations = upload
+                    .entries::<VotingRegistration>()
+                    .inspect(|_| entry_read(&jobs))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let raw_snapshot = raw_snapshot_content(&chunks_tag, &params, &registrations)?;
+                let snapshot = process_raw_snapshot(
+                    RawSnapshot::from(registrations),
+                    params.min_stake_threshold,
+                    params.voting_power_cap,
+                    params.direct_voters_group,
+                    params.representatives_group,
+                )?;
+                (
+                    params.update_timestamp,
+                    Some(raw_snapshot),
+                    Box::new(snapshot.into_iter().map(Ok)),
+                )
+            }
+            UploadKind::SnapshotInfo => {
+                let header: SnapshotInfoStreamHeader = upload.header()?;
+                let jobs = jobs.clone();
+                (
+                    header.update_timestamp,
+                    None,
+                    Box::new(
+                        upload
+                            .entries::<SnapshotInfo>()
+                            .inspect(move |_| entry_read(&jobs)),
+                    ),
+                )
+            }
+        };
+
+        let snapshot = models::snapshot::Snapshot {
+            tag: chunks_tag.clone(),
+            last_updated: update_timestamp,
+            content_hash: None,
+        };
+        let chunks = snapshot_chunks(chunks_tag, entries).inspect(|chunk| {
+            if let Ok(chunk) = chunk {
+                jobs.update(id, |job| {
+                    job.voters_processed += chunk.voters.len() as u64;
+                    job.contributions_processed += chunk.contributions.len() as u64;
+                })
+            }
+        });
+        replace_snapshot(snapshot, raw_snapshot, chunks, content_hash, &db_conn)
+    })
+    .await
+    .map_err(|e| HandleError::InternalError(format!("Error executing request: {}", e)))??;
+
+    stats.invalidate(&tag);
+
+    enforce_snapshot_retention(tag, retention, stats, pool).await
+}
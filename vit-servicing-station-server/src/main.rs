@@ -105,6 +105,7 @@ async fn main() {
     });
 
     // Check db file exists (should be here only for current sqlite db backend)
+    #[cfg(not(feature = "postgres"))]
     if !std::path::Path::new(&settings.db_url).exists() {
         error!("DB file {} not found.", &settings.db_url);
         std::process::exit(ApplicationExitCode::DbConnectionError.into())
@@ -115,6 +116,41 @@ async fn main() {
         std::process::exit(ApplicationExitCode::DbConnectionError.into())
     });
 
+    // refuse to start if the db schema is behind the one this binary expects
+    let pending_migrations = db_pool
+        .get()
+        .map_err(db::Error::from)
+        .and_then(|db_conn| db::migrations::pending_migrations(&db_conn).map_err(db::Error::from))
+        .unwrap_or_else(|e| {
+            error!("Error checking database schema: {}", e);
+            std::process::exit(ApplicationExitCode::DbConnectionError.into())
+        });
+    if !pending_migrations.is_empty() {
+        error!(
+            "Database schema is outdated, pending migrations: {}. Run `vit-servicing-station-cli db migrate` to update it.",
+            pending_migrations.join(", ")
+        );
+        std::process::exit(ApplicationExitCode::OutdatedDbSchemaError.into())
+    }
+
+    if settings.check_db_integrity {
+        let report = db_pool
+            .get()
+            .map_err(db::Error::from)
+            .and_then(|db_conn| db::integrity::check_integrity(&db_conn).map_err(db::Error::from))
+            .unwrap_or_else(|e| {
+                error!("Error checking database integrity: {}", e);
+                std::process::exit(ApplicationExitCode::DbConnectionError.into())
+            });
+        if !report.is_ok() {
+            error!(
+                "Database integrity check found {} errors. Run `vit-servicing-station-cli db check` for details.",
+                report.errors_count()
+            );
+            std::process::exit(ApplicationExitCode::DbIntegrityError.into())
+        }
+    }
+
     let paths: Vec<PathBuf> = if let Some(single_block0_path) = &settings.block0_path {
         vec![PathBuf::from_str(single_block0_path).unwrap()]
     } else {
@@ -134,7 +170,26 @@ async fn main() {
         std::process::exit(ApplicationExitCode::EmptyBlock0FolderError.into());
     }
 
-    let context = v0::context::new_shared_context(db_pool, paths, &settings.service_version);
+    let context = v0::context::new_shared_context(
+        db_pool,
+        paths,
+        &settings.service_version,
+        settings.snapshot_retention.clone(),
+        settings
+            .snapshot_max_batch_size
+            .unwrap_or(v0::endpoints::snapshot::DEFAULT_SNAPSHOT_MAX_BATCH_SIZE),
+        !settings.no_current_fund_fallback,
+        settings.backup_dir.clone(),
+    );
+
+    let _snapshot_watcher = server::snapshot_watcher::SnapshotWatcher::start(
+        &settings.snapshot_watcher,
+        context.clone(),
+    )
+    .unwrap_or_else(|e| {
+        error!("Error starting snapshot watcher: {}", e);
+        std::process::exit(ApplicationExitCode::SnapshotWatcherError.into())
+    });
 
     let app = v0::filter(context, settings.enable_api_tokens).await;
 
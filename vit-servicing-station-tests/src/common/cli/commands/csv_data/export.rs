@@ -0,0 +1,30 @@
+use std::path::Path;
+use std::process::Command;
+pub struct ExportCsvCommand {
+    command: Command,
+}
+
+impl ExportCsvCommand {
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    pub fn db_url<P: AsRef<Path>>(mut self, db_url: P) -> Self {
+        self.command.arg("--db-url").arg(db_url.as_ref());
+        self
+    }
+
+    pub fn output<P: AsRef<Path>>(mut self, output: P) -> Self {
+        self.command.arg("--output").arg(output.as_ref());
+        self
+    }
+
+    pub fn format<S: Into<String>>(mut self, format: S) -> Self {
+        self.command.arg("--format").arg(format.into());
+        self
+    }
+
+    pub fn build(self) -> Command {
+        self.command
+    }
+}
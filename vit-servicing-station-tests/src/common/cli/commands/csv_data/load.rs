@@ -54,6 +54,11 @@ impl LoadCsvCommand {
         self
     }
 
+    pub fn votes<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.command.arg("--votes").arg(path.as_ref());
+        self
+    }
+
     pub fn build(self) -> Command {
         self.command
     }
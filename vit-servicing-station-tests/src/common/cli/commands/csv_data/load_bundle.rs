@@ -0,0 +1,25 @@
+use std::path::Path;
+use std::process::Command;
+pub struct LoadBundleCommand {
+    command: Command,
+}
+
+impl LoadBundleCommand {
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    pub fn db_url<P: AsRef<Path>>(mut self, db_url: P) -> Self {
+        self.command.arg("--db-url").arg(db_url.as_ref());
+        self
+    }
+
+    pub fn bundle<P: AsRef<Path>>(mut self, bundle: P) -> Self {
+        self.command.arg("--bundle").arg(bundle.as_ref());
+        self
+    }
+
+    pub fn build(self) -> Command {
+        self.command
+    }
+}
@@ -1,7 +1,13 @@
+mod export;
 mod load;
+mod load_bundle;
+mod validate;
 
+pub use export::ExportCsvCommand;
 pub use load::LoadCsvCommand;
+pub use load_bundle::LoadBundleCommand;
 use std::process::Command;
+pub use validate::ValidateCsvCommand;
 
 pub struct CsvDataCommand {
     command: Command,
@@ -16,4 +22,19 @@ impl CsvDataCommand {
         self.command.arg("load");
         LoadCsvCommand::new(self.command)
     }
+
+    pub fn load_bundle(mut self) -> LoadBundleCommand {
+        self.command.arg("load-bundle");
+        LoadBundleCommand::new(self.command)
+    }
+
+    pub fn export(mut self) -> ExportCsvCommand {
+        self.command.arg("export");
+        ExportCsvCommand::new(self.command)
+    }
+
+    pub fn validate(mut self) -> ValidateCsvCommand {
+        self.command.arg("validate");
+        ValidateCsvCommand::new(self.command)
+    }
 }
@@ -0,0 +1,30 @@
+use std::path::Path;
+use std::process::Command;
+pub struct ValidateCsvCommand {
+    command: Command,
+}
+
+impl ValidateCsvCommand {
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    pub fn funds<P: AsRef<Path>>(mut self, funds: P) -> Self {
+        self.command.arg("--funds").arg(funds.as_ref());
+        self
+    }
+
+    pub fn voteplans<P: AsRef<Path>>(mut self, voteplans: P) -> Self {
+        self.command.arg("--voteplans").arg(voteplans.as_ref());
+        self
+    }
+
+    pub fn challenges<P: AsRef<Path>>(mut self, challenges: P) -> Self {
+        self.command.arg("--challenges").arg(challenges.as_ref());
+        self
+    }
+
+    pub fn build(self) -> Command {
+        self.command
+    }
+}
@@ -1,6 +1,18 @@
+mod backup;
+mod check;
 mod init;
+mod migrate;
+mod restore;
+mod rollback;
+mod status;
 
+pub use backup::BackupDbCommand;
+pub use check::CheckDbCommand;
 pub use init::InitDbCommand;
+pub use migrate::MigrateDbCommand;
+pub use restore::RestoreDbCommand;
+pub use rollback::RollbackDbCommand;
+pub use status::StatusDbCommand;
 use std::process::Command;
 
 pub struct DbCommand {
@@ -16,4 +28,34 @@ impl DbCommand {
         self.command.arg("init");
         InitDbCommand::new(self.command)
     }
+
+    pub fn status(mut self) -> StatusDbCommand {
+        self.command.arg("status");
+        StatusDbCommand::new(self.command)
+    }
+
+    pub fn migrate(mut self) -> MigrateDbCommand {
+        self.command.arg("migrate");
+        MigrateDbCommand::new(self.command)
+    }
+
+    pub fn rollback(mut self) -> RollbackDbCommand {
+        self.command.arg("rollback");
+        RollbackDbCommand::new(self.command)
+    }
+
+    pub fn backup(mut self) -> BackupDbCommand {
+        self.command.arg("backup");
+        BackupDbCommand::new(self.command)
+    }
+
+    pub fn restore(mut self) -> RestoreDbCommand {
+        self.command.arg("restore");
+        RestoreDbCommand::new(self.command)
+    }
+
+    pub fn check(mut self) -> CheckDbCommand {
+        self.command.arg("check");
+        CheckDbCommand::new(self.command)
+    }
 }
@@ -0,0 +1,25 @@
+use std::path::Path;
+use std::process::Command;
+pub struct RestoreDbCommand {
+    command: Command,
+}
+
+impl RestoreDbCommand {
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    pub fn db_url<P: AsRef<Path>>(mut self, db_url: P) -> Self {
+        self.command.arg("--db-url").arg(db_url.as_ref());
+        self
+    }
+
+    pub fn backup<P: AsRef<Path>>(mut self, backup: P) -> Self {
+        self.command.arg("--backup").arg(backup.as_ref());
+        self
+    }
+
+    pub fn build(self) -> Command {
+        self.command
+    }
+}
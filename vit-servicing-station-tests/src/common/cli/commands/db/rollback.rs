@@ -0,0 +1,25 @@
+use std::path::Path;
+use std::process::Command;
+pub struct RollbackDbCommand {
+    command: Command,
+}
+
+impl RollbackDbCommand {
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    pub fn db_url<P: AsRef<Path>>(mut self, db_url: P) -> Self {
+        self.command.arg("--db-url").arg(db_url.as_ref());
+        self
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.command.arg("--count").arg(count.to_string());
+        self
+    }
+
+    pub fn build(self) -> Command {
+        self.command
+    }
+}
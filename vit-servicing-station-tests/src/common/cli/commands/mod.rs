@@ -1,9 +1,11 @@
 mod csv_data;
 mod db;
+mod snapshot;
 mod token;
 
 use csv_data::CsvDataCommand;
 use db::DbCommand;
+use snapshot::SnapshotCommand;
 use token::ApiTokenCommand;
 
 use crate::common::startup::get_cli_exe;
@@ -41,4 +43,10 @@ impl VitCliCommand {
         command.arg("csv-data");
         CsvDataCommand::new(command)
     }
+
+    pub fn snapshot(self) -> SnapshotCommand {
+        let mut command = Command::new(self.exe);
+        command.arg("snapshot");
+        SnapshotCommand::new(command)
+    }
 }
@@ -0,0 +1,25 @@
+use std::path::Path;
+use std::process::Command;
+pub struct DeleteSnapshotCommand {
+    command: Command,
+}
+
+impl DeleteSnapshotCommand {
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    pub fn db_url<P: AsRef<Path>>(mut self, db_url: P) -> Self {
+        self.command.arg("--db-url").arg(db_url.as_ref());
+        self
+    }
+
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.command.arg("--tag").arg(tag.into());
+        self
+    }
+
+    pub fn build(self) -> Command {
+        self.command
+    }
+}
@@ -0,0 +1,19 @@
+mod delete;
+
+pub use delete::DeleteSnapshotCommand;
+use std::process::Command;
+
+pub struct SnapshotCommand {
+    command: Command,
+}
+
+impl SnapshotCommand {
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    pub fn delete(mut self) -> DeleteSnapshotCommand {
+        self.command.arg("delete");
+        DeleteSnapshotCommand::new(self.command)
+    }
+}
@@ -43,6 +43,7 @@ impl CsvConverter {
             "tallying_end",
             "results_url",
             "survey_url",
+            "snapshot_tag",
         ];
         let content: Vec<Vec<String>> = funds.iter().map(convert_fund).collect();
         self.build_file(headers, content, path)
@@ -275,6 +276,7 @@ fn convert_fund(fund: &Fund) -> Vec<String> {
         results_url,
         survey_url,
         groups: _,
+        snapshot_tag,
     } = fund;
 
     // TODO: can we leverage serde to build these vectors?
@@ -300,6 +302,7 @@ fn convert_fund(fund: &Fund) -> Vec<String> {
         unix_timestamp_to_rfc3339(stage_dates.tallying_end),
         results_url.to_string(),
         survey_url.to_string(),
+        snapshot_tag.clone().unwrap_or_default(),
     ]
 }
 
@@ -82,7 +82,14 @@ impl ArbitrarySnapshotGenerator {
 
         let chain_vote_plans = groups
             .iter()
-            .map(|g| self.voteplan_with_fund_id(id.abs(), g.token_identifier.clone()))
+            .map(|g| {
+                self.voteplan_with_fund_id(
+                    id.abs(),
+                    g.token_identifier.clone(),
+                    dates.voting_start,
+                    dates.voting_end,
+                )
+            })
             .collect();
 
         Fund {
@@ -117,6 +124,7 @@ impl ArbitrarySnapshotGenerator {
             results_url: format!("http://localhost/fund/{id}/results/"),
             survey_url: format!("http://localhost/fund/{id}/survey/"),
             groups,
+            snapshot_tag: None,
         }
     }
 
@@ -185,7 +193,7 @@ impl ArbitrarySnapshotGenerator {
         let refine_proposals_start = rand_datetime_in_range(proposal_submission_start, end);
         let finalize_proposals_start = rand_datetime_in_range(refine_proposals_start, end);
         let proposal_assessment_start = rand_datetime_in_range(finalize_proposals_start, end);
-        let assessment_qa_start = rand_datetime_in_range(finalize_proposals_start, end);
+        let assessment_qa_start = rand_datetime_in_range(proposal_assessment_start, end);
         let snapshot_start = rand_datetime_in_range(assessment_qa_start, end);
         let voting_start = rand_datetime_in_range(snapshot_start, end);
         let voting_end = rand_datetime_in_range(voting_start, end);
@@ -210,14 +218,18 @@ impl ArbitrarySnapshotGenerator {
         }
     }
 
-    fn voteplan_date_times(&self) -> VoteplanDateTimes {
-        let range_start_time = OffsetDateTime::now_utc() - Duration::days(10);
-        let range_end_time = OffsetDateTime::now_utc() + Duration::days(10);
-        let range_tally_time = range_end_time + Duration::days(10);
-        let start = rand_datetime_in_range(range_start_time, OffsetDateTime::now_utc());
-        let end = rand_datetime_in_range(OffsetDateTime::now_utc(), range_end_time);
-        let tally = rand_datetime_in_range(range_end_time, range_tally_time);
-        VoteplanDateTimes { start, end, tally }
+    /// Voteplans run for the whole voting window of their fund
+    fn voteplan_date_times(
+        &self,
+        voting_start: OffsetDateTime,
+        voting_end: OffsetDateTime,
+    ) -> VoteplanDateTimes {
+        let tally = rand_datetime_in_range(voting_end, voting_end + Duration::days(10));
+        VoteplanDateTimes {
+            start: voting_start,
+            end: voting_end,
+            tally,
+        }
     }
 
     pub fn voteplans(&mut self, funds: &[Fund]) -> Vec<Voteplan> {
@@ -272,9 +284,15 @@ impl ArbitrarySnapshotGenerator {
             .collect()
     }
 
-    pub fn voteplan_with_fund_id(&mut self, fund_id: i32, token_identifier: String) -> Voteplan {
+    pub fn voteplan_with_fund_id(
+        &mut self,
+        fund_id: i32,
+        token_identifier: String,
+        voting_start: OffsetDateTime,
+        voting_end: OffsetDateTime,
+    ) -> Voteplan {
         let id = self.id_generator.next_u32() as i32;
-        let dates = self.voteplan_date_times();
+        let dates = self.voteplan_date_times(voting_start, voting_end);
 
         Voteplan {
             id: id.abs(),
@@ -59,6 +59,7 @@ impl CurrentFund {
             results_url: self.info.results_url.clone(),
             survey_url: self.info.survey_url.clone(),
             groups: self.info.groups.iter().cloned().collect(),
+            snapshot_tag: None,
         }
     }
 }
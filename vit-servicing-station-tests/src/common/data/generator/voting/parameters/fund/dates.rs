@@ -21,8 +21,8 @@ impl Default for FundDates {
     fn default() -> Self {
         let now = OffsetDateTime::now_utc();
         Self {
-            proposal_submission_start: as_timestamp(now - 10.days()),
-            insight_sharing_start: as_timestamp(now - 9.days()),
+            insight_sharing_start: as_timestamp(now - 10.days()),
+            proposal_submission_start: as_timestamp(now - 9.days()),
             refine_proposals_start: as_timestamp(now - 8.days()),
             finalize_proposals_start: as_timestamp(now - 7.days()),
             proposal_assessment_start: as_timestamp(now - 6.days()),
@@ -43,6 +43,7 @@ impl Into<Fund> for FundInfo {
             results_url: self.results_url,
             survey_url: self.survey_url,
             groups: self.groups.into_iter().collect(),
+            snapshot_tag: None,
         }
     }
 }
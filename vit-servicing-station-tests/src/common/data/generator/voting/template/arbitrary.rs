@@ -194,14 +194,15 @@ impl ValidVotingTemplateGenerator for ArbitraryValidVotingTemplateGenerator {
     }
 
     fn next_challenge(&mut self) -> ChallengeTemplate {
+        let rewards_total = self.generator.next_u32() % 10000;
         let challenge = ChallengeTemplate {
             internal_id: self.next_challenge_id(),
             id: self.generator.id().to_string(),
             challenge_type: self.challenge_type(),
             title: CatchPhase().fake::<String>(),
             description: Buzzword().fake::<String>(),
-            rewards_total: (self.generator.next_u32() % 10000).to_string(),
-            proposers_rewards: (self.generator.next_u32() % 10000).to_string(),
+            rewards_total: rewards_total.to_string(),
+            proposers_rewards: (self.generator.next_u32() % (rewards_total + 1)).to_string(),
             challenge_url: self.gen_http_address(),
             fund_id: None,
             highlight: self.gen_highlights(),
@@ -13,6 +13,7 @@ pub struct BootstrapCommandBuilder {
     cert_file: Option<PathBuf>,
     db_url: Option<String>,
     enable_api_tokens: bool,
+    check_db_integrity: bool,
     in_settings_file: Option<PathBuf>,
     max_age_secs: Option<u32>,
     out_settings_file: Option<PathBuf>,
@@ -39,6 +40,7 @@ impl BootstrapCommandBuilder {
             cert_file: None,
             db_url: None,
             enable_api_tokens: false,
+            check_db_integrity: false,
             in_settings_file: None,
             max_age_secs: None,
             out_settings_file: None,
@@ -84,6 +86,11 @@ impl BootstrapCommandBuilder {
         self
     }
 
+    pub fn check_db_integrity(&mut self, enabled: bool) -> &mut Self {
+        self.check_db_integrity = enabled;
+        self
+    }
+
     pub fn in_settings_file(&mut self, in_settings_file: &Path) -> &mut Self {
         self.in_settings_file = Some(in_settings_file.to_path_buf());
         self
@@ -173,6 +180,10 @@ impl BootstrapCommandBuilder {
             command.arg("--enable-api-tokens");
         }
 
+        if self.check_db_integrity {
+            command.arg("--check-db-integrity");
+        }
+
         if let Some(log_file) = &self.log_file {
             command
                 .arg("--log-output-path")
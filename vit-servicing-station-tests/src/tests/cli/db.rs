@@ -1,9 +1,15 @@
 use crate::common::cli::VitCliCommand;
-use crate::common::startup::server::ServerBootstrapper;
+use crate::common::data;
+use crate::common::paths::BLOCK0_BIN;
+use crate::common::startup::db::DbBuilder;
+use crate::common::startup::server::{BootstrapCommandBuilder, ServerBootstrapper};
 use assert_cmd::assert::OutputAssertExt;
 use assert_fs::assert::PathAssert;
 use assert_fs::{fixture::PathChild, TempDir};
+use diesel::{Connection, RunQueryDsl, SqliteConnection};
 use jortestkit::prelude::file_exists_and_not_empty;
+use predicates::prelude::*;
+use vit_servicing_station_lib::server::exit_codes::ApplicationExitCode;
 #[test]
 pub fn generate_empty_db() {
     let temp_dir = TempDir::new().unwrap();
@@ -27,3 +33,188 @@ pub fn generate_empty_db() {
     std::thread::sleep(std::time::Duration::from_secs(1));
     assert!(server.rest_client().health().is_ok());
 }
+
+#[test]
+pub fn server_refuses_outdated_db_until_migrated() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_file = temp_dir.child("db.sqlite");
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .init()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .success();
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .rollback()
+        .db_url(db_file.path())
+        .count(2)
+        .build()
+        .assert()
+        .success();
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .status()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[pending]"));
+
+    let mut command_builder: BootstrapCommandBuilder = Default::default();
+    command_builder
+        .db_url(db_file.path().to_str().unwrap())
+        .block0_path(Some(BLOCK0_BIN.to_string()))
+        .build()
+        .assert()
+        .failure()
+        .code(ApplicationExitCode::OutdatedDbSchemaError as i32);
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .migrate()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .success();
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .status()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[pending]").not());
+
+    let server = ServerBootstrapper::new()
+        .with_db_path(db_file.path().to_str().unwrap())
+        .start(&temp_dir)
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    assert!(server.rest_client().health().is_ok());
+}
+
+#[test]
+pub fn check_db_integrity() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_file = temp_dir.child("db.sqlite");
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .init()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .success();
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .check()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"voters_without_snapshot\": []"));
+
+    // voter of a snapshot that does not exist
+    let db_conn = SqliteConnection::establish(db_file.path().to_str().unwrap()).unwrap();
+    diesel::sql_query(
+        "INSERT INTO voters (voting_key, voting_power, voting_group, snapshot_tag) \
+         VALUES ('voting_key', 1, 'direct', 'missing_tag')",
+    )
+    .execute(&db_conn)
+    .unwrap();
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .check()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("missing_tag"));
+
+    let mut command_builder: BootstrapCommandBuilder = Default::default();
+    command_builder
+        .db_url(db_file.path().to_str().unwrap())
+        .block0_path(Some(BLOCK0_BIN.to_string()))
+        .check_db_integrity(true)
+        .build()
+        .assert()
+        .failure()
+        .code(ApplicationExitCode::DbIntegrityError as i32);
+}
+
+#[test]
+pub fn backup_and_restore_db_while_server_is_running() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapshot = data::ArbitrarySnapshotGenerator::default().snapshot();
+    let db_path = DbBuilder::new()
+        .with_snapshot(&snapshot)
+        .build(&temp_dir)
+        .unwrap();
+
+    let server = ServerBootstrapper::new()
+        .with_db_path(db_path.to_str().unwrap())
+        .start(&temp_dir)
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    let expected_challenges = server
+        .rest_client_with_token(&snapshot.token_hash())
+        .challenges()
+        .unwrap();
+
+    let backup_file = temp_dir.child("backup.sqlite");
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .backup()
+        .db_url(&db_path)
+        .out(backup_file.path())
+        .build()
+        .assert()
+        .success();
+
+    let backup_server = ServerBootstrapper::new()
+        .with_db_path(backup_file.path().to_str().unwrap())
+        .start(&temp_dir)
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    assert_eq!(
+        backup_server
+            .rest_client_with_token(&snapshot.token_hash())
+            .challenges()
+            .unwrap(),
+        expected_challenges
+    );
+
+    // restore over the db in use by the running server
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .restore()
+        .db_url(&db_path)
+        .backup(backup_file.path())
+        .build()
+        .assert()
+        .success();
+    assert_eq!(
+        server
+            .rest_client_with_token(&snapshot.token_hash())
+            .challenges()
+            .unwrap(),
+        expected_challenges
+    );
+}
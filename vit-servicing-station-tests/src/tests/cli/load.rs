@@ -1,22 +1,34 @@
-use crate::common::data::{multivoteplan_snapshot, ArbitrarySnapshotGenerator};
+use crate::common::data::{multivoteplan_snapshot, ArbitrarySnapshotGenerator, Snapshot};
 use crate::common::{
     cli::VitCliCommand,
     data::CsvConverter,
+    db::DbInserter,
     startup::{db::DbBuilder, server::ServerBootstrapper},
 };
 use assert_cmd::assert::OutputAssertExt;
 use assert_fs::{fixture::PathChild, TempDir};
+use diesel::{Connection, SqliteConnection};
+use predicates::prelude::*;
+use std::path::Path;
+use vit_servicing_station_lib::db::models::{funds::Fund, voteplans::Voteplan};
 
-#[test]
-pub fn load_data_test() {
-    let temp_dir = TempDir::new().unwrap().into_persistent();
-    let db_file = temp_dir.child("db.sqlite");
+fn load_arbitrary_snapshot(temp_dir: &TempDir, db_file: &Path) {
     let snapshot = ArbitrarySnapshotGenerator::default().snapshot();
+    let voteplans = snapshot.voteplans().iter().take(1).cloned().collect();
+    load_snapshot(temp_dir, db_file, &snapshot, snapshot.funds(), voteplans);
+}
 
+fn load_snapshot(
+    temp_dir: &TempDir,
+    db_file: &Path,
+    snapshot: &Snapshot,
+    funds: Vec<Fund>,
+    voteplans: Vec<Voteplan>,
+) {
     let csv_converter = CsvConverter;
 
-    let funds = temp_dir.child("funds.csv");
-    csv_converter.funds(snapshot.funds(), funds.path()).unwrap();
+    let funds_file = temp_dir.child("funds.csv");
+    csv_converter.funds(funds, funds_file.path()).unwrap();
 
     let proposals = temp_dir.child("proposals.csv");
     csv_converter
@@ -26,12 +38,9 @@ pub fn load_data_test() {
         )
         .unwrap();
 
-    let voteplans = temp_dir.child("voteplans.csv");
+    let voteplans_file = temp_dir.child("voteplans.csv");
     csv_converter
-        .voteplans(
-            snapshot.voteplans().iter().take(1).cloned().collect(),
-            voteplans.path(),
-        )
+        .voteplans(voteplans, voteplans_file.path())
         .unwrap();
 
     let challenges = temp_dir.child("challenges.csv");
@@ -64,7 +73,7 @@ pub fn load_data_test() {
     vit_cli
         .db()
         .init()
-        .db_url(db_file.path())
+        .db_url(db_file)
         .build()
         .assert()
         .success();
@@ -73,10 +82,10 @@ pub fn load_data_test() {
     vit_cli
         .csv_data()
         .load()
-        .db_url(db_file.path())
-        .funds(funds.path())
+        .db_url(db_file)
+        .funds(funds_file.path())
         .proposals(proposals.path())
-        .voteplans(voteplans.path())
+        .voteplans(voteplans_file.path())
         .challenges(challenges.path())
         .advisor_reviews(reviews.path())
         .goals(goals.path())
@@ -84,6 +93,13 @@ pub fn load_data_test() {
         .build()
         .assert()
         .success();
+}
+
+#[test]
+pub fn load_data_test() {
+    let temp_dir = TempDir::new().unwrap().into_persistent();
+    let db_file = temp_dir.child("db.sqlite");
+    load_arbitrary_snapshot(&temp_dir, db_file.path());
 
     let server = ServerBootstrapper::new()
         .with_db_path(db_file.path().to_str().unwrap())
@@ -101,3 +117,270 @@ pub fn voting_snapshot_build() {
     db_builder.with_snapshot(&multivoteplan_snapshot());
     db_builder.build(&temp_dir).unwrap();
 }
+
+fn init_db(db_file: &Path) {
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .init()
+        .db_url(db_file)
+        .build()
+        .assert()
+        .success();
+}
+
+fn export(db_file: &Path, output: &Path, format: &str) {
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .csv_data()
+        .export()
+        .db_url(db_file)
+        .output(output)
+        .format(format)
+        .build()
+        .assert()
+        .success();
+}
+
+#[test]
+pub fn export_and_load_csv_round_trip() {
+    let temp_dir = TempDir::new().unwrap().into_persistent();
+    let db_file = temp_dir.child("db.sqlite");
+    load_arbitrary_snapshot(&temp_dir, db_file.path());
+
+    let exported = temp_dir.child("exported");
+    export(db_file.path(), exported.path(), "csv");
+
+    let reloaded_db_file = temp_dir.child("reloaded.sqlite");
+    init_db(reloaded_db_file.path());
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .csv_data()
+        .load()
+        .db_url(reloaded_db_file.path())
+        .funds(exported.child("funds.csv").path())
+        .proposals(exported.child("proposals.csv").path())
+        .voteplans(exported.child("voteplans.csv").path())
+        .challenges(exported.child("challenges.csv").path())
+        .advisor_reviews(exported.child("reviews.csv").path())
+        .goals(exported.child("goals.csv").path())
+        .groups(exported.child("groups.csv").path())
+        .votes(exported.child("votes").path())
+        .build()
+        .assert()
+        .success();
+
+    let reexported = temp_dir.child("reexported");
+    export(reloaded_db_file.path(), reexported.path(), "csv");
+
+    for file in [
+        "funds.csv",
+        "voteplans.csv",
+        "proposals.csv",
+        "challenges.csv",
+        "reviews.csv",
+        "goals.csv",
+        "groups.csv",
+        "votes/votes.csv",
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(exported.child(file).path()).unwrap(),
+            std::fs::read_to_string(reexported.child(file).path()).unwrap(),
+            "{} differs after the round trip",
+            file
+        );
+    }
+}
+
+#[test]
+pub fn export_and_load_json_bundle_round_trip() {
+    let temp_dir = TempDir::new().unwrap().into_persistent();
+    let db_file = temp_dir.child("db.sqlite");
+    load_arbitrary_snapshot(&temp_dir, db_file.path());
+
+    let bundle = temp_dir.child("bundle.json");
+    export(db_file.path(), bundle.path(), "json");
+
+    let reloaded_db_file = temp_dir.child("reloaded.sqlite");
+    init_db(reloaded_db_file.path());
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .csv_data()
+        .load_bundle()
+        .db_url(reloaded_db_file.path())
+        .bundle(bundle.path())
+        .build()
+        .assert()
+        .success();
+
+    let reexported_bundle = temp_dir.child("reexported_bundle.json");
+    export(reloaded_db_file.path(), reexported_bundle.path(), "json");
+
+    assert_eq!(
+        std::fs::read_to_string(bundle.path()).unwrap(),
+        std::fs::read_to_string(reexported_bundle.path()).unwrap()
+    );
+}
+
+fn shift_fund(fund: &mut Fund, secs: i64) {
+    let stage_dates = &mut fund.stage_dates;
+    for time in [
+        &mut fund.fund_start_time,
+        &mut fund.fund_end_time,
+        &mut fund.next_fund_start_time,
+        &mut fund.registration_snapshot_time,
+        &mut fund.next_registration_snapshot_time,
+        &mut stage_dates.insight_sharing_start,
+        &mut stage_dates.proposal_submission_start,
+        &mut stage_dates.refine_proposals_start,
+        &mut stage_dates.finalize_proposals_start,
+        &mut stage_dates.proposal_assessment_start,
+        &mut stage_dates.assessment_qa_start,
+        &mut stage_dates.snapshot_start,
+        &mut stage_dates.voting_start,
+        &mut stage_dates.voting_end,
+        &mut stage_dates.tallying_end,
+    ] {
+        *time += secs;
+    }
+}
+
+#[test]
+pub fn export_and_load_past_data_fund_round_trip() {
+    const SIXTY_DAYS: i64 = 60 * 24 * 3600;
+
+    let temp_dir = TempDir::new().unwrap().into_persistent();
+    let db_file = temp_dir.child("db.sqlite");
+    let snapshot = ArbitrarySnapshotGenerator::default().snapshot();
+
+    // the voteplans belong to a fund that already ended
+    let mut past_fund = snapshot.funds()[0].clone();
+    shift_fund(&mut past_fund, -SIXTY_DAYS);
+    let voteplans = snapshot
+        .voteplans()
+        .iter()
+        .take(1)
+        .cloned()
+        .map(|mut voteplan| {
+            voteplan.chain_vote_start_time -= SIXTY_DAYS;
+            voteplan.chain_vote_end_time -= SIXTY_DAYS;
+            voteplan.chain_committee_end_time -= SIXTY_DAYS;
+            voteplan
+        })
+        .collect();
+    load_snapshot(
+        &temp_dir,
+        db_file.path(),
+        &snapshot,
+        vec![past_fund.clone()],
+        voteplans,
+    );
+
+    // while the next fund has not started yet
+    let mut next_fund = past_fund.clone();
+    next_fund.id = past_fund.id.checked_add(1).unwrap_or(past_fund.id - 1);
+    next_fund.fund_name = format!("{} next", past_fund.fund_name);
+    next_fund.chain_vote_plans.clear();
+    next_fund.goals.clear();
+    shift_fund(&mut next_fund, 2 * SIXTY_DAYS);
+    let connection = SqliteConnection::establish(db_file.path().to_str().unwrap()).unwrap();
+    DbInserter::new(&connection)
+        .insert_funds(&[next_fund])
+        .unwrap();
+
+    let exported = temp_dir.child("exported");
+    export(db_file.path(), exported.path(), "csv");
+
+    let reloaded_db_file = temp_dir.child("reloaded.sqlite");
+    init_db(reloaded_db_file.path());
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .csv_data()
+        .load()
+        .db_url(reloaded_db_file.path())
+        .funds(exported.child("funds.csv").path())
+        .proposals(exported.child("proposals.csv").path())
+        .voteplans(exported.child("voteplans.csv").path())
+        .challenges(exported.child("challenges.csv").path())
+        .advisor_reviews(exported.child("reviews.csv").path())
+        .goals(exported.child("goals.csv").path())
+        .groups(exported.child("groups.csv").path())
+        .build()
+        .assert()
+        .success();
+
+    let reexported = temp_dir.child("reexported");
+    export(reloaded_db_file.path(), reexported.path(), "csv");
+
+    for file in [
+        "funds.csv",
+        "voteplans.csv",
+        "proposals.csv",
+        "challenges.csv",
+        "reviews.csv",
+        "goals.csv",
+        "groups.csv",
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(exported.child(file).path()).unwrap(),
+            std::fs::read_to_string(reexported.child(file).path()).unwrap(),
+            "{} differs after the round trip",
+            file
+        );
+    }
+}
+
+#[test]
+pub fn validate_fund_schedule() {
+    let temp_dir = TempDir::new().unwrap().into_persistent();
+    let snapshot = ArbitrarySnapshotGenerator::default().snapshot();
+    let csv_converter = CsvConverter;
+
+    let funds = temp_dir.child("funds.csv");
+    let voteplans = temp_dir.child("voteplans.csv");
+    let challenges = temp_dir.child("challenges.csv");
+    csv_converter
+        .voteplans(
+            snapshot.voteplans().iter().take(1).cloned().collect(),
+            voteplans.path(),
+        )
+        .unwrap();
+
+    let validate = || {
+        let vit_cli: VitCliCommand = Default::default();
+        vit_cli
+            .csv_data()
+            .validate()
+            .funds(funds.path())
+            .voteplans(voteplans.path())
+            .challenges(challenges.path())
+            .build()
+            .assert()
+    };
+
+    csv_converter.funds(snapshot.funds(), funds.path()).unwrap();
+    csv_converter
+        .challenges(
+            snapshot.challenges().iter().take(1).cloned().collect(),
+            challenges.path(),
+        )
+        .unwrap();
+    validate().success();
+
+    let mut invalid_funds = snapshot.funds();
+    let stage_dates = &mut invalid_funds[0].stage_dates;
+    stage_dates.voting_end = stage_dates.voting_start - 1;
+    csv_converter.funds(invalid_funds, funds.path()).unwrap();
+    let mut invalid_challenge = snapshot.challenges()[0].clone();
+    invalid_challenge.proposers_rewards = invalid_challenge.rewards_total + 1;
+    csv_converter
+        .challenges(vec![invalid_challenge], challenges.path())
+        .unwrap();
+
+    validate()
+        .failure()
+        .stdout(predicate::str::contains("funds.csv row 1"))
+        .stdout(predicate::str::contains("voting_end"))
+        .stdout(predicate::str::contains("challenges.csv row 1"))
+        .stdout(predicate::str::contains("proposers_rewards"));
+}
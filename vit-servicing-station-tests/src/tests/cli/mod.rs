@@ -1,3 +1,4 @@
 pub mod db;
 pub mod load;
+pub mod snapshot;
 pub mod token;
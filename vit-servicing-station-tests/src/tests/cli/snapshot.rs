@@ -0,0 +1,74 @@
+use crate::common::cli::VitCliCommand;
+use assert_cmd::assert::OutputAssertExt;
+use assert_fs::{fixture::PathChild, TempDir};
+use diesel::{Connection, QueryDsl, RunQueryDsl, SqliteConnection};
+use predicates::prelude::*;
+use vit_servicing_station_lib::db::schema::{contributions, snapshots, voters};
+
+#[test]
+pub fn delete_snapshot_tag() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_file = temp_dir.child("db.sqlite");
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .db()
+        .init()
+        .db_url(db_file.path())
+        .build()
+        .assert()
+        .success();
+
+    let db_conn = SqliteConnection::establish(db_file.path().to_str().unwrap()).unwrap();
+    for tag in ["tag_a", "tag_b"] {
+        diesel::sql_query(format!(
+            "INSERT INTO snapshots (tag, last_updated) VALUES ('{}', 0)",
+            tag
+        ))
+        .execute(&db_conn)
+        .unwrap();
+        diesel::sql_query(format!(
+            "INSERT INTO voters (voting_key, voting_power, voting_group, snapshot_tag) \
+             VALUES ('voting_key', 1, 'direct', '{}')",
+            tag
+        ))
+        .execute(&db_conn)
+        .unwrap();
+        diesel::sql_query(format!(
+            "INSERT INTO contributions \
+             (stake_public_key, reward_address, value, voting_key, voting_group, snapshot_tag) \
+             VALUES ('stake_public_key', 'reward_address', 1, 'voting_key', 'direct', '{}')",
+            tag
+        ))
+        .execute(&db_conn)
+        .unwrap();
+    }
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .snapshot()
+        .delete()
+        .db_url(db_file.path())
+        .tag("tag_a")
+        .build()
+        .assert()
+        .success();
+
+    let snapshots_count: i64 = snapshots::table.count().get_result(&db_conn).unwrap();
+    let voters_count: i64 = voters::table.count().get_result(&db_conn).unwrap();
+    let contributions_count: i64 = contributions::table.count().get_result(&db_conn).unwrap();
+    assert_eq!(
+        (snapshots_count, voters_count, contributions_count),
+        (1, 1, 1)
+    );
+
+    let vit_cli: VitCliCommand = Default::default();
+    vit_cli
+        .snapshot()
+        .delete()
+        .db_url(db_file.path())
+        .tag("tag_a")
+        .build()
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("tag_a not found"));
+}
@@ -1,12 +1,12 @@
 use super::MockStatusProvider;
 use crate::common::{
     load::{VitRestRequestGenerator, VotingPowerRequestGenerator},
-    snapshot::{SnapshotBuilder, SnapshotUpdater},
+    snapshot::{Snapshot, SnapshotBuilder, SnapshotUpdater},
     startup::quick_start,
 };
 use assert_fs::TempDir;
 use jortestkit::load::{self, ConfigurationBuilder, Monitor};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[test]
 pub fn update_snapshot_during_the_load_quick() {
@@ -67,6 +67,68 @@ pub fn rest_snapshot_load_quick() {
     assert!((stats.calculate_passrate() as u32) > 95);
 }
 
+/// Voter info requests must not depend on the size of the voting group of the voter. Voter
+/// info used to be computed from every voter of the group, so its latency grew with the group.
+#[test]
+pub fn voter_info_latency_with_large_voting_group() {
+    const REQUESTS: u32 = 500;
+    // both groups are served in the same order of magnitude of time, even though the large
+    // one holds 200 times more voters
+    const MAX_SLOWDOWN: u32 = 3;
+
+    let temp_dir = TempDir::new().unwrap();
+    let (server, data) = quick_start(&temp_dir).unwrap();
+    let mut rest_client = server.rest_client_with_token(&data.token_hash());
+
+    let small_group = SnapshotBuilder::default()
+        .with_tag("small")
+        .with_groups(vec!["direct"])
+        .with_entries_count(100)
+        .build();
+    let large_group = SnapshotBuilder::default()
+        .with_tag("large")
+        .with_groups(vec!["direct"])
+        .with_entries_count(20_000)
+        .build();
+    rest_client.put_snapshot_info(&small_group).unwrap();
+    rest_client.put_snapshot_info(&large_group).unwrap();
+    rest_client.disable_log();
+
+    let average_latency = |snapshot: &Snapshot| {
+        let started = Instant::now();
+        for entry in snapshot
+            .content
+            .snapshot
+            .iter()
+            .cycle()
+            .take(REQUESTS as usize)
+        {
+            let voter_info = rest_client
+                .voter_info(&snapshot.tag, &entry.hir.voting_key.to_hex())
+                .unwrap();
+            assert!(!voter_info.voter_info.is_empty());
+        }
+        started.elapsed() / REQUESTS
+    };
+    let small_group_latency = average_latency(&small_group);
+    let large_group_latency = average_latency(&large_group);
+    assert!(
+        large_group_latency < small_group_latency * MAX_SLOWDOWN,
+        "average voter info latency {:?} with a large voting group, {:?} with a small one",
+        large_group_latency,
+        small_group_latency
+    );
+
+    let request = VotingPowerRequestGenerator::new(large_group, rest_client);
+    let config = ConfigurationBuilder::duration(Duration::from_secs(20))
+        .thread_no(10)
+        .step_delay(Duration::from_millis(500))
+        .monitor(Monitor::Progress(100))
+        .build();
+    let stats = load::start_sync(request, config, "Vit station voter info rest");
+    assert!((stats.calculate_passrate() as u32) > 95);
+}
+
 #[test]
 pub fn rest_load_quick() {
     let temp_dir = TempDir::new().unwrap();